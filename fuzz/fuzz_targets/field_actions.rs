@@ -0,0 +1,47 @@
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+use minesweeper::model::Field;
+
+/**
+ * Plays random moves on a small board and checks that its counts still
+ * agree with its zones after every one of them (see `Field::check_invariants`).
+ * The first four bytes choose the board, and every three bytes after them
+ * make a move: which one, then its x and y, so that some fall out of bounds.
+ */
+fn play(data: &[u8]) {
+    if data.len() < 4 {
+        return;
+    }
+    let (board, moves) = data.split_at(4);
+    let width = board[0] as u32 % 16 + 1;
+    let height = board[1] as u32 % 16 + 1;
+    let num_mines = board[2] as u32 % (width * height + 1);
+    let field = if board[3] % 2 == 0 {
+        Field::new_with_seed(width, height, num_mines, board[3] as u64)
+    } else {
+        Field::new_deferred_with_seed(width, height, num_mines, board[3] as u64)
+    };
+    // too many mines for the board
+    let mut field = match field {
+        Ok(field) => field,
+        Err(_) => return,
+    };
+    for action in moves.chunks_exact(3) {
+        let (x, y) = (action[1] as u32 % (width + 2), action[2] as u32 % (height + 2));
+        // failing moves must leave the counts alone too
+        let _ = match action[0] % 6 {
+            0 => field.reveal_at(x, y).map(drop),
+            1 => field.reveal_area(x, y).map(drop),
+            2 => field.change_flag_at(x, y, true),
+            3 => field.change_flag_at(x, y, false),
+            4 => field.cycle_mark_at(x, y).map(drop),
+            _ => field.add_mine_at(x, y),
+        };
+        if let Err(violation) = field.check_invariants() {
+            panic!("{} after {:?}", violation, action);
+        }
+    }
+}
+
+fuzz_target!(|data: &[u8]| play(data));
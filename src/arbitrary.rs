@@ -0,0 +1,249 @@
+use crate::model::{Field, ModelResult};
+use alloc::format;
+use alloc::string::{String, ToString};
+use alloc::vec::Vec;
+use rand::{Rng, SeedableRng};
+use rand_chacha::ChaCha8Rng;
+use std::panic::{self, AssertUnwindSafe};
+
+#[derive(Debug, Clone, Eq, PartialEq)]
+/**
+ * A board for a property test, which always builds into a valid Field
+ * (see `to_field`), with consistent adjacent mine counts
+ * `mines` holds the (x, y) of every mine, each within the dimensions
+ */
+pub struct ArbitraryBoard {
+    pub width: u32,
+    pub height: u32,
+    pub mines: Vec<(u32, u32)>,
+}
+
+impl ArbitraryBoard {
+    /**
+     * A board from 1 by 1 up to max_size by max_size, each zone holding
+     * a mine with a probability chosen at random too
+     */
+    pub fn generate(rng: &mut impl Rng, max_size: u32) -> Self {
+        let width = rng.gen_range(1, max_size.max(1) + 1);
+        let height = rng.gen_range(1, max_size.max(1) + 1);
+        let density: f64 = rng.gen();
+        let positions = (0..height).flat_map(|y| (0..width).map(move |x| (x, y)));
+        let mines = positions.filter(|_| rng.gen_bool(density)).collect();
+        ArbitraryBoard {
+            width,
+            height,
+            mines,
+        }
+    }
+
+    pub fn to_field(&self) -> Field {
+        Field::with_mine_placements(self.width, self.height, self.mines.iter().copied())
+            .expect("an ArbitraryBoard is always a valid Field")
+    }
+
+    /**
+     * Smaller boards, the smallest first: without the last column, without
+     * the last row, and then without each mine in turn
+     */
+    pub fn shrink(&self) -> Vec<Self> {
+        let mut smaller = Vec::new();
+        if self.width > 1 {
+            smaller.push(self.cropped(self.width - 1, self.height));
+        }
+        if self.height > 1 {
+            smaller.push(self.cropped(self.width, self.height - 1));
+        }
+        for index in 0..self.mines.len() {
+            let mut fewer_mines = self.clone();
+            fewer_mines.mines.remove(index);
+            smaller.push(fewer_mines);
+        }
+        smaller
+    }
+
+    fn cropped(&self, width: u32, height: u32) -> Self {
+        ArbitraryBoard {
+            width,
+            height,
+            mines: self
+                .mines
+                .iter()
+                .copied()
+                .filter(|&(x, y)| x < width && y < height)
+                .collect(),
+        }
+    }
+}
+
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+/**
+ * A move for a property test, made on a Field by `apply`
+ * `Reveal` is `Field::reveal_at`, `RevealArea` is `Field::reveal_area`,
+ * `Flag` and `Unflag` are `Field::change_flag_at`, and `Mark` is
+ * `Field::cycle_mark_at`, each at the contained coordinates
+ */
+pub enum ArbitraryMove {
+    Reveal(u32, u32),
+    RevealArea(u32, u32),
+    Flag(u32, u32),
+    Unflag(u32, u32),
+    Mark(u32, u32),
+}
+
+impl ArbitraryMove {
+    /**
+     * A move at coordinates within the given dimensions. Moves which aren't
+     * allowed where they are made, like flagging a revealed zone, are legal
+     * all the same: they fail without changing anything.
+     */
+    pub fn generate(rng: &mut impl Rng, width: u32, height: u32) -> Self {
+        let (x, y) = (rng.gen_range(0, width), rng.gen_range(0, height));
+        match rng.gen_range(0, 5) {
+            0 => ArbitraryMove::Reveal(x, y),
+            1 => ArbitraryMove::RevealArea(x, y),
+            2 => ArbitraryMove::Flag(x, y),
+            3 => ArbitraryMove::Unflag(x, y),
+            _ => ArbitraryMove::Mark(x, y),
+        }
+    }
+
+    pub fn position(self) -> (u32, u32) {
+        match self {
+            ArbitraryMove::Reveal(x, y)
+            | ArbitraryMove::RevealArea(x, y)
+            | ArbitraryMove::Flag(x, y)
+            | ArbitraryMove::Unflag(x, y)
+            | ArbitraryMove::Mark(x, y) => (x, y),
+        }
+    }
+
+    pub fn apply(self, field: &mut Field) -> ModelResult<()> {
+        match self {
+            ArbitraryMove::Reveal(x, y) => field.reveal_at(x, y).map(drop),
+            ArbitraryMove::RevealArea(x, y) => field.reveal_area(x, y).map(drop),
+            ArbitraryMove::Flag(x, y) => field.change_flag_at(x, y, true),
+            ArbitraryMove::Unflag(x, y) => field.change_flag_at(x, y, false),
+            ArbitraryMove::Mark(x, y) => field.cycle_mark_at(x, y).map(drop),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Eq, PartialEq)]
+/**
+ * A board and the moves to make on it, in order, all within its dimensions
+ */
+pub struct ArbitraryGame {
+    pub board: ArbitraryBoard,
+    pub moves: Vec<ArbitraryMove>,
+}
+
+impl ArbitraryGame {
+    pub fn generate(rng: &mut impl Rng, max_size: u32, max_moves: usize) -> Self {
+        let board = ArbitraryBoard::generate(rng, max_size);
+        let num_moves = rng.gen_range(0, max_moves + 1);
+        let moves = (0..num_moves)
+            .map(|_| ArbitraryMove::generate(rng, board.width, board.height))
+            .collect();
+        ArbitraryGame { board, moves }
+    }
+
+    /**
+     * Smaller games, the smallest first: with the first half of the moves,
+     * without each move in turn, and then on each smaller board (see
+     * `ArbitraryBoard::shrink`), leaving out the moves that fall off it
+     */
+    pub fn shrink(&self) -> Vec<Self> {
+        let mut smaller = Vec::new();
+        if self.moves.len() > 1 {
+            smaller.push(ArbitraryGame {
+                board: self.board.clone(),
+                moves: self.moves[..self.moves.len() / 2].to_vec(),
+            });
+        }
+        for index in 0..self.moves.len() {
+            let mut fewer_moves = self.clone();
+            fewer_moves.moves.remove(index);
+            smaller.push(fewer_moves);
+        }
+        for board in self.board.shrink() {
+            let moves = self
+                .moves
+                .iter()
+                .copied()
+                .filter(|m| m.position().0 < board.width && m.position().1 < board.height)
+                .collect();
+            smaller.push(ArbitraryGame { board, moves });
+        }
+        smaller
+    }
+
+    /**
+     * Plays the moves on a Field built from the board, checking the Field's
+     * invariants (see `Field::check_invariants`) after each of them.
+     * A property for `check`.
+     */
+    pub fn keeps_invariants(&self) -> Result<(), String> {
+        let mut field = self.board.to_field();
+        for (index, &m) in self.moves.iter().enumerate() {
+            // a move which isn't allowed must change nothing either
+            let _ = m.apply(&mut field);
+            field
+                .check_invariants()
+                .map_err(|violation| format!("{} after move {}, {:?}", violation, index, m))?;
+        }
+        Ok(())
+    }
+}
+
+#[derive(Debug, Clone, Eq, PartialEq)]
+/**
+ * Produced by `check` when a property fails
+ * `game` is the smallest failing game shrinking found,
+ * and `reason` is why it failed, or the panic message if it panicked
+ */
+pub struct PropertyFailure {
+    pub game: ArbitraryGame,
+    pub reason: String,
+}
+
+/**
+ * Checks a property against `cases` games, of boards up to max_size by
+ * max_size and up to max_moves moves, generated from the given seed so that
+ * a failure can be reproduced. A property fails by returning Err or by
+ * panicking. The first failing game is shrunk for as long as a smaller game
+ * (see `ArbitraryGame::shrink`) fails too.
+ */
+pub fn check(
+    cases: u32,
+    seed: u64,
+    max_size: u32,
+    max_moves: usize,
+    property: impl Fn(&ArbitraryGame) -> Result<(), String>,
+) -> Result<(), PropertyFailure> {
+    let mut rng = ChaCha8Rng::seed_from_u64(seed);
+    let run = |game: &ArbitraryGame| match panic::catch_unwind(AssertUnwindSafe(|| property(game)))
+    {
+        Ok(result) => result,
+        Err(payload) => Err(payload
+            .downcast_ref::<String>()
+            .cloned()
+            .or_else(|| payload.downcast_ref::<&str>().map(|s| s.to_string()))
+            .unwrap_or_else(|| "panicked".to_string())),
+    };
+    for _ in 0..cases {
+        let game = ArbitraryGame::generate(&mut rng, max_size, max_moves);
+        if let Err(reason) = run(&game) {
+            let mut failure = PropertyFailure { game, reason };
+            while let Some((game, reason)) = failure
+                .game
+                .shrink()
+                .into_iter()
+                .find_map(|smaller| run(&smaller).err().map(|reason| (smaller, reason)))
+            {
+                failure = PropertyFailure { game, reason };
+            }
+            return Err(failure);
+        }
+    }
+    Ok(())
+}
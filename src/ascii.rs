@@ -0,0 +1,200 @@
+use crate::model::{CreationError, Mark};
+use alloc::vec::Vec;
+use core::convert::TryFrom;
+use core::fmt;
+
+/**
+ * The first line of a player's view of a Field (see `Field::to_ascii`),
+ * which sets it apart from a whole board
+ */
+pub const VIEW_HEADER: &str = "!view";
+
+#[derive(Debug, Clone, Eq, PartialEq)]
+/**
+ * Produced when text could not be parsed as a Field (see `Field::from_str`)
+ * Lines and columns are counted from 1.
+ * `Empty` indicates that there were no rows
+ * `PlayerView` indicates that the text was a player's view of a board
+ *     (see `VIEW_HEADER`), which doesn't say where the mines are
+ * `UnexpectedChar` indicates that `found` is not the symbol of any zone
+ * `RaggedRow` indicates that the row on `line` was `found` zones long,
+ *     rather than as long as the first row
+ * `Creation` indicates that the rows could not be turned into a Field,
+ *     for the contained reason
+ */
+pub enum AsciiError {
+    Empty,
+    PlayerView,
+    UnexpectedChar {
+        line: usize,
+        column: usize,
+        found: char,
+    },
+    RaggedRow {
+        line: usize,
+        expected: usize,
+        found: usize,
+    },
+    Creation(CreationError),
+}
+
+impl fmt::Display for AsciiError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            AsciiError::Empty => write!(f, "the board has no rows"),
+            AsciiError::PlayerView => {
+                write!(f, "a player's view doesn't say where the mines are")
+            }
+            AsciiError::UnexpectedChar {
+                line,
+                column,
+                found,
+            } => write!(
+                f,
+                "line {}, column {}: {:?} is not a zone",
+                line, column, found
+            ),
+            AsciiError::RaggedRow {
+                line,
+                expected,
+                found,
+            } => write!(
+                f,
+                "line {}: the row is {} zones long, but the first is {}",
+                line, found, expected
+            ),
+            AsciiError::Creation(e) => write!(f, "could not create the board: {}", e),
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for AsciiError {}
+
+impl From<CreationError> for AsciiError {
+    fn from(e: CreationError) -> Self {
+        AsciiError::Creation(e)
+    }
+}
+
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+/**
+ * A zone of a board drawn as text, one character each:
+ * `#` for a position masked out of the board
+ * `.` and `*` for a hidden zone, without and with a mine
+ * `f` and `F` for a flagged zone, without and with a mine
+ * `q` and `Q` for a questioned zone, without and with a mine
+ * `r` for a revealed zone without a mine
+ * `R` for a revealed mine, and `X` for the one that exploded
+ *     (see `Field::exploded_at`)
+ */
+pub enum AsciiZone {
+    Masked,
+    Hidden { mine: bool, mark: Mark },
+    Revealed,
+    RevealedMine { exploded: bool },
+}
+
+impl AsciiZone {
+    pub fn symbol(self) -> char {
+        match self {
+            AsciiZone::Masked => '#',
+            AsciiZone::Hidden { mine, mark } => match (mark, mine) {
+                (Mark::Unmarked, false) => '.',
+                (Mark::Unmarked, true) => '*',
+                (Mark::Flagged, false) => 'f',
+                (Mark::Flagged, true) => 'F',
+                (Mark::Questioned, false) => 'q',
+                (Mark::Questioned, true) => 'Q',
+            },
+            AsciiZone::Revealed => 'r',
+            AsciiZone::RevealedMine { exploded: false } => 'R',
+            AsciiZone::RevealedMine { exploded: true } => 'X',
+        }
+    }
+
+    pub fn from_symbol(symbol: char) -> Option<Self> {
+        let hidden = |mine, mark| Some(AsciiZone::Hidden { mine, mark });
+        match symbol {
+            '#' => Some(AsciiZone::Masked),
+            '.' => hidden(false, Mark::Unmarked),
+            '*' => hidden(true, Mark::Unmarked),
+            'f' => hidden(false, Mark::Flagged),
+            'F' => hidden(true, Mark::Flagged),
+            'q' => hidden(false, Mark::Questioned),
+            'Q' => hidden(true, Mark::Questioned),
+            'r' => Some(AsciiZone::Revealed),
+            'R' => Some(AsciiZone::RevealedMine { exploded: false }),
+            'X' => Some(AsciiZone::RevealedMine { exploded: true }),
+            _ => None,
+        }
+    }
+
+    pub fn has_mine(self) -> bool {
+        match self {
+            AsciiZone::Masked | AsciiZone::Revealed => false,
+            AsciiZone::Hidden { mine, .. } => mine,
+            AsciiZone::RevealedMine { .. } => true,
+        }
+    }
+}
+
+/**
+ * The zones of a board drawn as text
+ */
+pub struct AsciiBoard {
+    pub width: u32,
+    pub height: u32,
+    /**
+     * every zone, in row-major order
+     */
+    pub zones: Vec<AsciiZone>,
+}
+
+/**
+ * Reads a board drawn one row per line, in the symbols of `AsciiZone`.
+ * A final line break is optional.
+ */
+pub fn read_ascii(text: &str) -> Result<AsciiBoard, AsciiError> {
+    let mut lines = text.lines().peekable();
+    if lines.peek() == Some(&VIEW_HEADER) {
+        return Err(AsciiError::PlayerView);
+    }
+    let mut zones = Vec::new();
+    let mut width = None;
+    let mut height = 0;
+    for (index, line) in lines.enumerate() {
+        let row_start = zones.len();
+        for (column, symbol) in line.chars().enumerate() {
+            let zone = AsciiZone::from_symbol(symbol).ok_or(AsciiError::UnexpectedChar {
+                line: index + 1,
+                column: column + 1,
+                found: symbol,
+            })?;
+            zones.push(zone);
+        }
+        let found = zones.len() - row_start;
+        match width {
+            None => width = Some(found),
+            Some(expected) if expected != found => {
+                return Err(AsciiError::RaggedRow {
+                    line: index + 1,
+                    expected,
+                    found,
+                })
+            }
+            Some(_) => (),
+        }
+        height += 1;
+    }
+    let width = width.ok_or(AsciiError::Empty)?;
+    let too_large = || CreationError::TooLarge {
+        width: u32::try_from(width).unwrap_or(u32::MAX),
+        height: u32::try_from(height).unwrap_or(u32::MAX),
+    };
+    Ok(AsciiBoard {
+        width: u32::try_from(width).map_err(|_| too_large())?,
+        height: u32::try_from(height).map_err(|_| too_large())?,
+        zones,
+    })
+}
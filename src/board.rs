@@ -0,0 +1,304 @@
+use crate::chunked::ChunkedField;
+use crate::model::{ErrorKind, Field, Mark, ModelResult, Operation};
+use alloc::vec::Vec;
+use core::fmt;
+use core::str::FromStr;
+
+/**
+ * What a MinesweeperController needs of the board it is played on: moves
+ * that reveal and flag, which positions are adjacent, and what state the
+ * board is in. Implemented by Field, and by the endless ChunkedField.
+ * Each method does what the Field method of the same name does, on
+ * coordinates of the board's own `Coord` type.
+ */
+pub trait Board {
+    /**
+     * The type of a single coordinate: u32 for a Field, and i64 for a
+     * ChunkedField, which extends in every direction
+     */
+    type Coord: Copy + Ord + fmt::Debug + fmt::Display + FromStr;
+
+    /**
+     * Ok if the given coordinates are on the board, and otherwise the
+     * ErrorKind::OutOfBounds that any move there fails with
+     */
+    fn check_bounds(&self, x: Self::Coord, y: Self::Coord) -> ModelResult<()>;
+
+    /**
+     * Reveals the given position, cascading through the positions around
+     * it if it has no adjacent mines. Fails with NoOp if it was already
+     * revealed, or with Flagged if it has a flag.
+     * On success, returns every position revealed, in order.
+     */
+    fn reveal_area(
+        &mut self,
+        x: Self::Coord,
+        y: Self::Coord,
+    ) -> ModelResult<Vec<(Self::Coord, Self::Coord)>>;
+
+    fn change_flag_at(&mut self, x: Self::Coord, y: Self::Coord, flag: bool) -> ModelResult<()>;
+
+    /**
+     * Moves the mark at the given position one step along the cycle of
+     * marks the board has, returning the new mark
+     */
+    fn cycle_mark_at(&mut self, x: Self::Coord, y: Self::Coord) -> ModelResult<Mark>;
+
+    fn is_revealed_at(&self, x: Self::Coord, y: Self::Coord) -> Option<bool>;
+
+    fn is_flagged_at(&self, x: Self::Coord, y: Self::Coord) -> Option<bool>;
+
+    fn has_mine_at(&self, x: Self::Coord, y: Self::Coord) -> Option<bool>;
+
+    fn mines_adjacent_to(&self, x: Self::Coord, y: Self::Coord) -> Option<u32>;
+
+    /**
+     * The positions on the board adjacent to the given one
+     */
+    fn neighbors(&self, x: Self::Coord, y: Self::Coord) -> Vec<(Self::Coord, Self::Coord)>;
+
+    /**
+     * Every position with a flag on it
+     */
+    fn flagged_positions(&self) -> Vec<(Self::Coord, Self::Coord)>;
+
+    fn num_flagged(&self) -> u32;
+
+    fn is_placement_pending(&self) -> bool;
+
+    fn any_mine_revealed(&self) -> bool;
+
+    /**
+     * The mine that exploded, or failing that the first mine to be revealed
+     */
+    fn first_revealed_mine(&self) -> Option<(Self::Coord, Self::Coord)>;
+
+    fn is_cleared(&self) -> bool;
+
+    /**
+     * The number of zones with a mine, every one of which is to be flagged
+     * to win by flagging, or None if there is no telling: before the mines
+     * are placed, or when there is no end to them
+     */
+    fn num_mined_zones(&self) -> Option<u32>;
+
+    fn num_exposed(&self) -> u32;
+
+    fn num_flagged_exposed(&self) -> u32;
+
+    fn reveal_mines(&mut self);
+
+    fn flag_all_mines(&mut self);
+
+    fn reveal_safe_zones(&mut self);
+}
+
+impl Board for Field {
+    type Coord = u32;
+
+    fn check_bounds(&self, x: u32, y: u32) -> ModelResult<()> {
+        Field::check_bounds(self, x, y)
+    }
+
+    fn reveal_area(&mut self, x: u32, y: u32) -> ModelResult<Vec<(u32, u32)>> {
+        Field::reveal_area(self, x, y)
+    }
+
+    fn change_flag_at(&mut self, x: u32, y: u32, flag: bool) -> ModelResult<()> {
+        Field::change_flag_at(self, x, y, flag)
+    }
+
+    fn cycle_mark_at(&mut self, x: u32, y: u32) -> ModelResult<Mark> {
+        Field::cycle_mark_at(self, x, y)
+    }
+
+    fn is_revealed_at(&self, x: u32, y: u32) -> Option<bool> {
+        Field::is_revealed_at(self, x, y)
+    }
+
+    fn is_flagged_at(&self, x: u32, y: u32) -> Option<bool> {
+        Field::is_flagged_at(self, x, y)
+    }
+
+    fn has_mine_at(&self, x: u32, y: u32) -> Option<bool> {
+        Field::has_mine_at(self, x, y)
+    }
+
+    fn mines_adjacent_to(&self, x: u32, y: u32) -> Option<u32> {
+        Field::mines_adjacent_to(self, x, y)
+    }
+
+    fn neighbors(&self, x: u32, y: u32) -> Vec<(u32, u32)> {
+        self.adjacent_positions(x, y, true)
+    }
+
+    fn flagged_positions(&self) -> Vec<(u32, u32)> {
+        self.active_positions()
+            .filter(|&(x, y)| Field::is_flagged_at(self, x, y).unwrap())
+            .collect()
+    }
+
+    fn num_flagged(&self) -> u32 {
+        Field::num_flagged(self)
+    }
+
+    fn is_placement_pending(&self) -> bool {
+        Field::is_placement_pending(self)
+    }
+
+    fn any_mine_revealed(&self) -> bool {
+        Field::any_mine_revealed(self)
+    }
+
+    /**
+     * A Field restored with mines revealed but none exploded (see
+     * `Field::read_save`) falls back on the first mine revealed
+     */
+    fn first_revealed_mine(&self) -> Option<(u32, u32)> {
+        if !Field::any_mine_revealed(self) {
+            return None;
+        }
+        self.exploded_at().or_else(|| {
+            self.revealed_in_order()
+                .find(|&(x, y)| Field::has_mine_at(self, x, y).unwrap())
+        })
+    }
+
+    fn is_cleared(&self) -> bool {
+        Field::is_cleared(self)
+    }
+
+    fn num_mined_zones(&self) -> Option<u32> {
+        if Field::is_placement_pending(self) {
+            None
+        } else {
+            Some(Field::num_mined_zones(self))
+        }
+    }
+
+    fn num_exposed(&self) -> u32 {
+        Field::num_exposed(self)
+    }
+
+    fn num_flagged_exposed(&self) -> u32 {
+        Field::num_flagged_exposed(self)
+    }
+
+    fn reveal_mines(&mut self) {
+        Field::reveal_mines(self)
+    }
+
+    fn flag_all_mines(&mut self) {
+        Field::flag_all_mines(self)
+    }
+
+    fn reveal_safe_zones(&mut self) {
+        Field::reveal_safe_zones(self)
+    }
+}
+
+/**
+ * A ChunkedField has no edges, and no end to its mines: it can't be cleared
+ * or won, only lost. Reveals cascade no further than its reveal budget
+ * (see `ChunkedField::set_reveal_budget`), and there are no question marks.
+ */
+impl Board for ChunkedField {
+    type Coord = i64;
+
+    fn check_bounds(&self, _: i64, _: i64) -> ModelResult<()> {
+        Ok(())
+    }
+
+    fn reveal_area(&mut self, x: i64, y: i64) -> ModelResult<Vec<(i64, i64)>> {
+        let budget = self.reveal_budget();
+        let (_, revealed) = self.reveal_from(x, y, budget)?;
+        Ok(revealed)
+    }
+
+    fn change_flag_at(&mut self, x: i64, y: i64, flag: bool) -> ModelResult<()> {
+        ChunkedField::change_flag_at(self, x, y, flag)
+    }
+
+    /**
+     * Flags an unmarked position, and removes the flag of a flagged one
+     */
+    fn cycle_mark_at(&mut self, x: i64, y: i64) -> ModelResult<Mark> {
+        if ChunkedField::is_revealed_at(self, x, y) {
+            return Err(ErrorKind::NoOp {
+                op: Operation::Mark,
+            });
+        }
+        let flag = !ChunkedField::is_flagged_at(self, x, y);
+        ChunkedField::change_flag_at(self, x, y, flag)?;
+        Ok(if flag { Mark::Flagged } else { Mark::Unmarked })
+    }
+
+    fn is_revealed_at(&self, x: i64, y: i64) -> Option<bool> {
+        Some(ChunkedField::is_revealed_at(self, x, y))
+    }
+
+    fn is_flagged_at(&self, x: i64, y: i64) -> Option<bool> {
+        Some(ChunkedField::is_flagged_at(self, x, y))
+    }
+
+    fn has_mine_at(&self, x: i64, y: i64) -> Option<bool> {
+        Some(ChunkedField::has_mine_at(self, x, y))
+    }
+
+    fn mines_adjacent_to(&self, x: i64, y: i64) -> Option<u32> {
+        Some(ChunkedField::mines_adjacent_to(self, x, y))
+    }
+
+    fn neighbors(&self, x: i64, y: i64) -> Vec<(i64, i64)> {
+        ChunkedField::adjacent_positions(x, y)
+    }
+
+    fn flagged_positions(&self) -> Vec<(i64, i64)> {
+        self.flagged_iter().collect()
+    }
+
+    fn num_flagged(&self) -> u32 {
+        ChunkedField::num_flagged(self) as u32
+    }
+
+    fn is_placement_pending(&self) -> bool {
+        false
+    }
+
+    fn any_mine_revealed(&self) -> bool {
+        self.exploded_at().is_some()
+    }
+
+    fn first_revealed_mine(&self) -> Option<(i64, i64)> {
+        self.exploded_at()
+    }
+
+    fn is_cleared(&self) -> bool {
+        false
+    }
+
+    fn num_mined_zones(&self) -> Option<u32> {
+        None
+    }
+
+    fn num_exposed(&self) -> u32 {
+        0
+    }
+
+    fn num_flagged_exposed(&self) -> u32 {
+        0
+    }
+
+    fn reveal_mines(&mut self) {
+        ChunkedField::reveal_mines(self)
+    }
+
+    fn flag_all_mines(&mut self) {
+        ChunkedField::flag_all_mines(self)
+    }
+
+    /**
+     * There is no end to the zones without a mine, so none are revealed
+     */
+    fn reveal_safe_zones(&mut self) {}
+}
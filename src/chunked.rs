@@ -0,0 +1,355 @@
+use crate::model::{CreationError, ErrorKind, ModelResult, Operation};
+use alloc::collections::{BTreeMap, BTreeSet, VecDeque};
+use alloc::vec;
+use alloc::vec::Vec;
+use core::cell::RefCell;
+use rand::{Rng, SeedableRng};
+use rand_chacha::ChaCha8Rng;
+
+/**
+ * A board without edges, made of square chunks whose mines are generated on
+ * first use. Each chunk's layout is determined by the master seed and the
+ * chunk's coordinates alone, so a chunk can be dropped from memory (see
+ * `evict_chunks`) and regenerated later with exactly the same mines.
+ * What the player has revealed or flagged is kept separately, and survives
+ * eviction.
+ * Coordinates can be negative; chunk (0, 0) spans positions (0, 0) through
+ * (chunk_size - 1, chunk_size - 1).
+ */
+pub struct ChunkedField {
+    seed: u64,
+    chunk_size: u32,
+    mines_per_chunk: u32,
+    // mine layouts of the chunks generated so far, indexed by x * chunk_size + y
+    // within the chunk. Behind a RefCell so that queries can generate chunks.
+    chunks: RefCell<BTreeMap<(i64, i64), Vec<bool>>>,
+    revealed: BTreeSet<(i64, i64)>,
+    flagged: BTreeSet<(i64, i64)>,
+    // the first mine revealed by `reveal_at`, see `exploded_at`
+    exploded: Option<(i64, i64)>,
+    // see `set_reveal_budget`
+    reveal_budget: usize,
+}
+
+impl ChunkedField {
+    /**
+     * The most positions a single reveal cascades through, unless set
+     * otherwise (see `set_reveal_budget`)
+     */
+    pub const DEFAULT_REVEAL_BUDGET: usize = 4096;
+
+    /**
+     * Create a new ChunkedField of chunk_size by chunk_size chunks,
+     * each containing exactly mines_per_chunk mines.
+     * The same seed and arguments always produce the same board.
+     */
+    pub fn new(chunk_size: u32, mines_per_chunk: u32, seed: u64) -> Result<Self, CreationError> {
+        if chunk_size == 0 {
+            return Err(CreationError::ZeroWidth);
+        }
+        let capacity = chunk_size
+            .checked_mul(chunk_size)
+            .ok_or(CreationError::TooLarge {
+                width: chunk_size,
+                height: chunk_size,
+            })?;
+        if mines_per_chunk > capacity {
+            return Err(CreationError::TooManyMines {
+                requested: mines_per_chunk,
+                capacity,
+            });
+        }
+        Ok(ChunkedField {
+            seed,
+            chunk_size,
+            mines_per_chunk,
+            chunks: RefCell::new(BTreeMap::new()),
+            revealed: BTreeSet::new(),
+            flagged: BTreeSet::new(),
+            exploded: None,
+            reveal_budget: Self::DEFAULT_REVEAL_BUDGET,
+        })
+    }
+
+    pub fn seed(&self) -> u64 {
+        self.seed
+    }
+
+    pub fn chunk_size(&self) -> u32 {
+        self.chunk_size
+    }
+
+    /**
+     * Decides how many positions a reveal made through the `Board` trait
+     * (see `reveal_from`) may reveal at most
+     */
+    pub fn set_reveal_budget(&mut self, budget: usize) {
+        self.reveal_budget = budget;
+    }
+
+    pub fn reveal_budget(&self) -> usize {
+        self.reveal_budget
+    }
+
+    /**
+     * The coordinates of the chunk containing the given position
+     */
+    pub fn chunk_of(&self, x: i64, y: i64) -> (i64, i64) {
+        let size = self.chunk_size as i64;
+        (x.div_euclid(size), y.div_euclid(size))
+    }
+
+    /**
+     * The number of chunks whose mines are currently held in memory
+     */
+    pub fn num_cached_chunks(&self) -> usize {
+        self.chunks.borrow().len()
+    }
+
+    /**
+     * Drops the mine layouts of every chunk from memory.
+     * They are regenerated, identically, the next time they are needed.
+     */
+    pub fn evict_chunks(&self) {
+        self.chunks.borrow_mut().clear();
+    }
+
+    pub fn has_mine_at(&self, x: i64, y: i64) -> bool {
+        let chunk = self.chunk_of(x, y);
+        let size = self.chunk_size as i64;
+        let index = (x.rem_euclid(size) * size + y.rem_euclid(size)) as usize;
+        let mut chunks = self.chunks.borrow_mut();
+        let mines = chunks
+            .entry(chunk)
+            .or_insert_with(|| self.generate_chunk(chunk));
+        mines[index]
+    }
+
+    /**
+     * The number of mines in the 8 positions surrounding the given one,
+     * generating neighboring chunks if the position is on a chunk's border
+     */
+    pub fn mines_adjacent_to(&self, x: i64, y: i64) -> u32 {
+        Self::adjacent_positions(x, y)
+            .into_iter()
+            .filter(|&(adj_x, adj_y)| self.has_mine_at(adj_x, adj_y))
+            .count() as u32
+    }
+
+    /**
+     * The 8 positions surrounding the given one. There are no edges,
+     * so there are always 8.
+     */
+    pub fn adjacent_positions(x: i64, y: i64) -> Vec<(i64, i64)> {
+        let mut positions = Vec::with_capacity(8);
+        for dx in -1..=1 {
+            for dy in -1..=1 {
+                if (dx, dy) != (0, 0) {
+                    positions.push((x + dx, y + dy));
+                }
+            }
+        }
+        positions
+    }
+
+    pub fn is_revealed_at(&self, x: i64, y: i64) -> bool {
+        self.revealed.contains(&(x, y))
+    }
+
+    pub fn is_flagged_at(&self, x: i64, y: i64) -> bool {
+        self.flagged.contains(&(x, y))
+    }
+
+    /**
+     * The number of positions revealed so far
+     */
+    pub fn num_revealed(&self) -> usize {
+        self.revealed.len()
+    }
+
+    /**
+     * Every position with a flag on it, in no particular order
+     */
+    pub fn flagged_iter(&self) -> impl Iterator<Item = (i64, i64)> + '_ {
+        self.flagged.iter().copied()
+    }
+
+    /**
+     * The number of flags planted so far
+     */
+    pub fn num_flagged(&self) -> usize {
+        self.flagged.len()
+    }
+
+    /**
+     * The position of the mine that blew up: the first position with a mine
+     * to be revealed by `reveal_at` (or `reveal_from`), or None if there is none
+     */
+    pub fn exploded_at(&self) -> Option<(i64, i64)> {
+        self.exploded
+    }
+
+    /**
+     * if `new_flag_value` is true, adds a flag, otherwise removes a flag.
+     * Like `Field::change_flag_at`, fails with NoOp if that changes nothing,
+     * or if trying to flag a revealed position.
+     */
+    pub fn change_flag_at(&mut self, x: i64, y: i64, new_flag_value: bool) -> ModelResult<()> {
+        let changed = if new_flag_value {
+            !self.revealed.contains(&(x, y)) && self.flagged.insert((x, y))
+        } else {
+            self.flagged.remove(&(x, y))
+        };
+        if changed {
+            Ok(())
+        } else if new_flag_value {
+            Err(ErrorKind::NoOp {
+                op: Operation::Flag,
+            })
+        } else {
+            Err(ErrorKind::NoOp {
+                op: Operation::Unflag,
+            })
+        }
+    }
+
+    /**
+     * Reveals the given position alone, returning whether it contains a mine.
+     * Fails with NoOp if it has already been revealed, or like
+     * `Field::reveal_at` with Flagged if it has a flag, which is left in place.
+     */
+    pub fn reveal_at(&mut self, x: i64, y: i64) -> ModelResult<bool> {
+        if self.flagged.contains(&(x, y)) {
+            return Err(ErrorKind::Flagged);
+        }
+        if !self.revealed.insert((x, y)) {
+            return Err(ErrorKind::NoOp {
+                op: Operation::Reveal,
+            });
+        }
+        let has_mine = self.has_mine_at(x, y);
+        if has_mine && self.exploded.is_none() {
+            self.exploded = Some((x, y));
+        }
+        Ok(has_mine)
+    }
+
+    /**
+     * Reveals every mine of the chunks held in memory (see `num_cached_chunks`),
+     * flagged or not, for showing the board around the player once the game
+     * is lost. There is no end to the mines elsewhere.
+     */
+    pub fn reveal_mines(&mut self) {
+        let mines = self.cached_mines();
+        self.revealed.extend(mines);
+    }
+
+    /**
+     * Plants a flag on every hidden mine of the chunks held in memory
+     * (see `reveal_mines`)
+     */
+    pub fn flag_all_mines(&mut self) {
+        let hidden: Vec<_> = self
+            .cached_mines()
+            .into_iter()
+            .filter(|position| !self.revealed.contains(position))
+            .collect();
+        self.flagged.extend(hidden);
+    }
+
+    /**
+     * Reveals the given position, and if it has no adjacent mines, cascades
+     * through the surrounding positions the way `Field::reveal_area` does:
+     * every unflagged neighbor of a revealed position without adjacent mines
+     * is revealed too. An endless board can have endless openings, so at most
+     * `budget` positions are revealed in all; the cascade can be continued
+     * later by revealing from the edge of what was revealed.
+     * Fails just like `reveal_at`, or with NoOp if the budget is 0.
+     * On success, returns whether the given position contains a mine, along
+     * with every position revealed, in the order they were revealed.
+     */
+    pub fn reveal_from(
+        &mut self,
+        x: i64,
+        y: i64,
+        budget: usize,
+    ) -> ModelResult<(bool, Vec<(i64, i64)>)> {
+        if budget == 0 {
+            return Err(ErrorKind::NoOp {
+                op: Operation::Reveal,
+            });
+        }
+        let has_mine = self.reveal_at(x, y)?;
+        let mut revealed = vec![(x, y)];
+        if has_mine {
+            return Ok((true, revealed));
+        }
+        let mut queue = VecDeque::new();
+        queue.push_back((x, y));
+        while let Some((x, y)) = queue.pop_front() {
+            if self.mines_adjacent_to(x, y) != 0 {
+                continue;
+            }
+            for (adj_x, adj_y) in Self::adjacent_positions(x, y) {
+                if revealed.len() == budget {
+                    return Ok((false, revealed));
+                }
+                // flags stop the cascade
+                if self.reveal_at(adj_x, adj_y).is_err() {
+                    continue;
+                }
+                revealed.push((adj_x, adj_y));
+                queue.push_back((adj_x, adj_y));
+            }
+        }
+        Ok((false, revealed))
+    }
+
+    /**
+     * The positions of every mine of the chunks held in memory
+     */
+    fn cached_mines(&self) -> Vec<(i64, i64)> {
+        let size = self.chunk_size as i64;
+        let chunks = self.chunks.borrow();
+        let mut mines = Vec::new();
+        for (&(chunk_x, chunk_y), layout) in chunks.iter() {
+            for (index, _) in layout.iter().enumerate().filter(|&(_, &mine)| mine) {
+                let index = index as i64;
+                mines.push((chunk_x * size + index / size, chunk_y * size + index % size));
+            }
+        }
+        mines
+    }
+
+    /**
+     * Randomly places mines_per_chunk mines in the given chunk,
+     * using a generator seeded from the master seed and the chunk coordinates
+     */
+    fn generate_chunk(&self, chunk: (i64, i64)) -> Vec<bool> {
+        let mut rng = ChaCha8Rng::seed_from_u64(Self::chunk_seed(self.seed, chunk));
+        let num_positions = (self.chunk_size * self.chunk_size) as usize;
+        let mut indices: Vec<usize> = (0..num_positions).collect();
+        let mut mines = vec![false; num_positions];
+        // partial Fisher-Yates shuffle, as in `Field::generate_placements`
+        for i in 0..self.mines_per_chunk as usize {
+            let j = rng.gen_range(i, num_positions);
+            indices.swap(i, j);
+            mines[indices[i]] = true;
+        }
+        mines
+    }
+
+    /**
+     * Mixes the master seed with chunk coordinates (splitmix64), so that
+     * neighboring chunks get unrelated seeds on every platform
+     */
+    fn chunk_seed(seed: u64, chunk: (i64, i64)) -> u64 {
+        fn mix(mut z: u64) -> u64 {
+            z = z.wrapping_add(0x9E37_79B9_7F4A_7C15);
+            z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+            z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+            z ^ (z >> 31)
+        }
+        mix(mix(mix(seed) ^ chunk.0 as u64) ^ chunk.1 as u64)
+    }
+}
@@ -1,51 +1,331 @@
-use crate::model::{ErrorKind::*, MinesweeperModel, ModelResult};
+use crate::board::Board;
+use crate::model::{
+    ErrorKind, ErrorKind::*, Mark, MinesweeperModel, ModelResult, Operation, Position, ZoneState,
+};
+use crate::solver;
+use alloc::format;
+use alloc::string::String;
+use alloc::vec::Vec;
+use std::time::{Duration, Instant};
 
-pub struct MinesweeperController {
-    model: MinesweeperModel,
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+/**
+ * The kinds of moves a player can make on a MinesweeperController
+ */
+pub enum Action {
+    Reveal,
+    Flag,
+    Chord,
+    Mark,
+}
+
+impl Action {
+    /**
+     * The single letter used for this Action in a transcript
+     */
+    pub fn letter(self) -> char {
+        match self {
+            Action::Reveal => 'R',
+            Action::Flag => 'F',
+            Action::Chord => 'C',
+            Action::Mark => 'M',
+        }
+    }
+}
+
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+/**
+ * Where a game stands (see `MinesweeperController::state`)
+ * `InProgress` is a game that can still be played
+ * `Won` is a game won as `MinesweeperController::won` says
+ * `Lost` is a game in which a mine was revealed, `exploded` being the
+ *     position of the mine that went off
+ */
+pub enum GameState<C = u32> {
+    InProgress,
+    Won,
+    Lost { exploded: (C, C) },
+}
+
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+/**
+ * Produced when a transcript could not be replayed.
+ * `line` is the (one-indexed) line number of the offending move.
+ */
+pub struct TranscriptError {
+    pub line: usize,
+    pub kind: TranscriptErrorKind,
+}
+
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+/**
+ * The `Malformed` variant indicates that the line could not be parsed as a move
+ * The `Move` variant indicates that the move was parsed, but could not be
+ * made, for the contained reason
+ */
+pub enum TranscriptErrorKind {
+    Malformed,
+    Move(ErrorKind),
+}
+
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+/**
+ * Produced when a plan could not be carried out.
+ * `index` is the (zero-indexed) position of the offending move in the plan.
+ */
+pub struct PlanError {
+    pub index: usize,
+    pub kind: PlanErrorKind,
+}
+
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+/**
+ * The `Illegal` variant indicates that the move could not be made, for the contained reason
+ * The `Detonated` variant indicates that the move was made, but revealed a mine
+ */
+pub enum PlanErrorKind {
+    Illegal(ErrorKind),
+    Detonated,
+}
+
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(
+        remote = "Self",
+        bound(
+            serialize = "B: serde::Serialize, B::Coord: serde::Serialize",
+            deserialize = "B: serde::Deserialize<'de>, B::Coord: serde::Deserialize<'de>"
+        )
+    )
+)]
+/**
+ * With the `serde` feature enabled, a MinesweeperController can be serialized
+ * and deserialized along with its model, saving and restoring a whole game.
+ * Deserializing checks the model (see `MinesweeperModel::check_invariants`),
+ * the count of correct flags and the coordinates of every move in the
+ * history, and fails rather than restore a game that doesn't add up.
+ * The controller plays on any Board, a MinesweeperModel unless said
+ * otherwise; the solver, the statistics and the transcripts need a
+ * MinesweeperModel.
+ */
+pub struct MinesweeperController<B: Board = MinesweeperModel> {
+    model: B,
     num_correctly_flagged: u32,
-    exploded_mine: Option<(u32, u32)>,
+    history: Vec<(Action, B::Coord, B::Coord)>,
+    num_flags_placed: u32,
+    // see `set_exposed_mines_need_flags`
+    exposed_mines_need_flags: bool,
+    // see `set_question_marks`
+    question_marks: bool,
+    // see `set_mine_totals`
+    mine_totals: bool,
+    // true if the mines were yet to be placed when the history began, so
+    // that replaying it places them again (see `reveal_statistics`)
+    #[cfg_attr(feature = "serde", serde(default))]
+    started_pending: bool,
+    #[cfg_attr(feature = "serde", serde(skip))]
+    started_at: Option<Instant>,
+    #[cfg_attr(feature = "serde", serde(skip))]
+    finished_after: Option<Duration>,
 }
 
-impl MinesweeperController {
-    pub fn new(model: MinesweeperModel) -> Self {
+#[cfg(feature = "serde")]
+impl serde::Serialize for MinesweeperController {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        MinesweeperController::serialize(self, serializer)
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<'de> serde::Deserialize<'de> for MinesweeperController {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        use serde::de::Error;
+        let controller: Self = MinesweeperController::deserialize(deserializer)?;
+        if controller.num_correctly_flagged != controller.count_correctly_flagged() {
+            return Err(D::Error::custom("the count of correct flags is off"));
+        }
+        for &(_, x, y) in &controller.history {
+            controller
+                .model
+                .check_bounds(x, y)
+                .map_err(D::Error::custom)?;
+        }
+        Ok(controller)
+    }
+}
+
+impl<B: Board> MinesweeperController<B> {
+    pub fn new(model: B) -> Self {
         MinesweeperController {
+            started_pending: model.is_placement_pending(),
             model,
             num_correctly_flagged: 0,
-            exploded_mine: None,
+            history: Vec::new(),
+            num_flags_placed: 0,
+            exposed_mines_need_flags: true,
+            question_marks: false,
+            mine_totals: false,
+            started_at: None,
+            finished_after: None,
         }
     }
 
     /**
      * returns an immutable reference to the model in this controller
      */
-    pub fn model(&self) -> &MinesweeperModel {
+    pub fn model(&self) -> &B {
         &self.model
     }
 
+    /**
+     * Where the game stands. A lost game is lost even if every zone without
+     * a mine has been revealed too.
+     */
+    pub fn state(&self) -> GameState<B::Coord> {
+        if let Some(exploded) = self.first_revealed_mine() {
+            GameState::Lost { exploded }
+        } else if self.won() {
+            GameState::Won
+        } else {
+            GameState::InProgress
+        }
+    }
+
+    /**
+     * returns true if the game is still in progress (see `state`)
+     */
     pub fn can_keep_playing(&self) -> bool {
-        !(self.won() || self.lost())
+        self.state() == GameState::InProgress
     }
 
     /**
-     * returns true if no mines have exploded, all he all the mines have been flagged,
-     * and no space without a mine has been flagged.
+     * returns true if no mine has been revealed, and either every zone without
+     * a mine has been revealed (see `MinesweeperModel::is_cleared`), or all the
+     * mines have been flagged and no space without a mine has been flagged.
+     * A board with no mines to flag, like a fresh board whose mines are yet
+     * to be placed, can only be won by revealing it, and a board with no end
+     * to its mines (see `Board::num_mined_zones`) can't be won at all.
+     * A single flag covers every mine of a zone holding several.
+     * Anti-mines (see `MinesweeperModel::with_anti_mine_placements`) count as
+     * mines, and must be flagged too.
+     * Mines shown to the player from the start (see `MinesweeperModel::expose_mines`)
+     * need not be flagged unless `set_exposed_mines_need_flags` says so.
+     * Takes constant time: the model keeps count of the mined, exposed and
+     * flagged zones.
      */
     pub fn won(&self) -> bool {
-        !self.lost() && self.num_correctly_flagged == self.model.num_mines()
+        if self.lost() {
+            return false;
+        }
+        if self.model.is_cleared() {
+            return true;
+        }
+        // before the mines are placed, or with no end to them, there is
+        // nothing to flag
+        let num_mined_zones = match self.model.num_mined_zones() {
+            Some(num_mined_zones) => num_mined_zones,
+            None => return false,
+        };
+        if self.model.num_flagged() != self.num_correctly_flagged {
+            return false;
+        }
+        if self.exposed_mines_need_flags {
+            return num_mined_zones > 0 && self.num_correctly_flagged == num_mined_zones;
+        }
+        let num_to_flag = num_mined_zones - self.model.num_exposed();
+        num_to_flag > 0
+            && self.num_correctly_flagged - self.model.num_flagged_exposed() == num_to_flag
+    }
+
+    /**
+     * Decides whether mines shown to the player from the start
+     * (see `MinesweeperModel::expose_mines`) must be flagged to win,
+     * which they must unless set otherwise
+     */
+    pub fn set_exposed_mines_need_flags(&mut self, need_flags: bool) {
+        self.exposed_mines_need_flags = need_flags;
+    }
+
+    pub fn exposed_mines_need_flags(&self) -> bool {
+        self.exposed_mines_need_flags
+    }
+
+    /**
+     * Decides whether `toggle_flag_at` cycles through question marks as well
+     * (see `cycle_mark_at`), which it doesn't unless set otherwise
+     */
+    pub fn set_question_marks(&mut self, question_marks: bool) {
+        self.question_marks = question_marks;
+    }
+
+    pub fn question_marks(&self) -> bool {
+        self.question_marks
     }
 
     /**
-     * returns true if a mine has exploded
+     * Decides whether the player gets to see the number of mines in each
+     * row and column (see `MinesweeperModel::row_mine_counts`), as an assist
+     * mode. They are hidden unless set otherwise. Only the frontend pays
+     * attention to this.
+     */
+    pub fn set_mine_totals(&mut self, mine_totals: bool) {
+        self.mine_totals = mine_totals;
+    }
+
+    pub fn mine_totals(&self) -> bool {
+        self.mine_totals
+    }
+
+    /**
+     * returns true if a mine has exploded, or has otherwise been revealed
+     * (see `MinesweeperModel::any_mine_revealed`)
      */
     pub fn lost(&self) -> bool {
-        self.exploded_mine.is_some()
+        self.model.any_mine_revealed()
+    }
+
+    /**
+     * The mine that exploded, or failing that the first mine to be revealed
+     * (see `Board::first_revealed_mine`)
+     */
+    fn first_revealed_mine(&self) -> Option<(B::Coord, B::Coord)> {
+        self.model.first_revealed_mine()
     }
 
     /**
-     * TODO
+     * The time spent playing so far. The clock starts with the first move
+     * and stops once the game is over.
      */
-    pub fn exploded_mine_pos(&self) -> Option<(u32, u32)> {
-        self.exploded_mine
+    pub fn elapsed(&self) -> Duration {
+        match (self.finished_after, self.started_at) {
+            (Some(duration), _) => duration,
+            (None, Some(start)) => start.elapsed(),
+            (None, None) => Duration::from_secs(0),
+        }
+    }
+
+    /**
+     * Every successful move made on this controller, in the order they were made
+     */
+    pub fn history(&self) -> &[(Action, B::Coord, B::Coord)] {
+        &self.history
+    }
+
+    /**
+     * Produces the history of this controller as text, one move per line,
+     * e.g. `R 3 4` for a reveal at (3, 4), `F 0 0` for a flag toggle at (0, 0),
+     * `C 2 2` for a chord at (2, 2) or `M 1 0` for a change of mark at (1, 0).
+     * The moves can be applied again with `replay`.
+     */
+    pub fn transcript(&self) -> String {
+        let mut transcript = String::new();
+        for &(action, x, y) in &self.history {
+            transcript.push_str(&format!("{} {} {}\n", action.letter(), x, y));
+        }
+        transcript
     }
 
     /**
@@ -56,23 +336,108 @@ impl MinesweeperController {
         self.num_correctly_flagged
     }
 
+    /**
+     * The total number of flags placed over the course of the game,
+     * including flags which were later removed
+     */
+    pub fn num_flags_placed(&self) -> u32 {
+        self.num_flags_placed
+    }
+
+    /**
+     * The ratio of correctly placed flags currently on the board to the
+     * total number of flags ever placed, measuring how accurately the
+     * player flags. None if no flag has been placed yet.
+     */
+    pub fn flag_efficiency(&self) -> Option<f64> {
+        if self.num_flags_placed == 0 {
+            None
+        } else {
+            Some(self.num_correctly_flagged as f64 / self.num_flags_placed as f64)
+        }
+    }
+
     /**
      * Flag the zone at the given coordinates. If the zone at those
      * coordinates is already flagged, nothing happens.
-     * Fails if given coordinates were out of bounds
+     * Fails if given coordinates were out of bounds, or with
+     * FlagLimitReached if no flags are left (see `MinesweeperModel::set_flag_limit`)
      * On success, returns a boolean indicating if a flag was
      * added (true) or removed (false)
+     * With question marks turned on (see `set_question_marks`), this
+     * is `cycle_mark_at` instead.
+     * Fails with GameOver once the game has been won or lost.
+     */
+    pub fn toggle_flag_at(&mut self, x: B::Coord, y: B::Coord) -> ModelResult<bool> {
+        if self.question_marks {
+            return self.cycle_mark_at(x, y);
+        }
+        self.check_in_progress()?;
+        self.model.check_bounds(x, y)?;
+        if self.model.is_revealed_at(x, y).unwrap() {
+            return Err(NoOp {
+                op: Operation::Flag,
+            });
+        }
+        let add_flag = !self.model.is_flagged_at(x, y).unwrap();
+        // OutOfBounds errors are handled above, and NoOp errors are ruled out
+        // by toggling based on the result of is_flagged_at,
+        // which leaves FlagLimitReached
+        self.model.change_flag_at(x, y, add_flag)?;
+        self.count_flag_change(x, y, add_flag);
+        self.record_move(Action::Flag, x, y);
+        Ok(add_flag)
+    }
+
+    /**
+     * Flags every mine still hidden, as the final board of a won game shows.
+     * Fails with NoOp while the game is still going, since the flags would
+     * give away where the mines are.
+     */
+    pub fn flag_all_mines(&mut self) -> ModelResult<()> {
+        if self.can_keep_playing() {
+            return Err(NoOp {
+                op: Operation::FlagAllMines,
+            });
+        }
+        self.model.flag_all_mines();
+        self.num_correctly_flagged = match self.model.num_mined_zones() {
+            Some(num_mined_zones) => num_mined_zones,
+            None => self.count_correctly_flagged(),
+        };
+        Ok(())
+    }
+
+    /**
+     * Moves the mark on the zone at the given coordinates one step along
+     * the cycle of `MinesweeperModel::cycle_mark_at`: unmarked, flagged,
+     * questioned, and unmarked again.
+     * Fails if the given coordinates were out of bounds,
+     * with NoOp if the zone was already revealed, or with FlagLimitReached
+     * if the zone is unmarked and no flags are left.
+     * Fails with GameOver once the game has been won or lost.
+     * On success, returns a boolean indicating if a flag was added
+     */
+    pub fn cycle_mark_at(&mut self, x: B::Coord, y: B::Coord) -> ModelResult<bool> {
+        self.check_in_progress()?;
+        self.model.check_bounds(x, y)?;
+        let was_flagged = self.model.is_flagged_at(x, y).unwrap();
+        let add_flag = self.model.cycle_mark_at(x, y)? == Mark::Flagged;
+        if add_flag || was_flagged {
+            self.count_flag_change(x, y, add_flag);
+        }
+        self.record_move(Action::Mark, x, y);
+        Ok(add_flag)
+    }
+
+    /**
+     * Keeps the flag statistics up to date after a flag was
+     * added (true) or removed (false) at the given coordinates
      */
-    pub fn toggle_flag_at(&mut self, x: u32, y: u32) -> ModelResult<bool> {
-        if self.model.is_revealed_at(x, y).ok_or(OutOfBounds)? {
-            return Err(NoOp);
+    fn count_flag_change(&mut self, x: B::Coord, y: B::Coord, add_flag: bool) {
+        if add_flag {
+            self.num_flags_placed += 1;
         }
-        let add_flag = !self.model.is_flagged_at(x, y).ok_or(OutOfBounds)?;
-        // disregard err variants --
-        //  OutOfBounds errors should be handled above
-        //  and NoOp errors are covered by the fact that
-        //  we are toggling based on the result of is_flagged_at
-        self.model.change_flag_at(x, y, add_flag).unwrap();
         if self.model.has_mine_at(x, y).unwrap() {
             if add_flag {
                 self.num_correctly_flagged += 1;
@@ -80,46 +445,574 @@ impl MinesweeperController {
                 self.num_correctly_flagged -= 1;
             }
         }
-        Ok(add_flag)
     }
 
     /**
-     * TODO
+     * Reveals the zone at the given coordinates, cascading through the
+     * zones around it if it has no adjacent mines. Flagged zones are never
+     * revealed, by the cascade or otherwise.
+     * Fails with NoOp if the zone was already revealed,
+     * or with Flagged if it has a flag, which is left in place.
+     * Fails with GameOver once the game has been won or lost.
+     * On success, returns a boolean indicating if the zone contained a mine.
+     */
+    pub fn reveal_zone_at(&mut self, x: B::Coord, y: B::Coord) -> ModelResult<bool> {
+        self.reveal_recorded(x, y)?;
+        Ok(self.model.has_mine_at(x, y).unwrap())
+    }
+
+    /**
+     * `reveal_zone_at`, returning every position revealed instead,
+     * in the order they were revealed
+     */
+    fn reveal_recorded(
+        &mut self,
+        x: B::Coord,
+        y: B::Coord,
+    ) -> ModelResult<Vec<(B::Coord, B::Coord)>> {
+        self.check_in_progress()?;
+        self.model.check_bounds(x, y)?;
+        if self.model.is_flagged_at(x, y).unwrap() {
+            return Err(Flagged);
+        }
+        let revealed = self.reveal_unrecorded(x, y)?;
+        self.record_move(Action::Reveal, x, y);
+        Ok(revealed)
+    }
+
+    /**
+     * Adds a successful move to the history, starting the clock
+     * if it's the first move. If the move ended the game, stops the clock
+     * and reveals the final board: every mine after a loss
+     * (see `MinesweeperModel::reveal_mines`), every zone without a mine
+     * after a win, with flags on every mine
+     * (see `MinesweeperModel::reveal_safe_zones`).
+     */
+    fn record_move(&mut self, action: Action, x: B::Coord, y: B::Coord) {
+        self.history.push((action, x, y));
+        let start = *self.started_at.get_or_insert_with(Instant::now);
+        if !self.can_keep_playing() && self.finished_after.is_none() {
+            self.finished_after = Some(start.elapsed());
+            if self.lost() {
+                self.model.reveal_mines();
+            } else {
+                self.flag_all_mines().unwrap();
+                self.model.reveal_safe_zones();
+            }
+        }
+    }
+
+    /**
+     * Reveals the zone at the given coordinates, exploding or cascading as
+     * appropriate, without recording the reveal as a move in the history.
+     * On success, returns every position revealed (see `MinesweeperModel::reveal_area`).
+     */
+    fn reveal_unrecorded(
+        &mut self,
+        x: B::Coord,
+        y: B::Coord,
+    ) -> ModelResult<Vec<(B::Coord, B::Coord)>> {
+        let was_pending = self.model.is_placement_pending();
+        let revealed = self.model.reveal_area(x, y)?;
+        if was_pending {
+            // flags placed before the mines were may have turned out correct
+            self.num_correctly_flagged = self.count_correctly_flagged();
+        }
+        Ok(revealed)
+    }
+
+    /**
+     * Fails with GameOver once the game has been won or lost, so that moves
+     * can't change the final board
+     */
+    fn check_in_progress(&self) -> ModelResult<()> {
+        if self.can_keep_playing() {
+            Ok(())
+        } else {
+            Err(GameOver)
+        }
+    }
+
+    /**
+     * Counts the flags on zones with a mine, from the model itself
+     */
+    fn count_correctly_flagged(&self) -> u32 {
+        self.model
+            .flagged_positions()
+            .into_iter()
+            .filter(|&(x, y)| self.model.has_mine_at(x, y).unwrap())
+            .count() as u32
+    }
+}
+
+impl MinesweeperController {
+    /**
+     * Creates a controller for the given model, then replays onto it the moves
+     * of a transcript in the format produced by `transcript`.
+     * Blank lines are ignored.
+     */
+    pub fn from_transcript(
+        model: MinesweeperModel,
+        transcript: &str,
+    ) -> Result<Self, TranscriptError> {
+        let mut controller = Self::new(model);
+        for (index, line) in transcript.lines().enumerate() {
+            if line.trim().is_empty() {
+                continue;
+            }
+            let error = |kind| TranscriptError {
+                line: index + 1,
+                kind,
+            };
+            let (action, x, y) =
+                Self::parse_move(line).ok_or_else(|| error(TranscriptErrorKind::Malformed))?;
+            controller
+                .apply(action, x, y)
+                .map_err(|e| error(TranscriptErrorKind::Move(e)))?;
+        }
+        Ok(controller)
+    }
+
+    /**
+     * Starts the game over on the same board (see `MinesweeperModel::reset`),
+     * clearing the history, the statistics and the clock along with it.
+     */
+    pub fn restart(&mut self) {
+        self.model.reset();
+        self.started_pending = false;
+        self.num_correctly_flagged = 0;
+        self.history.clear();
+        self.num_flags_placed = 0;
+        self.started_at = None;
+        self.finished_after = None;
+    }
+
+    /**
+     * The position of the mine that exploded, if one has
+     * (see `MinesweeperModel::exploded_at`)
+     */
+    pub fn exploded_mine_pos(&self) -> Option<Position> {
+        self.model.exploded_at().map(Position::from)
+    }
+
+    /**
+     * A single line summarizing the game for a status bar, formatted like
+     * `Mines: 7  Time: 00:42  [Playing]`, where the mine count is the model's
+     * `mines_remaining` (and so can be negative).
+     */
+    pub fn status_line(&self) -> String {
+        let mines_left = self.model.mines_remaining();
+        let seconds = self.elapsed().as_secs();
+        let state = match self.state() {
+            GameState::InProgress => "Playing",
+            GameState::Won => "Won",
+            GameState::Lost { .. } => "Lost",
+        };
+        format!(
+            "Mines: {}  Time: {:02}:{:02}  [{}]",
+            mines_left,
+            seconds / 60,
+            seconds % 60,
+            state
+        )
+    }
+
+    /**
+     * What the player can see of the game, as a single line of JSON
+     * (see `PlayerView::to_json`), with a status of "playing", "won" or "lost"
+     */
+    pub fn state_json(&self) -> String {
+        self.model.player_view().to_json(self.status())
+    }
+
+    fn status(&self) -> &'static str {
+        match self.state() {
+            GameState::InProgress => "playing",
+            GameState::Won => "won",
+            GameState::Lost { .. } => "lost",
+        }
+    }
+
+    /**
+     * The score for a won game (None if the game hasn't been won), computed as
+     * `floor(1000 * 3bv * efficiency * (1 + 10 * density) / seconds)` where
+     *  3bv        is the model's `board_3bv`
+     *  efficiency is `3bv / moves`, capped at 1, where moves is the length of
+     *             the history (at least 1)
+     *  density    is the number of mines divided by the number of zones
+     *  seconds    is the whole number of seconds elapsed, at least 1
+     */
+    pub fn final_score(&self) -> Option<u64> {
+        if !self.won() {
+            return None;
+        }
+        let bbbv = self.model.board_3bv() as f64;
+        let moves = self.history.len().max(1) as f64;
+        let efficiency = (bbbv / moves).min(1.0);
+        let num_zones = self.model.num_active() as f64;
+        let density = self.model.num_mines() as f64 / num_zones;
+        let seconds = self.elapsed().as_secs().max(1) as f64;
+        Some((1000.0 * bbbv * efficiency * (1.0 + 10.0 * density) / seconds).floor() as u64)
+    }
+
+    /**
+     * The number of reveals made on zones that could not be deduced to be
+     * safe from the information visible at the time, whether or not some
+     * other zone could have been. The first reveal of a game (with mines)
+     * is always a guess.
+     * Worked out from the history (see `reveal_statistics`), so it takes a
+     * run of the solver for every reveal made.
+     */
+    pub fn guess_count(&self) -> u32 {
+        self.reveal_statistics().0
+    }
+
+    /**
+     * Performs the given action at the given coordinates.
+     * The returned boolean has the same meaning as it does for
+     * `reveal_zone_at`, `toggle_flag_at`, `chord_at` or `cycle_mark_at`,
+     * respectively.
+     */
+    pub fn apply(&mut self, action: Action, x: u32, y: u32) -> ModelResult<bool> {
+        match action {
+            Action::Reveal => self.reveal_zone_at(x, y),
+            Action::Flag => self.toggle_flag_at(x, y),
+            Action::Chord => self.chord_at(x, y),
+            Action::Mark => self.cycle_mark_at(x, y),
+        }
+    }
+
+    /**
+     * Applies each of the given moves in order, stopping at the first
+     * one that fails and returning its error.
+     */
+    pub fn replay(
+        &mut self,
+        moves: impl IntoIterator<Item = (Action, u32, u32)>,
+    ) -> ModelResult<()> {
+        for (action, x, y) in moves {
+            self.apply(action, x, y)?;
+        }
+        Ok(())
+    }
+
+    /**
+     * The number of consecutive reveals without a mine since the last reveal
+     * that hit a mine or was a forced guess (a guess made when nothing could
+     * be deduced to be safe)
+     * Worked out from the history, like `guess_count`.
+     */
+    pub fn safe_streak(&self) -> u32 {
+        self.reveal_statistics().1
+    }
+
+    /**
+     * The guess count and the safe streak, worked out by replaying the
+     * history on the board as it was at the start and asking the solver what
+     * could be deduced before each reveal. Done only when asked for, so that
+     * a reveal doesn't cost a run of the solver over the whole board.
+     * A move that fails on the replay ends it there.
+     */
+    fn reveal_statistics(&self) -> (u32, u32) {
+        let mut board = self.model.clone();
+        board.reset();
+        if self.started_pending {
+            board.unplace_deferred_mines();
+        }
+        let mut replay = Self::new(board);
+        replay.exposed_mines_need_flags = self.exposed_mines_need_flags;
+        replay.question_marks = self.question_marks;
+        let (mut guess_count, mut safe_streak) = (0, 0);
+        for &(action, x, y) in &self.history {
+            if action != Action::Reveal {
+                if replay.apply(action, x, y).is_err() {
+                    break;
+                }
+                continue;
+            }
+            let safe = solver::deduce(&replay.model).safe;
+            let (guessed, forced) = (!safe.contains(&(x, y)), safe.is_empty());
+            let has_mine = match replay.reveal_zone_at(x, y) {
+                Ok(has_mine) => has_mine,
+                Err(_) => break,
+            };
+            if guessed {
+                guess_count += 1;
+            }
+            if has_mine || (guessed && forced) {
+                safe_streak = 0;
+            } else {
+                safe_streak += 1;
+            }
+        }
+        (guess_count, safe_streak)
+    }
+
+    /**
+     * Removes every flag from the board, as if none had been placed
+     * Fails with GameOver once the game has been won or lost.
+     */
+    pub fn clear_flags(&mut self) -> ModelResult<()> {
+        self.check_in_progress()?;
+        self.model.clear_flags();
+        self.num_correctly_flagged = 0;
+        Ok(())
+    }
+
+    /**
+     * Carries out each of the moves in the given plan in order, stopping at the
+     * first move which is illegal or which reveals a mine. Moves before the
+     * offending one stay applied.
+     */
+    pub fn execute_plan(&mut self, plan: &[(Action, u32, u32)]) -> Result<(), PlanError> {
+        for (index, &(action, x, y)) in plan.iter().enumerate() {
+            let detonated = self.apply(action, x, y).map_err(|e| PlanError {
+                index,
+                kind: PlanErrorKind::Illegal(e),
+            })? && action != Action::Flag
+                && action != Action::Mark;
+            if detonated {
+                return Err(PlanError {
+                    index,
+                    kind: PlanErrorKind::Detonated,
+                });
+            }
+        }
+        Ok(())
+    }
+
+    /**
+     * Parses a single transcript line such as `R 3 4`
+     */
+    fn parse_move(line: &str) -> Option<(Action, u32, u32)> {
+        let mut parts = line.split_whitespace();
+        let action = match parts.next()? {
+            "R" => Action::Reveal,
+            "F" => Action::Flag,
+            "C" => Action::Chord,
+            "M" => Action::Mark,
+            _ => return None,
+        };
+        let x = parts.next()?.parse().ok()?;
+        let y = parts.next()?.parse().ok()?;
+        if parts.next().is_some() {
+            return None;
+        }
+        Some((action, x, y))
+    }
+
+    /**
+     * Chords at the given coordinates: if the zone there is revealed and
+     * has exactly as many flags adjacent to it as it has adjacent mines,
+     * then every hidden, unflagged zone adjacent to it is revealed.
+     * Mines shown to the player from the start count as flagged.
+     * The flags are compared to the count displayed, which may be a lie
+     * (see `MinesweeperModel::displayed_count_at`).
+     * A misplaced flag means that one of the revealed zones has a mine.
+     * Fails with NoOp if the zone isn't a revealed number with matching
+     * flags, or if there is nothing left to reveal around it.
+     * Fails with GameOver once the game has been won or lost.
+     * On success, returns a boolean indicating if any revealed zone
+     * contained a mine.
+     */
+    pub fn chord_at(&mut self, x: u32, y: u32) -> ModelResult<bool> {
+        let revealed = self.chord_recorded(x, y)?;
+        Ok(revealed
+            .into_iter()
+            .any(|(x, y)| self.model.has_mine_at(x, y).unwrap()))
+    }
+
+    /**
+     * `chord_at`, returning every position revealed instead,
+     * in the order they were revealed
+     */
+    fn chord_recorded(&mut self, x: u32, y: u32) -> ModelResult<Vec<(u32, u32)>> {
+        self.check_in_progress()?;
+        self.model.check_bounds(x, y)?;
+        let summary = self.model.neighborhood_summary(x, y).unwrap();
+        // only a revealed zone without a mine shows a count
+        if summary.adjacent != Some(summary.flagged as i32) || summary.hidden == 0 {
+            return Err(NoOp {
+                op: Operation::Chord,
+            });
+        }
+        let targets: Vec<_> = self
+            .model
+            .adjacent_iter(x, y, true)
+            .filter(|&(adj_x, adj_y)| {
+                matches!(
+                    self.model.zone_state_at(adj_x, adj_y),
+                    Some(ZoneState::Hidden {
+                        flagged: false,
+                        exposed: false,
+                        ..
+                    })
+                )
+            })
+            .collect();
+        let mut revealed = Vec::new();
+        for (target_x, target_y) in targets {
+            match self.reveal_unrecorded(target_x, target_y) {
+                Ok(positions) => revealed.extend(positions),
+                // already revealed by the cascade from an earlier target
+                Err(NoOp { .. }) => continue,
+                Err(e) => return Err(e),
+            }
+        }
+        self.record_move(Action::Chord, x, y);
+        Ok(revealed)
+    }
+
+    /**
+     * Reveals the zone at the given coordinates, then keeps chording the
+     * numbers whose adjacent flags match them among the zones just revealed
+     * and the revealed zones around those, until no chord reveals anything
+     * more or the game is over.
+     * On success, returns every position revealed along the way, in the
+     * order they were revealed.
+     */
+    pub fn reveal_and_autochord(&mut self, x: u32, y: u32) -> ModelResult<Vec<(u32, u32)>> {
+        let mut revealed = self.reveal_recorded(x, y)?;
+        let mut just_revealed = revealed.clone();
+        while !just_revealed.is_empty() && self.can_keep_playing() {
+            let mut numbers: Vec<_> = just_revealed
+                .iter()
+                .flat_map(|&(x, y)| {
+                    core::iter::once((x, y)).chain(self.model.adjacent_iter(x, y, true))
+                })
+                .filter(|&(x, y)| self.model.is_revealed_at(x, y).unwrap())
+                .collect();
+            numbers.sort_unstable();
+            numbers.dedup();
+            just_revealed.clear();
+            for (chord_x, chord_y) in numbers {
+                if !self.can_keep_playing() {
+                    break;
+                }
+                if let Ok(positions) = self.chord_recorded(chord_x, chord_y) {
+                    just_revealed.extend(positions);
+                }
+            }
+            revealed.extend(just_revealed.iter().copied());
+        }
+        Ok(revealed)
+    }
+
+    /**
+     * Reveals the hidden, unflagged zone least likely to contain a mine,
+     * as chosen by `solver::best_guess`.
+     * On success, returns the coordinates of the revealed zone
+     * along with whether it contained a mine.
+     * Fails with NoOp if there is no hidden, unflagged zone left,
+     * or with GameOver once the game has been won or lost.
+     */
+    pub fn guess_best(&mut self) -> ModelResult<(u32, u32, bool)> {
+        self.check_in_progress()?;
+        let (x, y) = solver::best_guess(&self.model).ok_or(NoOp {
+            op: Operation::Guess,
+        })?;
+        let has_mine = self.reveal_zone_at(x, y)?;
+        Ok((x, y, has_mine))
+    }
+
+    /**
+     * returns true if the location of every mine that is still hidden can be
+     * deduced from the information visible to the player, so that the rest
+     * of the game could be finished without any guessing.
+     */
+    pub fn remaining_mines_all_known(&self) -> bool {
+        let mut revealed_mines = 0;
+        for (x, y) in self.model.active_positions() {
+            if self.model.is_revealed_at(x, y).unwrap() && self.model.has_mine_at(x, y).unwrap() {
+                revealed_mines += 1;
+            }
+        }
+        let hidden_mines = (self.model.num_mines() - revealed_mines) as usize;
+        solver::deduce(&self.model).mines.len() == hidden_mines
+    }
+
+    /**
+     * When the game is still going but no hidden zone can be deduced to be
+     * safe, the player is down to a guess. In that case, returns the number
+     * of hidden zones whose contents can't be deduced along with the number
+     * of mines among them, giving the odds of the guess. Otherwise None.
      */
-    pub fn reveal_zone_at(&mut self, x: u32, y: u32) -> ModelResult<bool> {
-        let has_mine = self.model.reveal_at(x, y)?;
-        if has_mine {
-            self.exploded_mine = Some((x, y));
-        } else if self.model.mines_adjacent_to(x, y).unwrap() == 0 {
-            self.cascading_reveal_from(x, y);
+    pub fn endgame_odds(&self) -> Option<(u32, u32)> {
+        if !self.can_keep_playing() {
+            return None;
+        }
+        let deductions = solver::deduce(&self.model);
+        if !deductions.safe.is_empty() {
+            return None;
+        }
+        let mut undecided = 0;
+        let mut revealed_mines = 0;
+        for (x, y) in self.model.active_positions() {
+            if self.model.is_revealed_at(x, y).unwrap() {
+                if self.model.has_mine_at(x, y).unwrap() {
+                    revealed_mines += 1;
+                }
+            } else if !deductions.mines.contains(&(x, y)) {
+                undecided += 1;
+            }
         }
-        Ok(has_mine)
+        if undecided == 0 {
+            return None;
+        }
+        let mines = self.model.num_mines() - revealed_mines - deductions.mines.len() as u32;
+        Some((undecided, mines))
     }
 
     /**
-     * TODO
-     * pre-condition: self.model.num_mines_adjacent_to(starting_x, starting_y).unwrap() == 0
+     * Plays the rest of the game automatically: reveals zones deduced to be
+     * safe, flags zones deduced to contain mines, and reveals the best guess
+     * whenever nothing can be deduced. Stops once the game is over, or once
+     * there is nothing left to do.
+     * Returns the coordinates of every zone chosen to be revealed, in order,
+     * so that the playthrough can be animated.
      */
-    fn cascading_reveal_from(&mut self, starting_x: u32, starting_y: u32) {
-        debug_assert!(
-            self.model
-                .mines_adjacent_to(starting_x, starting_y)
-                .unwrap()
-                == 0
-        );
-        let mut stack = self.model.adjacent_positions(starting_x, starting_y, false);
-        while let Some((x, y)) = stack.pop() {
-            match self.model.reveal_at(x, y) {
-                Ok(_) => stack.extend(
-                    // add all adjacent postitions with 0 adjacent mines
-                    self.model
-                        .adjacent_positions(x, y, false)
-                        .into_iter()
-                        .filter(|&(x, y)| self.model.mines_adjacent_to(x, y).unwrap() == 0),
-                ),
-                Err(NoOp) => continue,
-                Err(OutOfBounds) => panic!("out of bounds with coordinates {:?}", (x, y)),
+    pub fn demo_solve(&mut self) -> Vec<(u32, u32)> {
+        let mut revealed = Vec::new();
+        while self.can_keep_playing() {
+            let deductions = solver::deduce(&self.model);
+            if let Some(&(x, y)) = deductions.safe.iter().min() {
+                // flags can be wrong, and the deduction has the final word
+                if self.model.is_flagged_at(x, y).unwrap() {
+                    self.toggle_flag_at(x, y).unwrap();
+                }
+                self.reveal_zone_at(x, y).unwrap();
+                revealed.push((x, y));
+                continue;
+            }
+            let unflagged_mine = deductions
+                .mines
+                .iter()
+                .filter(|&&(x, y)| !self.model.is_flagged_at(x, y).unwrap())
+                .min();
+            if let Some(&(x, y)) = unflagged_mine {
+                match self.toggle_flag_at(x, y) {
+                    // out of flags, so the mines are simply left alone
+                    Err(FlagLimitReached { .. }) => (),
+                    result => {
+                        result.unwrap();
+                        continue;
+                    }
+                }
+            }
+            match self.guess_best() {
+                Ok((x, y, _)) => revealed.push((x, y)),
+                Err(_) => break,
             }
         }
+        revealed
+    }
+
+    /**
+     * Reveals the zone at the given coordinates just like `reveal_zone_at`,
+     * then returns the model's `numeric_view` as it stands after the reveal.
+     */
+    pub fn reveal_and_observe(&mut self, x: u32, y: u32) -> ModelResult<Vec<Vec<i8>>> {
+        self.reveal_zone_at(x, y)?;
+        Ok(self.model.numeric_view())
     }
 }
@@ -0,0 +1,30 @@
+/*!
+ * The model of a game of minesweeper, and everything that plays on it.
+ * Without the default `std` feature the library is `no_std`, needing only
+ * `alloc`: boards can then only be created from a seed or from an explicit
+ * layout, nothing is read or written through `std::io` (PBM images and
+ * saves), and there is no controller (which keeps time) or simulation.
+ */
+#![no_std]
+
+extern crate alloc;
+#[cfg(feature = "std")]
+extern crate std;
+
+#[cfg(feature = "arbitrary")]
+pub mod arbitrary;
+pub mod ascii;
+pub mod board;
+pub mod chunked;
+#[cfg(feature = "std")]
+pub mod controller;
+pub mod mbf;
+pub mod model;
+#[cfg(feature = "std")]
+pub mod pbm;
+pub mod save;
+pub mod share;
+#[cfg(feature = "std")]
+pub mod simulation;
+pub mod solver;
+pub mod topology;
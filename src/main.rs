@@ -1,43 +1,128 @@
-mod controller;
-mod model;
-
-use controller::*;
-use model::{ErrorKind as ModelErrorKind, MinesweeperModel};
-use std::io::stdin;
+use minesweeper::controller::*;
+use minesweeper::model::{BoardAnalysis, Mark, MinesweeperModel, Position, Tiling, ZoneState};
+use std::fs::File;
+use std::io::{stdin, BufReader};
 
 fn main() {
-    let m = MinesweeperModel::new(10, 10, 10).unwrap();
-    let c = MinesweeperController::new(m);
-    play_game(c);
+    let mut args: Vec<String> = std::env::args().skip(1).collect();
+    // may come along with any of the others
+    let mine_totals = args.iter().any(|arg| arg == "--totals");
+    args.retain(|arg| arg != "--totals");
+    // so may this, along with the path after it
+    let dump_state = match args.iter().position(|arg| arg == "--dump-state") {
+        Some(index) if index + 1 < args.len() => {
+            let path = args.remove(index + 1);
+            args.remove(index);
+            Some(path)
+        }
+        Some(_) => {
+            println!("--dump-state needs the path of the file to write to");
+            return;
+        }
+        None => None,
+    };
+    let m = match args.as_slice() {
+        [flag, path] if flag == "--board-image" => match File::open(path) {
+            Ok(file) => match MinesweeperModel::from_pbm(BufReader::new(file)) {
+                Ok(m) => m,
+                Err(e) => {
+                    println!("Could not load the board from {}: {}", path, e);
+                    return;
+                }
+            },
+            Err(e) => {
+                println!("Could not open {}: {}", path, e);
+                return;
+            }
+        },
+        [flag, path] if flag == "--analyze" => {
+            match File::open(path) {
+                Ok(file) => match MinesweeperModel::from_pbm(BufReader::new(file)) {
+                    Ok(m) => print_analysis(&m.analyze()),
+                    Err(e) => println!("Could not load the board from {}: {}", path, e),
+                },
+                Err(e) => println!("Could not open {}: {}", path, e),
+            }
+            return;
+        }
+        [] => match MinesweeperModel::new_deferred(10, 10, 10) {
+            Ok(m) => m,
+            Err(e) => {
+                println!("Could not create the board: {}", e);
+                return;
+            }
+        },
+        _ => {
+            println!(
+                "Usage: minesweeper [--totals] [--dump-state path.json] \
+                 [--board-image path.pbm | --analyze path.pbm]"
+            );
+            return;
+        }
+    };
+    let mut c = MinesweeperController::new(m);
+    c.set_question_marks(true);
+    c.set_mine_totals(mine_totals);
+    play_game(c, dump_state.as_deref());
+}
+
+fn print_analysis(analysis: &BoardAnalysis) {
+    println!("3BV: {}", analysis.bbbv);
+    println!("Openings: {}", analysis.num_openings);
+    println!("Largest opening: {} zones", analysis.largest_opening);
+    println!("Islands: {}", analysis.num_islands);
+    println!("Mine density: {:.1}%", 100.0 * analysis.mine_density);
+    println!("Difficulty: {:?}", analysis.difficulty);
 }
 
 /**
  * Main game logic loop
+ * dump_state is the path of a file to keep the state of the game in, for
+ * other tools to follow along (see `MinesweeperController::state_json`),
+ * rewritten before the first move and after every move
  */
-fn play_game(mut c: MinesweeperController) {
-    while c.can_keep_playing() {
-        draw_board(c.model(), false);
-        let action = get_user_action();
+fn play_game(mut c: MinesweeperController, dump_state: Option<&str>) {
+    let depth = c.model().depth();
+    let mut layer = 0;
+    write_state(&c, dump_state);
+    while c.state() == GameState::InProgress {
+        println!("{}", c.status_line());
+        println!("{}% cleared", cleared_percentage(c.model()));
+        if depth > 1 {
+            println!("Layer {} of {}", layer, depth);
+        }
+        draw_board(c.model(), layer, None, false, c.mine_totals());
+        let action = get_user_action(depth > 1);
+        if let UserAction::Layer(new_layer) = action {
+            if new_layer < depth {
+                layer = new_layer;
+            } else {
+                println!("There is no layer {}!", new_layer);
+            }
+            println!();
+            continue;
+        }
         let (x, y) = get_user_coordinates();
+        let (model_x, model_y) = match c.model().flatten_position(x, y, layer) {
+            Some(position) => position,
+            None => {
+                println!("Given coordinates ({}, {}) were not in bounds!", x, y);
+                println!();
+                continue;
+            }
+        };
         match action {
-            UserAction::Flag => match c.toggle_flag_at(x, y) {
-                Ok(added_flag) => {
-                    if added_flag {
-                        println!("Added a flag at ({}, {})", x, y);
-                    } else {
-                        println!("Removed a flag from ({}, {})", x, y);
-                    }
-                }
-                Err(ModelErrorKind::OutOfBounds) => {
-                    println!("Given coordinates ({}, {}) were not in bounds!", x, y)
-                }
-                Err(ModelErrorKind::NoOp) => println!("Given coordinates ({}, {}) were already revealed!", x, y),
+            UserAction::Layer(_) => unreachable!(),
+            UserAction::Flag => match c.toggle_flag_at(model_x, model_y) {
+                Ok(_) => match c.model().mark_at(model_x, model_y).unwrap() {
+                    Mark::Flagged => println!("Added a flag at ({}, {})", x, y),
+                    Mark::Questioned => println!("Marked ({}, {}) with a question mark", x, y),
+                    Mark::Unmarked => println!("Removed the mark from ({}, {})", x, y),
+                },
+                Err(e) => println!("Can't do that: {}!", e),
             },
-            UserAction::Reveal => match c.reveal_zone_at(x, y) {
-                Err(ModelErrorKind::OutOfBounds) => {
-                    println!("Given coordinates were out of bounds!")
-                }
-                Err(ModelErrorKind::NoOp) => println!("That space was already revealed!"),
+            UserAction::Reveal => match c.reveal_zone_at(model_x, model_y) {
+                Err(e) => println!("Can't do that: {}!", e),
                 Ok(mine) => {
                     if mine {
                         println!("KA-BOOM!!")
@@ -45,25 +130,73 @@ fn play_game(mut c: MinesweeperController) {
                 }
             },
         }
+        write_state(&c, dump_state);
         println!();
     }
-    draw_board(c.model(), true);
-    if c.won() {
-        println!("Congratulations! You won!")
-    } else {
-        println!("Sorry! Better luck next time!")
+    println!("{}", c.status_line());
+    for layer in 0..depth {
+        if depth > 1 {
+            println!("Layer {} of {}", layer, depth);
+        }
+        // the controller has revealed the final board
+        draw_board(
+            c.model(),
+            layer,
+            c.exploded_mine_pos(),
+            false,
+            c.mine_totals(),
+        );
+    }
+    match c.state() {
+        GameState::Won => println!("Congratulations! You won!"),
+        GameState::Lost { exploded } => {
+            let (x, y, layer) = c.model().layer_position(exploded.0, exploded.1).unwrap();
+            if depth > 1 {
+                println!("The mine at ({}, {}) of layer {} went off.", x, y, layer);
+            } else {
+                println!("The mine at ({}, {}) went off.", x, y);
+            }
+            println!("Sorry! Better luck next time!")
+        }
+        GameState::InProgress => unreachable!(),
+    }
+}
+
+/**
+ * Writes the state of the game to the file at the given path, if there is one
+ */
+fn write_state(c: &MinesweeperController, path: Option<&str>) {
+    if let Some(path) = path {
+        if let Err(e) = std::fs::write(path, c.state_json() + "\n") {
+            println!("Could not write the state of the game to {}: {}", path, e);
+        }
     }
 }
 
 enum UserAction {
     Flag,
     Reveal,
+    Layer(u32),
 }
 
-fn get_user_action() -> UserAction {
+/**
+ * layered is true if the board has several layers,
+ * which the user can switch between with e.g. `layer 2`
+ */
+fn get_user_action(layered: bool) -> UserAction {
+    let prompt = if layered {
+        "(F)lag/mark, (R)eveal or switch to layer N with \"layer N\"?"
+    } else {
+        "(F)lag/mark or (R)eveal?"
+    };
     loop {
-        let s = get_user_input("(F)lag or (R)eveal?");
-        if s.starts_with('f') {
+        let s = get_user_input(prompt);
+        if let Some(layer) = s.strip_prefix("layer") {
+            match layer.trim().parse() {
+                Ok(layer) if layered => return UserAction::Layer(layer),
+                _ => println!("I didn't understand that!"),
+            }
+        } else if s.starts_with('f') {
             return UserAction::Flag;
         } else if s.starts_with('r') {
             return UserAction::Reveal;
@@ -109,13 +242,44 @@ fn get_user_input(prompt: &str) -> String {
 }
 
 /**
- * print the given MinesweeperModel to stdout
+ * print the given layer of the given MinesweeperModel to stdout
+ * (layer 0 is the whole board, unless it has several layers)
+ * exploded is the position of the mine that ended the game, if one did,
+ * which is drawn apart from the other revealed mines
  * xray is a flag for debugging purposes, which if true causes all
  * bombs to be displayed regardless of if they have yet been revealed
+ * totals is true if the number of mines in each column is to be drawn
+ * under the x-axis, and the number in each row after it
+ * (see `MinesweeperController::set_mine_totals`). On a board with several
+ * layers, the column totals count the mines of every layer.
  */
-fn draw_board(model: &MinesweeperModel, xray: bool) {
-    let x_item_width = num_digits_b10(model.width() - 1);
-    let y_item_width = num_digits_b10(model.height() - 1);
+fn draw_board(
+    model: &MinesweeperModel,
+    layer: u32,
+    exploded: Option<Position>,
+    xray: bool,
+    totals: bool,
+) {
+    // wide enough for every x coordinate, and for every adjacent mine count,
+    // which can have more than one digit with a large enough kernel,
+    // or a minus sign with anti-mines
+    let count_width = model
+        .positions()
+        .filter_map(|(x, y)| model.displayed_count_at(x, y))
+        .map(|count| count.to_string().len())
+        .max()
+        .unwrap_or(1);
+    let col_totals = model.col_mine_counts();
+    let row_totals = model.row_mine_counts();
+    // the column totals are drawn in the columns, so they must fit too
+    let total_width = match col_totals.iter().max() {
+        Some(&max_total) if totals => num_digits_b10(max_total),
+        _ => 1,
+    };
+    let x_item_width = num_digits_b10(model.width() - 1)
+        .max(count_width)
+        .max(total_width);
+    let y_item_width = num_digits_b10(model.layer_height() - 1);
 
     // print the x-axis
     println!(
@@ -124,54 +288,99 @@ fn draw_board(model: &MinesweeperModel, xray: bool) {
         y_item_width + 1,
         x_axis(model.width(), x_item_width)
     );
+    if totals {
+        let col_totals: Vec<_> = col_totals
+            .iter()
+            .map(|total| format!("{0:>1$}", total, x_item_width))
+            .collect();
+        println!("{0:1$}{2}", ' ', y_item_width + 1, col_totals.join(" "));
+    }
 
-    for y in 0..model.height() {
-        let mut line = format!("{0:01$} ", y, y_item_width);
-        for x in 0..model.width() {
-            for _ in 1..x_item_width {
-                line.push(' ');
+    let layer_rows = layer * model.layer_height()..(layer + 1) * model.layer_height();
+    let mut line = String::new();
+    // positions come row by row, left to right
+    for (x, y) in model.positions().filter(|(_, y)| layer_rows.contains(y)) {
+        let layer_y = y - layer_rows.start;
+        if x == 0 {
+            line = format!("{0:01$} ", layer_y, y_item_width);
+            if model.tiling() == Tiling::Hex {
+                // shift each row half a cell right of the one above,
+                // so that every cell sits between its neighbors above and below
+                let shift = layer_y as usize * (x_item_width + 1) / 2;
+                line.extend(std::iter::repeat_n(' ', shift));
             }
-            line.push(if model.is_revealed_at(x, y).unwrap() {
-                if model.has_mine_at(x, y).unwrap() {
-                    '💥'
-                } else {
-                    let num_adjacent = model.mines_adjacent_to(x, y).unwrap();
-                    if num_adjacent > 0 {
-                        std::char::from_digit(num_adjacent, 10).unwrap()
-                    } else {
-                        '□'
-                    }
-                }
-            } else if xray && model.has_mine_at(x, y).unwrap() {
-                if model.is_flagged_at(x, y).unwrap() {
-                    '✅'
-                } else {
-                    '💣'
-                }
-            } else if model.is_flagged_at(x, y).unwrap() {
-                '🚩'
-            } else {
-                '■'
-            });
-            line.push(' ');
         }
-        // remove final trailing space
-        line.pop();
-        println!("{}", line);
+        // xray peeks at the hidden mines, for showing the final board
+        let peeked_mine = xray && model.has_mine_at(x, y) == Some(true);
+        let item = match model.zone_state_at(x, y) {
+            // masked out of the board
+            None => " ".to_string(),
+            Some(ZoneState::RevealedMine) if exploded == Some(Position::new(x, y)) => {
+                "💥".to_string()
+            }
+            Some(ZoneState::RevealedMine) if model.is_flagged_at(x, y).unwrap() => "✅".to_string(),
+            Some(ZoneState::RevealedMine) => "💣".to_string(),
+            // a 0 between a mine and an anti-mine is still shown
+            Some(ZoneState::Revealed { adjacent })
+                if adjacent != 0 || model.mines_adjacent_to(x, y).unwrap() > 0 =>
+            {
+                adjacent.to_string()
+            }
+            Some(ZoneState::Revealed { .. }) => "□".to_string(),
+            Some(ZoneState::Hidden {
+                flagged: false,
+                exposed: true,
+                ..
+            }) => "💣".to_string(),
+            Some(ZoneState::Hidden { flagged: true, .. }) if peeked_mine => "✅".to_string(),
+            Some(ZoneState::Hidden { flagged: false, .. }) if peeked_mine => "💣".to_string(),
+            Some(ZoneState::Hidden { flagged: true, .. }) => "🚩".to_string(),
+            Some(ZoneState::Hidden {
+                questioned: true, ..
+            }) => "?".to_string(),
+            Some(ZoneState::Hidden { .. }) => "■".to_string(),
+        };
+        line.push_str(&format!("{0:>1$} ", item, x_item_width));
+        if x == model.width() - 1 {
+            // remove final trailing space
+            line.pop();
+            if totals {
+                line.push_str(&format!(" | {}", row_totals[y as usize]));
+            }
+            println!("{}", line);
+        }
     }
+    if model.wraps() {
+        println!("(the board wraps around: opposite edges are adjacent)");
+    }
+}
+
+/**
+ * How much of the board's mine-free area has been revealed, rounded down.
+ * Only meaningful while no mine has been revealed.
+ */
+fn cleared_percentage(model: &MinesweeperModel) -> u32 {
+    let revealed = model.num_revealed() as u64;
+    let safe = revealed + model.remaining_safe() as u64;
+    (100 * revealed).checked_div(safe).unwrap_or(100) as u32
 }
 
 /**
  * given an integer, produces how many digits are needed
  * to represent that number in base-10 (without leading zeros)
  */
-fn num_digits_b10(number: u32) -> usize {
-    (number as f64).log10().floor() as usize + 1
+fn num_digits_b10(mut number: u32) -> usize {
+    let mut digits = 1;
+    while number >= 10 {
+        number /= 10;
+        digits += 1;
+    }
+    digits
 }
 
 /**
  * Produces the x-axis as a String
- * 
+ *
  * model_width is the width of the MinesweeperModel, and number_width is how many
  * digits are needed to represent model_width in base-10 (without leading zeros)
  */
@@ -0,0 +1,151 @@
+use crate::model::{CreationError, Position};
+use alloc::vec;
+use alloc::vec::Vec;
+use core::fmt;
+
+/**
+ * The most zones wide or tall an MBF board can be, as each dimension is one byte
+ */
+pub const MAX_DIMENSION: u32 = u8::MAX as u32;
+
+/**
+ * The most mines an MBF board can have, as the mine count is two bytes
+ */
+pub const MAX_MINES: u32 = u16::MAX as u32;
+
+// width, height and mine count
+const HEADER_LEN: usize = 4;
+
+#[derive(Debug, Clone, Eq, PartialEq)]
+/**
+ * Produced by `Field::from_mbf` and `Field::to_mbf`
+ * `Truncated` indicates that the data ended after `found` of its
+ *     `expected` bytes
+ * `TooLarge` indicates that a board of the contained dimensions is wider
+ *     or taller than `MAX_DIMENSION`
+ * `TooManyMines` indicates that the contained number of mines is more
+ *     than `MAX_MINES`
+ * `DuplicateMine` indicates that the mine at the contained position was
+ *     listed more than once
+ * `MinesNotPlaced` indicates that the board's mines are yet to be placed
+ *     (see `Field::new_deferred`), so there is no layout to export
+ * `Creation` indicates that the data could not be turned into a Field,
+ *     for the contained reason
+ */
+pub enum MbfError {
+    Truncated { expected: usize, found: usize },
+    TooLarge { width: u32, height: u32 },
+    TooManyMines(u32),
+    DuplicateMine(Position),
+    MinesNotPlaced,
+    Creation(CreationError),
+}
+
+impl fmt::Display for MbfError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            MbfError::Truncated { expected, found } => write!(
+                f,
+                "the board should be {} bytes long, but is only {}",
+                expected, found
+            ),
+            MbfError::TooLarge { width, height } => write!(
+                f,
+                "a {}x{} board is more than {} zones wide or tall",
+                width, height, MAX_DIMENSION
+            ),
+            MbfError::TooManyMines(mines) => {
+                write!(f, "{} mines are more than {}", mines, MAX_MINES)
+            }
+            MbfError::DuplicateMine(position) => {
+                write!(f, "the mine at {} is listed more than once", position)
+            }
+            MbfError::MinesNotPlaced => write!(f, "the mines have not been placed yet"),
+            MbfError::Creation(e) => write!(f, "could not create the board: {}", e),
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for MbfError {}
+
+impl From<CreationError> for MbfError {
+    fn from(e: CreationError) -> Self {
+        MbfError::Creation(e)
+    }
+}
+
+/**
+ * The contents of an MBF board
+ */
+pub struct MbfBoard {
+    pub width: u32,
+    pub height: u32,
+    /**
+     * the (x, y) of every mine, in the order they were listed
+     */
+    pub mines: Vec<(u32, u32)>,
+}
+
+/**
+ * Reads an MBF board: the width and the height, a byte each, the number of
+ * mines, as 2 big-endian bytes, and then the x and the y of every mine,
+ * a byte each. Anything after the last mine is ignored.
+ */
+pub fn read_mbf(bytes: &[u8]) -> Result<MbfBoard, MbfError> {
+    let truncated = |expected| MbfError::Truncated {
+        expected,
+        found: bytes.len(),
+    };
+    if bytes.len() < HEADER_LEN {
+        return Err(truncated(HEADER_LEN));
+    }
+    let (width, height) = (bytes[0] as u32, bytes[1] as u32);
+    let num_mines = u16::from_be_bytes([bytes[2], bytes[3]]) as usize;
+    let expected = HEADER_LEN + 2 * num_mines;
+    if bytes.len() < expected {
+        return Err(truncated(expected));
+    }
+    // a flag for every position a byte each of x and y can name
+    let mut listed = vec![false; 1 << 16];
+    let mut mines = Vec::with_capacity(num_mines);
+    for mine in bytes[HEADER_LEN..expected].chunks_exact(2) {
+        let position = (mine[0] as u32, mine[1] as u32);
+        let index = (mine[0] as usize) << 8 | mine[1] as usize;
+        if listed[index] {
+            return Err(MbfError::DuplicateMine(position.into()));
+        }
+        listed[index] = true;
+        mines.push(position);
+    }
+    Ok(MbfBoard {
+        width,
+        height,
+        mines,
+    })
+}
+
+/**
+ * Writes an MBF board in the layout read by `read_mbf`
+ */
+pub fn write_mbf(board: &MbfBoard) -> Result<Vec<u8>, MbfError> {
+    if board.width > MAX_DIMENSION || board.height > MAX_DIMENSION {
+        return Err(MbfError::TooLarge {
+            width: board.width,
+            height: board.height,
+        });
+    }
+    let num_mines = board.mines.len() as u32;
+    if num_mines > MAX_MINES {
+        return Err(MbfError::TooManyMines(num_mines));
+    }
+    let mut bytes = Vec::with_capacity(HEADER_LEN + 2 * board.mines.len());
+    bytes.push(board.width as u8);
+    bytes.push(board.height as u8);
+    bytes.extend_from_slice(&(num_mines as u16).to_be_bytes());
+    for &(x, y) in &board.mines {
+        bytes.push(x as u8);
+        bytes.push(y as u8);
+    }
+    Ok(bytes)
+}
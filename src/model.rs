@@ -1,268 +1,5874 @@
-//#![allow(dead_code)]
+use crate::ascii::{self, AsciiError, AsciiZone};
+use crate::mbf::{self, MbfBoard, MbfError};
+#[cfg(feature = "std")]
+use crate::pbm::{self, PbmError};
+#[cfg(feature = "std")]
+use crate::save::{self, SaveData, SaveError};
+use crate::share::{self, ShareCodeError};
+use crate::solver;
+use alloc::collections::BTreeMap;
+use alloc::format;
+use alloc::string::{String, ToString};
+use alloc::sync::Arc;
+use alloc::vec;
+use alloc::vec::Vec;
+use core::cell::OnceCell;
+use core::convert::TryFrom;
+use core::fmt;
+use core::iter::FromIterator;
+use core::ops::Index;
+use core::str::FromStr;
+use rand::{seq::SliceRandom, Rng, RngCore, SeedableRng};
+use rand_chacha::ChaCha8Rng;
+#[cfg(feature = "std")]
+use std::io::{self, BufRead, Read, Write};
 
-use rand::Rng;
-use std::collections::HashSet;
+use crate::topology::{Hex6, Layered, Standard8, Torus8};
+pub use crate::topology::{Kernel, Tiling, Topology};
 
 pub type MinesweeperModel = Field;
 pub type ModelResult<T> = Result<T, ErrorKind>;
 
+// how many layouts `Field::new_solvable` tries before giving up
+const MAX_SOLVABLE_ATTEMPTS: u32 = 1000;
+// the reveal stamp of a zone that is still hidden (see `Field::reveal_order_at`)
+const UNSTAMPED: u32 = u32::MAX;
+
+// the generator behind every seeded constructor. Its output is fully
+// specified, so the same seed gives the same layout on every platform.
+type SeededRng = ChaCha8Rng;
+
 #[derive(Debug, Copy, Clone, Eq, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[non_exhaustive]
 /**
- * Enumeration for use in a ModelResult
- * The `OutOfBounds` variant indicates that the given
- * coordinates are not in range of the Model's dimensions
- * The `NoOp` variant indicates that the requested action would
+ * Enumeration for use in a ModelResult, displayed as a message for the player
+ * The `OutOfBounds` variant indicates that the coordinates of `pos` are not
+ * in range of the dimensions of the `width` by `height` Model,
+ * or have been masked out of it
+ * The `NoOp` variant indicates that the attempted `op` would
  * have no change or does not make sense for the given coordinate position
+ * The `Flagged` variant indicates that the zone can't be revealed while it
+ * has a flag on it
+ * The `LayoutMismatch` variant indicates that a snapshot was taken of a
+ * board with a different mine layout (see `Field::restore`)
+ * The `FlagLimitReached` variant indicates that no more flags can be
+ * planted until one is removed, `limit` having been planted already
+ * (see `Field::set_flag_limit`)
+ * The `GameOver` variant indicates that the game has been won or lost, so
+ * the board can no longer be changed (see `MinesweeperController::state`)
+ * The `SplitsLayers` variant indicates that a rectangle of a Field made of
+ * layers `layer_height` tall cuts across a layer without lying inside it
+ * (see `Field::subfield`)
  */
 pub enum ErrorKind {
-    OutOfBounds,
-    NoOp,
+    OutOfBounds {
+        pos: Position,
+        width: u32,
+        height: u32,
+    },
+    NoOp {
+        op: Operation,
+    },
+    Flagged,
+    LayoutMismatch,
+    FlagLimitReached {
+        limit: u32,
+    },
+    GameOver,
+    SplitsLayers {
+        layer_height: u32,
+    },
+}
+
+impl fmt::Display for ErrorKind {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ErrorKind::OutOfBounds { pos, width, height } => {
+                write!(f, "{} is not on the {}x{} board", pos, width, height)
+            }
+            ErrorKind::NoOp { op } => match op {
+                Operation::Reveal => write!(f, "that zone has already been revealed"),
+                Operation::Flag => write!(f, "that zone is already flagged or revealed"),
+                Operation::Unflag => write!(f, "that zone has no flag to remove"),
+                Operation::Mark => write!(f, "a revealed zone can't be marked"),
+                Operation::Chord => write!(f, "there is nothing to chord there"),
+                Operation::AddMine => write!(f, "a mine can't be added there"),
+                Operation::RemoveMine => write!(f, "there is no mine to remove there"),
+                Operation::ExposeMines | Operation::Crop => {
+                    write!(f, "the mines have not been placed yet")
+                }
+                Operation::Guess => write!(f, "there is no zone left to guess"),
+                Operation::FlagAllMines => write!(f, "the game is still going"),
+            },
+            ErrorKind::Flagged => write!(f, "that zone is flagged; remove the flag first"),
+            ErrorKind::LayoutMismatch => {
+                write!(f, "the snapshot was taken of a different board")
+            }
+            ErrorKind::FlagLimitReached { limit } => {
+                write!(f, "all {} flags have been planted; remove one first", limit)
+            }
+            ErrorKind::GameOver => write!(f, "the game is over"),
+            ErrorKind::SplitsLayers { layer_height } => write!(
+                f,
+                "the rectangle must hold whole layers {} rows tall, or lie inside one",
+                layer_height
+            ),
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for ErrorKind {}
+
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+/**
+ * What was being attempted when an ErrorKind::NoOp came up
+ * `Reveal`, `Flag`, `Unflag` and `Mark` act on a single zone
+ *     (see `Field::reveal_at`, `Field::change_flag_at`, `Field::cycle_mark_at`)
+ * `Chord` is `MinesweeperController::chord_at`
+ * `AddMine` and `RemoveMine` are `Field::add_mine_at` and `Field::remove_mine_at`
+ * `ExposeMines` is `Field::expose_mines`
+ * `Crop` is `Field::subfield`
+ * `Guess` is `MinesweeperController::guess_best`
+ * `FlagAllMines` is `MinesweeperController::flag_all_mines`
+ */
+pub enum Operation {
+    Reveal,
+    Flag,
+    Unflag,
+    Mark,
+    Chord,
+    AddMine,
+    RemoveMine,
+    ExposeMines,
+    Crop,
+    Guess,
+    FlagAllMines,
+}
+
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash, Ord, PartialOrd)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+/**
+ * A position on a Field, naming its coordinates so that they can't be swapped
+ * by accident. Converts to and from an (x, y) tuple, and orders like one:
+ * by x, then by y. Displayed as `(x, y)`.
+ */
+pub struct Position {
+    pub x: u32,
+    pub y: u32,
+}
+
+impl Position {
+    pub fn new(x: u32, y: u32) -> Self {
+        Position { x, y }
+    }
+}
+
+impl From<(u32, u32)> for Position {
+    fn from((x, y): (u32, u32)) -> Self {
+        Position { x, y }
+    }
+}
+
+impl From<Position> for (u32, u32) {
+    fn from(position: Position) -> Self {
+        (position.x, position.y)
+    }
+}
+
+impl fmt::Display for Position {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "({}, {})", self.x, self.y)
+    }
+}
+
+#[derive(Clone, Default, Eq, PartialEq)]
+/**
+ * A set of (x, y) positions, kept as a sorted Vec without repeats, so that
+ * it needs nothing beyond `alloc`. Looking a position up takes logarithmic
+ * time, and so does inserting one, aside from shifting the positions after
+ * it: a set built all at once is best collected from an iterator.
+ * Iterates in the order of `Position`: by x, then by y.
+ */
+pub struct PositionSet {
+    positions: Vec<(u32, u32)>,
+}
+
+impl PositionSet {
+    pub fn new() -> Self {
+        PositionSet::default()
+    }
+
+    pub fn len(&self) -> usize {
+        self.positions.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.positions.is_empty()
+    }
+
+    pub fn contains(&self, position: &(u32, u32)) -> bool {
+        self.positions.binary_search(position).is_ok()
+    }
+
+    /**
+     * Adds the given position, returning false if it was already there
+     */
+    pub fn insert(&mut self, position: (u32, u32)) -> bool {
+        match self.positions.binary_search(&position) {
+            Ok(_) => false,
+            Err(index) => {
+                self.positions.insert(index, position);
+                true
+            }
+        }
+    }
+
+    /**
+     * true if every position in this set is also in the other
+     */
+    pub fn is_subset(&self, other: &PositionSet) -> bool {
+        self.len() <= other.len() && self.iter().all(|position| other.contains(position))
+    }
+
+    pub fn iter(&self) -> core::slice::Iter<'_, (u32, u32)> {
+        self.positions.iter()
+    }
+}
+
+impl fmt::Debug for PositionSet {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_set().entries(self.iter()).finish()
+    }
+}
+
+impl FromIterator<(u32, u32)> for PositionSet {
+    fn from_iter<I: IntoIterator<Item = (u32, u32)>>(iter: I) -> Self {
+        let mut positions: Vec<_> = iter.into_iter().collect();
+        positions.sort_unstable();
+        positions.dedup();
+        PositionSet { positions }
+    }
+}
+
+impl Extend<(u32, u32)> for PositionSet {
+    fn extend<I: IntoIterator<Item = (u32, u32)>>(&mut self, iter: I) {
+        self.positions.extend(iter);
+        self.positions.sort_unstable();
+        self.positions.dedup();
+    }
+}
+
+impl IntoIterator for PositionSet {
+    type Item = (u32, u32);
+    type IntoIter = alloc::vec::IntoIter<(u32, u32)>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.positions.into_iter()
+    }
+}
+
+impl<'a> IntoIterator for &'a PositionSet {
+    type Item = &'a (u32, u32);
+    type IntoIter = core::slice::Iter<'a, (u32, u32)>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.positions.iter()
+    }
+}
+
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+/**
+ * Symmetries a mine layout can be generated with (see `Field::new_symmetric`)
+ * `Rotational` layouts look the same after a 180 degree rotation,
+ *     i.e. (x, y) mirrors (width - 1 - x, height - 1 - y)
+ * `MirrorX` layouts are mirrored across the vertical center line,
+ *     i.e. (x, y) mirrors (width - 1 - x, y)
+ * `MirrorY` layouts are mirrored across the horizontal center line,
+ *     i.e. (x, y) mirrors (x, height - 1 - y)
+ */
+pub enum Symmetry {
+    None,
+    Rotational,
+    MirrorX,
+    MirrorY,
+}
+
+impl Symmetry {
+    /**
+     * The position which must have a mine exactly when the given position does
+     */
+    pub fn mirror(self, x: u32, y: u32, width: u32, height: u32) -> (u32, u32) {
+        match self {
+            Symmetry::None => (x, y),
+            Symmetry::Rotational => (width - 1 - x, height - 1 - y),
+            Symmetry::MirrorX => (width - 1 - x, y),
+            Symmetry::MirrorY => (x, height - 1 - y),
+        }
+    }
+}
+
+/**
+ * Decides where the mines of a new Field go (see `Field::new_with_strategy`)
+ */
+pub trait PlacementStrategy {
+    /**
+     * Chooses exactly num_mines distinct positions within the given
+     * dimensions, drawing any randomness it needs from rng.
+     * num_mines is never more than width * height.
+     */
+    fn place(
+        &mut self,
+        width: u32,
+        height: u32,
+        num_mines: u32,
+        rng: &mut dyn RngCore,
+    ) -> Result<PositionSet, CreationError>;
+}
+
+#[derive(Debug, Copy, Clone, Default, Eq, PartialEq)]
+/**
+ * Gives every position the same chance of receiving a mine
+ */
+pub struct UniformRandom;
+
+impl PlacementStrategy for UniformRandom {
+    fn place(
+        &mut self,
+        width: u32,
+        height: u32,
+        num_mines: u32,
+        mut rng: &mut dyn RngCore,
+    ) -> Result<PositionSet, CreationError> {
+        Ok(Field::generate_placements(
+            num_mines,
+            width,
+            height,
+            &mut rng,
+            &PositionSet::new(),
+        ))
+    }
+}
+
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+/**
+ * Places mines with the contained symmetry (see `Field::new_symmetric`)
+ */
+pub struct Symmetric(pub Symmetry);
+
+impl PlacementStrategy for Symmetric {
+    fn place(
+        &mut self,
+        width: u32,
+        height: u32,
+        num_mines: u32,
+        mut rng: &mut dyn RngCore,
+    ) -> Result<PositionSet, CreationError> {
+        let Symmetric(symmetry) = *self;
+        let num_mines = num_mines as usize;
+        let mut singles = Vec::new();
+        let mut pairs = Vec::new();
+        for x in 0..width {
+            for y in 0..height {
+                let mirror = symmetry.mirror(x, y, width, height);
+                if mirror == (x, y) {
+                    singles.push((x, y));
+                } else if (x, y) < mirror {
+                    pairs.push(((x, y), mirror));
+                }
+            }
+        }
+        // choose how many mines go on their own, keeping roughly the same
+        // density among single positions as there is across the whole board
+        let parity = num_mines % 2;
+        let fewest_singles = num_mines.saturating_sub(2 * pairs.len());
+        let fewest_singles = fewest_singles + (fewest_singles % 2 != parity) as usize;
+        let most_singles = singles.len().min(num_mines);
+        let most_singles = match most_singles.checked_sub((most_singles % 2 != parity) as usize) {
+            Some(most_singles) if most_singles >= fewest_singles => most_singles,
+            _ => return Err(CreationError::UnsatisfiableSymmetry),
+        };
+        let num_zones = (width * height) as usize;
+        let target = num_mines * singles.len() / num_zones;
+        let target = target + (target % 2 != parity) as usize;
+        let num_singles = target.max(fewest_singles).min(most_singles);
+
+        singles.shuffle(&mut rng);
+        pairs.shuffle(&mut rng);
+        Ok(singles
+            .into_iter()
+            .take(num_singles)
+            .chain(
+                pairs
+                    .into_iter()
+                    .take((num_mines - num_singles) / 2)
+                    .flat_map(|(a, b)| vec![a, b]),
+            )
+            .collect())
+    }
+}
+
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+/**
+ * Built-in distributions of mines for `Field::new_with_profile`
+ * `Uniform` gives every position the same chance of receiving a mine
+ * `CenterHeavy` makes positions near the center of the board more likely to receive mines
+ * `EdgeHeavy` makes positions near the edges and corners more likely to receive mines
+ */
+pub enum WeightProfile {
+    Uniform,
+    CenterHeavy,
+    EdgeHeavy,
+}
+
+#[cfg(feature = "std")]
+impl WeightProfile {
+    /**
+     * The relative likelihood of the given position receiving a mine on a
+     * board of the given dimensions. Always positive.
+     * Needs the `std` feature, for the square root.
+     */
+    pub fn weight(self, x: u32, y: u32, width: u32, height: u32) -> f64 {
+        // distance from the center, scaled so that the corners are at 1
+        let center_x = (width - 1) as f64 / 2.0;
+        let center_y = (height - 1) as f64 / 2.0;
+        let dx = (x as f64 - center_x) / center_x.max(1.0);
+        let dy = (y as f64 - center_y) / center_y.max(1.0);
+        let distance = ((dx * dx + dy * dy) / 2.0).sqrt();
+        match self {
+            WeightProfile::Uniform => 1.0,
+            WeightProfile::CenterHeavy => (1.0 - distance).powi(2) + 0.05,
+            WeightProfile::EdgeHeavy => distance.powi(2) + 0.05,
+        }
+    }
+}
+
+#[derive(Debug, Copy, Clone, Default, Eq, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+/**
+ * Options controlling how the mines of a deferred Field are placed
+ * (see `Field::new_deferred_with_options`)
+ * If `safe_opening` is true, then the zones adjacent to the first zone revealed
+ * are kept free of mines too, so that the first reveal always cascades.
+ * When there isn't enough room for that, only the first zone revealed is kept free.
+ */
+pub struct GenerationOptions {
+    pub safe_opening: bool,
+}
+
+#[derive(Debug, Clone, Eq, PartialEq)]
+/**
+ * Produced when a Field could not be created
+ * `ZeroWidth` and `ZeroHeight` indicate that a dimension of the Field was 0
+ * `ZeroDepth` indicates that a layered Field was to have no layers
+ * `TooLarge` indicates that a Field of the contained dimensions would have
+ *     more than `u32::MAX` zones
+ * `TooManyMines` indicates that more mines were requested than the Field has room for
+ * `PlacementsOutOfBounds` lists every requested mine placement that was not
+ *     within the Field's dimensions
+ * `InvalidMask` indicates that a mask did not have exactly one entry per position
+ * `ZoneOverfilled` indicates that more than 255 mines were to be buried
+ *     at the contained position
+ * `InvalidDensity` indicates that a mine density was NaN or not between 0 and 1
+ * `InvalidWeight` indicates that the weight of the contained position was
+ *     negative or not finite
+ * `FirstClickOutOfBounds` indicates that the given first click was not
+ *     within the Field's dimensions
+ * `UnsatisfiableSymmetry` indicates that the requested number of mines
+ *     could not be arranged with the requested symmetry
+ * `WrongMineCount` indicates that a PlacementStrategy placed a different
+ *     number of mines than it was asked to
+ * `AttemptsExhausted` indicates that no suitable layout was generated
+ *     within the contained number of attempts
+ * `ConflictingOptions` indicates that two FieldBuilder options which can't
+ *     be combined were both set
+ * `MissingOption` indicates that a FieldBuilder option was set without
+ *     another option it depends on
+ * `UnevenRows` indicates that the contained row of a grid did not have
+ *     as many entries as the first row
+ * `InvalidLiarFraction` indicates that a fraction of liars was NaN or not
+ *     between 0 and 1
+ * `AmbiguousMine` indicates that the contained position was to hold both
+ *     a mine and an anti-mine
+ */
+pub enum CreationError {
+    ZeroWidth,
+    ZeroHeight,
+    ZeroDepth,
+    TooLarge {
+        width: u32,
+        height: u32,
+    },
+    TooManyMines {
+        requested: u32,
+        capacity: u32,
+    },
+    PlacementsOutOfBounds(Vec<Position>),
+    InvalidMask,
+    ZoneOverfilled {
+        position: Position,
+    },
+    InvalidDensity,
+    InvalidWeight {
+        position: Position,
+    },
+    FirstClickOutOfBounds,
+    UnsatisfiableSymmetry,
+    WrongMineCount {
+        requested: u32,
+        placed: u32,
+    },
+    AttemptsExhausted {
+        attempts: u32,
+    },
+    ConflictingOptions {
+        first: &'static str,
+        second: &'static str,
+    },
+    MissingOption {
+        option: &'static str,
+        required_by: &'static str,
+    },
+    UnevenRows {
+        row: usize,
+    },
+    InvalidLiarFraction,
+    AmbiguousMine {
+        position: Position,
+    },
+}
+
+impl fmt::Display for CreationError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            CreationError::ZeroWidth => write!(f, "the width must be greater than 0"),
+            CreationError::ZeroHeight => write!(f, "the height must be greater than 0"),
+            CreationError::ZeroDepth => write!(f, "the depth must be greater than 0"),
+            CreationError::TooLarge { width, height } => {
+                write!(f, "a {}x{} board has too many zones", width, height)
+            }
+            CreationError::TooManyMines {
+                requested,
+                capacity,
+            } => write!(
+                f,
+                "{} mines were requested, but there is only room for {}",
+                requested, capacity
+            ),
+            CreationError::PlacementsOutOfBounds(positions) => {
+                let positions: Vec<_> = positions.iter().map(|p| p.to_string()).collect();
+                write!(f, "mine placements out of bounds: {}", positions.join(", "))
+            }
+            CreationError::InvalidMask => {
+                write!(f, "the mask must have one entry for every position")
+            }
+            CreationError::ZoneOverfilled { position } => {
+                write!(f, "too many mines at {}", position)
+            }
+            CreationError::InvalidDensity => write!(f, "the density must be between 0 and 1"),
+            CreationError::InvalidWeight { position } => {
+                write!(f, "the weight at {} is negative or not finite", position)
+            }
+            CreationError::FirstClickOutOfBounds => write!(f, "the first click is out of bounds"),
+            CreationError::UnsatisfiableSymmetry => {
+                write!(
+                    f,
+                    "that many mines can't be arranged with the requested symmetry"
+                )
+            }
+            CreationError::WrongMineCount { requested, placed } => write!(
+                f,
+                "{} mines were requested, but {} were placed",
+                requested, placed
+            ),
+            CreationError::AttemptsExhausted { attempts } => {
+                write!(f, "no suitable layout was found in {} attempts", attempts)
+            }
+            CreationError::ConflictingOptions { first, second } => {
+                write!(f, "{} and {} can't be combined", first, second)
+            }
+            CreationError::MissingOption {
+                option,
+                required_by,
+            } => write!(f, "{} requires {}", required_by, option),
+            CreationError::UnevenRows { row } => {
+                write!(f, "row {} is not as long as the first row", row)
+            }
+            CreationError::InvalidLiarFraction => {
+                write!(f, "the fraction of liars must be between 0 and 1")
+            }
+            CreationError::AmbiguousMine { position } => {
+                write!(f, "{} can't hold both a mine and an anti-mine", position)
+            }
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for CreationError {}
+
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+/**
+ * Produced by `Field::check_invariants`, naming the first count found to
+ * disagree with the zones, as `stored` against the `actual` recount
+ * `AdjacentMines` and `AdjacentNet` are the adjacent mine counts of the zone
+ *     at `position` (see `Field::mines_adjacent_to`, `Field::net_count_at`)
+ * `Mines`, `Flagged`, `Revealed` and `RevealedSafe` are the counts of mines,
+ *     flags, revealed zones and revealed zones without a mine
+ * `SafeZones` and `MinedZones` are the counts of zones without and with a mine
+ * `Exposed` and `FlaggedExposed` are the counts of exposed mines, and of
+ *     those with a flag (see `Field::expose_mines`)
+ * `RowMines` and `ColMines` are the mine counts of the row or column at
+ *     `index` (see `Field::row_mine_counts`)
+ * `Dimensions` indicates that the zones, or the mine counts of the rows or
+ *     the columns, don't number as many as the width and height call for
+ * `MalformedZone` indicates that the zone at `position` is in a state no
+ *     move could have left it in, such as being both flagged and questioned
+ */
+pub enum InvariantViolation {
+    AdjacentMines {
+        position: Position,
+        stored: u32,
+        actual: u32,
+    },
+    AdjacentNet {
+        position: Position,
+        stored: i32,
+        actual: i32,
+    },
+    Mines {
+        stored: u32,
+        actual: u32,
+    },
+    Flagged {
+        stored: u32,
+        actual: u32,
+    },
+    Revealed {
+        stored: u32,
+        actual: u32,
+    },
+    RevealedSafe {
+        stored: u32,
+        actual: u32,
+    },
+    SafeZones {
+        stored: u32,
+        actual: u32,
+    },
+    MinedZones {
+        stored: u32,
+        actual: u32,
+    },
+    Exposed {
+        stored: u32,
+        actual: u32,
+    },
+    FlaggedExposed {
+        stored: u32,
+        actual: u32,
+    },
+    RowMines {
+        index: u32,
+        stored: u32,
+        actual: u32,
+    },
+    ColMines {
+        index: u32,
+        stored: u32,
+        actual: u32,
+    },
+    Dimensions,
+    MalformedZone {
+        position: Position,
+    },
+}
+
+impl fmt::Display for InvariantViolation {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let (what, stored, actual) = match *self {
+            InvariantViolation::AdjacentMines {
+                position,
+                stored,
+                actual,
+            } => (
+                format!("adjacent mine count at {}", position),
+                stored as i64,
+                actual as i64,
+            ),
+            InvariantViolation::AdjacentNet {
+                position,
+                stored,
+                actual,
+            } => (
+                format!("adjacent net count at {}", position),
+                stored as i64,
+                actual as i64,
+            ),
+            InvariantViolation::Mines { stored, actual } => {
+                ("mine count".to_string(), stored as i64, actual as i64)
+            }
+            InvariantViolation::Flagged { stored, actual } => {
+                ("flag count".to_string(), stored as i64, actual as i64)
+            }
+            InvariantViolation::Revealed { stored, actual } => {
+                ("revealed count".to_string(), stored as i64, actual as i64)
+            }
+            InvariantViolation::RevealedSafe { stored, actual } => (
+                "revealed safe zone count".to_string(),
+                stored as i64,
+                actual as i64,
+            ),
+            InvariantViolation::SafeZones { stored, actual } => {
+                ("safe zone count".to_string(), stored as i64, actual as i64)
+            }
+            InvariantViolation::MinedZones { stored, actual } => {
+                ("mined zone count".to_string(), stored as i64, actual as i64)
+            }
+            InvariantViolation::Exposed { stored, actual } => (
+                "exposed mine count".to_string(),
+                stored as i64,
+                actual as i64,
+            ),
+            InvariantViolation::FlaggedExposed { stored, actual } => (
+                "flagged exposed mine count".to_string(),
+                stored as i64,
+                actual as i64,
+            ),
+            InvariantViolation::RowMines {
+                index,
+                stored,
+                actual,
+            } => (
+                format!("mine count of row {}", index),
+                stored as i64,
+                actual as i64,
+            ),
+            InvariantViolation::ColMines {
+                index,
+                stored,
+                actual,
+            } => (
+                format!("mine count of column {}", index),
+                stored as i64,
+                actual as i64,
+            ),
+            InvariantViolation::Dimensions => {
+                return write!(f, "the zones don't match the width and height");
+            }
+            InvariantViolation::MalformedZone { position } => {
+                return write!(f, "the zone at {} is malformed", position);
+            }
+        };
+        write!(f, "the {} is {}, but should be {}", what, stored, actual)
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for InvariantViolation {}
+
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+/**
+ * Produced by `Field::reveal_expecting`
+ * The `Model` variant indicates that the reveal itself failed, for the contained reason
+ * The `Mispredicted` variant indicates that the zone was revealed,
+ * but `has_mine` is what it actually contained
+ */
+pub enum PredictionError {
+    Model(ErrorKind),
+    Mispredicted { has_mine: bool },
+}
+
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+/**
+ * One of the four corners of a Field.
+ * The top of a Field is the row where y == 0,
+ * and the left is the column where x == 0.
+ */
+pub enum Corner {
+    TopLeft,
+    TopRight,
+    BottomLeft,
+    BottomRight,
+}
+
+impl Corner {
+    /**
+     * The coordinates of this corner on a Field with the given dimensions
+     */
+    pub fn position(self, width: u32, height: u32) -> (u32, u32) {
+        match self {
+            Corner::TopLeft => (0, 0),
+            Corner::TopRight => (width - 1, 0),
+            Corner::BottomLeft => (0, height - 1),
+            Corner::BottomRight => (width - 1, height - 1),
+        }
+    }
 }
 
-struct Zone {
-    flagged: bool,
-    revealed: bool,
-    has_mine: bool,
-    adj_mine_count: u32,
-}
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+/**
+ * Decides which mines `Field::expose_mines` shows to the player
+ * `Random` picks them at random
+ * `MostIsolated` picks the mines with the fewest mines around them,
+ *     preferring lower x, then lower y, among equally isolated mines
+ */
+pub enum MineSelection {
+    Random,
+    MostIsolated,
+}
+
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Ord, PartialOrd)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+/**
+ * A coarse difficulty rating for a board (see `BoardAnalysis::difficulty`)
+ * `Trivial` boards are cleared by a single click
+ * `Easy`, `Medium` and `Hard` boards need more clicks per mine-free zone
+ *     (going by 3BV), in that order
+ */
+pub enum Difficulty {
+    Trivial,
+    Easy,
+    Medium,
+    Hard,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+/**
+ * The shape of a board's mine layout (see `Field::analyze`), independent of
+ * what has been revealed or flagged. Says nothing about where the mines are.
+ */
+pub struct BoardAnalysis {
+    /**
+     * the board's 3BV (see `Field::board_3bv`)
+     */
+    pub bbbv: u32,
+    /**
+     * the number of openings: connected regions of mine-free zones
+     * without adjacent mines (see `Field::opening_id_at`)
+     */
+    pub num_openings: u32,
+    /**
+     * the number of connected regions of numbered mine-free zones which
+     * don't border any opening, and so must each be cleared by hand
+     */
+    pub num_islands: u32,
+    /**
+     * the number of zones in the largest opening, 0 if there are none
+     */
+    pub largest_opening: u32,
+    /**
+     * the number of mines per zone
+     */
+    pub mine_density: f64,
+    pub difficulty: Difficulty,
+}
+
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+/**
+ * The marks a player can put on a hidden zone (see `Field::cycle_mark_at`)
+ * `Unmarked` is a zone without a mark
+ * `Flagged` is a zone with a flag, which counts towards `Field::num_flagged`
+ * `Questioned` is a zone with a question mark, which counts towards nothing
+ *     and is cleared when the zone is revealed or flagged
+ */
+pub enum Mark {
+    Unmarked,
+    Flagged,
+    Questioned,
+}
+
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+/**
+ * Something a player can see change on a Field (see `Field::drain_events`)
+ * `Revealed` is a zone without a mine being revealed, showing the `adjacent`
+ *     count of `Field::displayed_count_at`
+ * `MineRevealed` is a zone with a mine being revealed
+ * `MineExposed` is a hidden mine being shown to the player (see `Field::expose_mines`)
+ * `Flagged` and `Unflagged` are a flag being planted or removed. A flag
+ *     replaces any question mark.
+ * `Questioned` and `Unquestioned` are a question mark being put on or taken
+ *     off a zone by `Field::cycle_mark_at`. A reveal clears any question mark.
+ * `Reset` is any number of zones being covered back up and having their
+ *     marks changed at once, by `Field::reset` or `Field::restore`
+ */
+pub enum ModelEvent {
+    Revealed { pos: Position, adjacent: i32 },
+    MineRevealed { pos: Position },
+    MineExposed { pos: Position },
+    Flagged { pos: Position },
+    Unflagged { pos: Position },
+    Questioned { pos: Position },
+    Unquestioned { pos: Position },
+    Reset,
+}
+
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+/**
+ * Everything a player can see of a zone (see `Field::zone_state_at`)
+ * `Hidden` is a zone yet to be revealed, which is `exposed` if it is a mine
+ *     shown to the player from the start (see `Field::expose_mines`),
+ *     and `questioned` if it has a question mark (see `Field::cycle_mark_at`)
+ * `Revealed` is a revealed zone without a mine, showing the `adjacent` count
+ *     of `Field::displayed_count_at`
+ * `RevealedMine` is a revealed zone containing a mine
+ */
+pub enum ZoneState {
+    Hidden {
+        flagged: bool,
+        questioned: bool,
+        exposed: bool,
+    },
+    Revealed {
+        adjacent: i32,
+    },
+    RevealedMine,
+}
+
+#[derive(Clone, Copy, Eq, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+/**
+ * A zone, as the methods of a Field read and change it. Its yes-or-no
+ * states are packed into the bits of `bits` (see `Zone::FLAGGED` and co.),
+ * leaving room for the counts, which stay wide enough for several mines per
+ * zone and for large kernels. Most Fields store it in a single byte instead
+ * (see `Zones`).
+ */
+struct Zone {
+    bits: u8,
+    // more than 1 only on a Field with several mines per zone, see `Field::with_mine_counts`
+    mine_count: u8,
+    // how far the displayed count is off from adj_net_count, see `FieldBuilder::liars`
+    lie: i8,
+    // mines of either kind, see `Field::with_anti_mine_placements`
+    adj_mine_count: u32,
+    // mines minus anti-mines
+    adj_net_count: i32,
+}
+
+impl Zone {
+    const FLAGGED: u8 = 1;
+    // a question mark, see `Field::cycle_mark_at`. Never set along with FLAGGED.
+    const QUESTIONED: u8 = 1 << 1;
+    const REVEALED: u8 = 1 << 2;
+    // unset for positions masked out of the board, see `Field::with_mask`
+    const ACTIVE: u8 = 1 << 3;
+    // a mine shown to the player from the start, see `Field::expose_mines`
+    const EXPOSED: u8 = 1 << 4;
+    // mine_count counts anti-mines, see `Field::with_anti_mine_placements`
+    const ANTI: u8 = 1 << 5;
+
+    // the byte of a packed zone (see `pack`) holds adj_mine_count in its
+    // low 4 bits, and these bits for the rest
+    const PACKED_COUNT: u8 = 0xf;
+    const PACKED_MINE: u8 = 1 << 4;
+    const PACKED_FLAGGED: u8 = 1 << 5;
+    const PACKED_QUESTIONED: u8 = 1 << 6;
+    const PACKED_REVEALED: u8 = 1 << 7;
+
+    fn new(mine_count: u8) -> Self {
+        Zone {
+            bits: Zone::ACTIVE,
+            mine_count,
+            lie: 0,
+            adj_mine_count: 0,
+            adj_net_count: 0,
+        }
+    }
+
+    /**
+     * This zone in a single byte, or None if it doesn't fit in one: a zone
+     * fits if it holds at most one mine, has at most 15 mines around it, and
+     * is neither masked out, exposed, an anti-mine nor a liar
+     */
+    fn pack(&self) -> Option<u8> {
+        let marks = Zone::FLAGGED | Zone::QUESTIONED | Zone::REVEALED;
+        if self.bits & !marks != Zone::ACTIVE
+            || self.mine_count > 1
+            || self.lie != 0
+            || self.adj_mine_count > Zone::PACKED_COUNT as u32
+            || self.adj_net_count != self.adj_mine_count as i32
+        {
+            return None;
+        }
+        let mut byte = self.adj_mine_count as u8;
+        if self.has_mine() {
+            byte |= Zone::PACKED_MINE;
+        }
+        if self.flagged() {
+            byte |= Zone::PACKED_FLAGGED;
+        }
+        if self.questioned() {
+            byte |= Zone::PACKED_QUESTIONED;
+        }
+        if self.revealed() {
+            byte |= Zone::PACKED_REVEALED;
+        }
+        Some(byte)
+    }
+
+    /**
+     * The zone packed into the given byte by `pack`
+     */
+    const fn unpack(byte: u8) -> Zone {
+        let mut bits = Zone::ACTIVE;
+        if byte & Zone::PACKED_FLAGGED != 0 {
+            bits |= Zone::FLAGGED;
+        }
+        if byte & Zone::PACKED_QUESTIONED != 0 {
+            bits |= Zone::QUESTIONED;
+        }
+        if byte & Zone::PACKED_REVEALED != 0 {
+            bits |= Zone::REVEALED;
+        }
+        let count = (byte & Zone::PACKED_COUNT) as u32;
+        Zone {
+            bits,
+            mine_count: (byte & Zone::PACKED_MINE != 0) as u8,
+            lie: 0,
+            adj_mine_count: count,
+            adj_net_count: count as i32,
+        }
+    }
+
+    fn bit(&self, bit: u8) -> bool {
+        self.bits & bit != 0
+    }
+
+    fn set_bit(&mut self, bit: u8, value: bool) {
+        if value {
+            self.bits |= bit;
+        } else {
+            self.bits &= !bit;
+        }
+    }
+
+    fn flagged(&self) -> bool {
+        self.bit(Zone::FLAGGED)
+    }
+
+    fn set_flagged(&mut self, flagged: bool) {
+        self.set_bit(Zone::FLAGGED, flagged);
+    }
+
+    fn questioned(&self) -> bool {
+        self.bit(Zone::QUESTIONED)
+    }
+
+    fn set_questioned(&mut self, questioned: bool) {
+        self.set_bit(Zone::QUESTIONED, questioned);
+    }
+
+    fn revealed(&self) -> bool {
+        self.bit(Zone::REVEALED)
+    }
+
+    fn set_revealed(&mut self, revealed: bool) {
+        self.set_bit(Zone::REVEALED, revealed);
+    }
+
+    fn active(&self) -> bool {
+        self.bit(Zone::ACTIVE)
+    }
+
+    fn set_active(&mut self, active: bool) {
+        self.set_bit(Zone::ACTIVE, active);
+    }
+
+    fn exposed(&self) -> bool {
+        self.bit(Zone::EXPOSED)
+    }
+
+    fn set_exposed(&mut self, exposed: bool) {
+        self.set_bit(Zone::EXPOSED, exposed);
+    }
+
+    fn anti(&self) -> bool {
+        self.bit(Zone::ANTI)
+    }
+
+    fn set_anti(&mut self, anti: bool) {
+        self.set_bit(Zone::ANTI, anti);
+    }
+
+    fn has_mine(&self) -> bool {
+        self.mine_count > 0
+    }
+
+    fn displayed_count(&self) -> i32 {
+        self.adj_net_count + self.lie as i32
+    }
+
+    /**
+     * The contribution of this zone's mines to its neighbors' net counts
+     */
+    fn net_mines(&self) -> i32 {
+        if self.anti() {
+            -(self.mine_count as i32)
+        } else {
+            self.mine_count as i32
+        }
+    }
+}
+
+// every zone a byte can hold (see `Zone::pack`), unpacked,
+// for `Zones` to lend out references to
+static UNPACKED_ZONES: [Zone; 256] = {
+    let mut zones = [Zone::unpack(0); 256];
+    let mut byte = 0;
+    while byte < zones.len() {
+        zones[byte] = Zone::unpack(byte as u8);
+        byte += 1;
+    }
+    zones
+};
+
+#[derive(Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+/**
+ * The zones of a Field, column by column. Each zone takes a single byte
+ * (see `Zone::pack`) until one that doesn't fit in a byte is stored, as on
+ * a Field with anti-mines, liars, exposed mines, masked out positions,
+ * several mines to a zone or a topology with more than 15 neighbors:
+ * from then on, every zone is kept unpacked.
+ * Zones are read by indexing, and changed with `set` or `update`.
+ */
+enum Zones {
+    Packed(Vec<u8>),
+    Unpacked(Vec<Zone>),
+}
+
+impl Zones {
+    fn len(&self) -> usize {
+        match self {
+            Zones::Packed(bytes) => bytes.len(),
+            Zones::Unpacked(zones) => zones.len(),
+        }
+    }
+
+    fn iter(&self) -> impl Iterator<Item = &Zone> + '_ {
+        (0..self.len()).map(move |index| &self[index])
+    }
+
+    fn set(&mut self, index: usize, zone: Zone) {
+        if let Zones::Packed(bytes) = self {
+            match zone.pack() {
+                Some(byte) => {
+                    bytes[index] = byte;
+                    return;
+                }
+                None => self.unpack(),
+            }
+        }
+        if let Zones::Unpacked(zones) = self {
+            zones[index] = zone;
+        }
+    }
+
+    /**
+     * Changes the zone at the given index with the given function
+     */
+    fn update(&mut self, index: usize, change: impl FnOnce(&mut Zone)) {
+        let mut zone = self[index];
+        change(&mut zone);
+        self.set(index, zone);
+    }
+
+    fn push(&mut self, zone: Zone) {
+        if let Zones::Packed(bytes) = self {
+            match zone.pack() {
+                Some(byte) => {
+                    bytes.push(byte);
+                    return;
+                }
+                None => self.unpack(),
+            }
+        }
+        if let Zones::Unpacked(zones) = self {
+            zones.push(zone);
+        }
+    }
+
+    /**
+     * Stops packing the zones, for storing one that doesn't fit in a byte
+     */
+    fn unpack(&mut self) {
+        if let Zones::Packed(bytes) = self {
+            *self = Zones::Unpacked(bytes.iter().map(|&byte| Zone::unpack(byte)).collect());
+        }
+    }
+}
+
+impl Index<usize> for Zones {
+    type Output = Zone;
+
+    fn index(&self, index: usize) -> &Zone {
+        match self {
+            Zones::Packed(bytes) => &UNPACKED_ZONES[bytes[index] as usize],
+            Zones::Unpacked(zones) => &zones[index],
+        }
+    }
+}
+
+impl FromIterator<Zone> for Zones {
+    fn from_iter<I: IntoIterator<Item = Zone>>(iter: I) -> Self {
+        let mut zones = Zones::Packed(Vec::new());
+        for zone in iter {
+            zones.push(zone);
+        }
+        zones
+    }
+}
+
+impl PartialEq for Zones {
+    /**
+     * Zones are equal if they hold equal zones, packed or not
+     */
+    fn eq(&self, other: &Self) -> bool {
+        self.len() == other.len() && self.iter().eq(other.iter())
+    }
+}
+
+#[derive(Clone)]
+#[repr(transparent)]
+/**
+ * A read-only look at a zone, as a player sees it (see `Field::get`, or index
+ * a Field with `field[(x, y)]`). Nothing about a hidden zone's mines can be
+ * learned from it, unless the mine was exposed (see `Field::expose_mines`).
+ */
+pub struct ZoneView {
+    // repr(transparent), so that a &Zone can be viewed as a &ZoneView
+    zone: Zone,
+}
+
+impl ZoneView {
+    fn of(zone: &Zone) -> &ZoneView {
+        // ZoneView is a transparent wrapper around Zone, so they share a layout
+        unsafe { &*(zone as *const Zone as *const ZoneView) }
+    }
+
+    pub fn flagged(&self) -> bool {
+        self.zone.flagged()
+    }
+
+    pub fn questioned(&self) -> bool {
+        self.zone.questioned()
+    }
+
+    pub fn revealed(&self) -> bool {
+        self.zone.revealed()
+    }
+
+    /**
+     * The count shown on a revealed zone without a mine
+     * (see `Field::displayed_count_at`). None for any other zone.
+     */
+    pub fn adjacent_mines(&self) -> Option<i32> {
+        if self.zone.revealed() && !self.zone.has_mine() {
+            Some(self.zone.displayed_count())
+        } else {
+            None
+        }
+    }
+
+    /**
+     * Whether a revealed or exposed zone has a mine. None for any other zone.
+     */
+    pub fn has_mine(&self) -> Option<bool> {
+        if self.zone.revealed() || self.zone.exposed() {
+            Some(self.zone.has_mine())
+        } else {
+            None
+        }
+    }
+
+    /**
+     * The zone's ZoneState (see `Field::zone_state_at`)
+     */
+    pub fn state(&self) -> ZoneState {
+        let zone = &self.zone;
+        if !zone.revealed() {
+            ZoneState::Hidden {
+                flagged: zone.flagged(),
+                questioned: zone.questioned(),
+                exposed: zone.exposed(),
+            }
+        } else if zone.has_mine() {
+            ZoneState::RevealedMine
+        } else {
+            ZoneState::Revealed {
+                adjacent: zone.displayed_count(),
+            }
+        }
+    }
+}
+
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+/**
+ * What a player can see around a zone (see `Field::neighborhood_summary`)
+ * `flagged` counts the hidden neighbors known to be mines: those with a flag,
+ *     and the mines shown to the player from the start (see `Field::expose_mines`)
+ * `hidden` counts every other hidden neighbor
+ * `revealed` counts the revealed neighbors, mines included
+ * `adjacent` is the count shown on the zone itself, if it is a revealed zone
+ *     without a mine (see `ZoneView::adjacent_mines`)
+ */
+pub struct NeighborhoodSummary {
+    pub flagged: u32,
+    pub hidden: u32,
+    pub revealed: u32,
+    pub adjacent: Option<i32>,
+}
+
+#[derive(Debug, Clone, Default, Eq, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+/**
+ * One opening of a Field (see `Field::openings`)
+ * `cells` are the positions of the opening itself, which have no adjacent mines
+ * `border` are the numbered positions around it, which a cascade reveals with
+ *     it. A numbered position can border several openings.
+ * Both are in x-major order.
+ */
+pub struct Opening {
+    pub cells: Vec<(u32, u32)>,
+    pub border: Vec<(u32, u32)>,
+}
+
+#[derive(Debug, Clone, Eq, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+/**
+ * Exactly `mines` of the positions in `cells` contain a mine, going by what
+ * a player can see and trusting their flags (see `Field::constraints`)
+ */
+pub struct Constraint {
+    pub cells: Vec<(u32, u32)>,
+    pub mines: u32,
+}
+
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+/**
+ * An owned snapshot of everything a player can see of a Field
+ * (see `Field::player_view`): its dimensions, its rules, and the ZoneState of
+ * every zone. Whatever works from a PlayerView, such as
+ * `solver::deduce_from_view`, can't learn where the hidden mines are.
+ */
+pub struct PlayerView {
+    width: u32,
+    height: u32,
+    num_mines: u32,
+    num_flagged: u32,
+    max_mines_per_zone: u8,
+    has_liars: bool,
+    has_anti_mines: bool,
+    // indexed by x * height + y, None for positions masked out of the board
+    states: Vec<Option<ZoneState>>,
+    #[cfg_attr(feature = "serde", serde(with = "crate::topology::serde_topology"))]
+    topology: Arc<dyn Topology>,
+}
+
+impl PlayerView {
+    pub fn width(&self) -> u32 {
+        self.width
+    }
+
+    pub fn height(&self) -> u32 {
+        self.height
+    }
+
+    pub fn num_mines(&self) -> u32 {
+        self.num_mines
+    }
+
+    pub fn num_flagged(&self) -> u32 {
+        self.num_flagged
+    }
+
+    /**
+     * See `Field::max_mines_per_zone`
+     */
+    pub fn max_mines_per_zone(&self) -> u8 {
+        self.max_mines_per_zone
+    }
+
+    /**
+     * See `Field::has_liars`
+     */
+    pub fn has_liars(&self) -> bool {
+        self.has_liars
+    }
+
+    /**
+     * See `Field::has_anti_mines`
+     */
+    pub fn has_anti_mines(&self) -> bool {
+        self.has_anti_mines
+    }
+
+    /**
+     * The ZoneState at the given coordinates when the view was taken,
+     * or None if they are outside the board or masked out of it
+     */
+    pub fn state_at(&self, x: u32, y: u32) -> Option<ZoneState> {
+        if x < self.width && y < self.height {
+            self.states[(x * self.height + y) as usize]
+        } else {
+            None
+        }
+    }
+
+    /**
+     * Every zone with its ZoneState, in the row-major order of
+     * `Field::positions`. Positions masked out of the board are skipped.
+     */
+    pub fn zones(&self) -> impl Iterator<Item = ((u32, u32), ZoneState)> + '_ {
+        let width = self.width;
+        (0..self.height)
+            .flat_map(move |y| (0..width).map(move |x| (x, y)))
+            .filter_map(move |(x, y)| self.state_at(x, y).map(|state| ((x, y), state)))
+    }
+
+    /**
+     * Every zone yet to be revealed, flagged or not, in the order of `zones`
+     */
+    pub fn hidden_positions(&self) -> impl Iterator<Item = (u32, u32)> + '_ {
+        self.zones().filter_map(|(pos, state)| match state {
+            ZoneState::Hidden { .. } => Some(pos),
+            _ => None,
+        })
+    }
+
+    /**
+     * The positions adjacent to the given ones, as `Field::adjacent_positions`
+     * with the diagonals included would produce them
+     */
+    pub fn adjacent_positions(&self, x: u32, y: u32) -> Vec<(u32, u32)> {
+        self.topology
+            .neighbors(x, y, self.width, self.height)
+            .into_iter()
+            .filter(|&(adj_x, adj_y)| self.state_at(adj_x, adj_y).is_some())
+            .collect()
+    }
+
+    /**
+     * The view as a single line of JSON, for tools following a game, e.g.
+     *     {"width":2,"height":1,"mines":1,"flags_remaining":1,
+     *      "status":"playing","cells":[["hidden",1]]}
+     * `status` is written as given, e.g. "playing", "won" or "lost"
+     * (see `MinesweeperController::state_json`). `flags_remaining` is the
+     * number of mines less the number of flags, and can be negative.
+     * `cells` holds the rows of the board from the top, each zone drawn as
+     * "hidden", "flag", "question", "mine" for a revealed or exposed mine,
+     * its displayed count for any other revealed zone, or null if it is
+     * masked out of the board. Being a PlayerView, it never gives away a
+     * mine the player can't see.
+     */
+    pub fn to_json(&self, status: &str) -> String {
+        let rows: Vec<String> = (0..self.height)
+            .map(|y| {
+                let cells: Vec<String> = (0..self.width)
+                    .map(|x| match self.state_at(x, y) {
+                        None => "null".to_string(),
+                        Some(ZoneState::Hidden { exposed: true, .. })
+                        | Some(ZoneState::RevealedMine) => "\"mine\"".to_string(),
+                        Some(ZoneState::Hidden { flagged: true, .. }) => "\"flag\"".to_string(),
+                        Some(ZoneState::Hidden {
+                            questioned: true, ..
+                        }) => "\"question\"".to_string(),
+                        Some(ZoneState::Hidden { .. }) => "\"hidden\"".to_string(),
+                        Some(ZoneState::Revealed { adjacent }) => adjacent.to_string(),
+                    })
+                    .collect();
+                format!("[{}]", cells.join(","))
+            })
+            .collect();
+        let escaped: String = status
+            .chars()
+            .flat_map(|c| match c {
+                '"' | '\\' => vec!['\\', c],
+                _ => vec![c],
+            })
+            .collect();
+        format!(
+            "{{\"width\":{},\"height\":{},\"mines\":{},\"flags_remaining\":{},\"status\":\"{}\",\"cells\":[{}]}}",
+            self.width,
+            self.height,
+            self.num_mines,
+            self.num_mines as i64 - self.num_flagged as i64,
+            escaped,
+            rows.join(",")
+        )
+    }
+}
+
+#[derive(Debug, Clone, Eq, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+/**
+ * The reveals, flags and question marks of a Field at some point, along
+ * with its counters (see `Field::snapshot`). The mine layout doesn't change
+ * during a game, so it isn't copied; only its fingerprint is kept, so that
+ * the snapshot can't be restored onto a different board.
+ */
+pub struct FieldSnapshot {
+    layout_fingerprint: u64,
+    num_flagged: u32,
+    num_revealed: u32,
+    num_revealed_safe: u32,
+    // the bits of `FieldSnapshot::FLAGGED` and co. for every zone,
+    // indexed by x * height + y
+    marks: Vec<u8>,
+    // see `Field::reveal_order_at` and `Field::exploded_at`
+    reveal_order: Vec<u32>,
+    exploded: Vec<u32>,
+}
+
+impl FieldSnapshot {
+    const FLAGGED: u8 = 1;
+    const QUESTIONED: u8 = 2;
+    const REVEALED: u8 = 4;
+}
+
+#[derive(Debug, Clone)]
+/**
+ * Gathers the options for creating a Field, and checks that they fit
+ * together when the Field is built (see `Field::builder`).
+ * Unless set otherwise, a FieldBuilder builds a 10 by 10 Field with 10 mines
+ * from a randomly chosen seed, like `Field::new(10, 10, 10)`.
+ * `mines` and `density` are mutually exclusive. `first_click_safe` places
+ * the mines on the first reveal, so it can't be combined with the options
+ * deciding where mines go up front: `symmetry`, `safe_corner` and
+ * `solvable_from`, of which at most one can be set. Nor can it be combined
+ * with `liars` or `anti_mines`, which need the mines in place. `liars` and
+ * `anti_mines` can't be combined either, and neither can `liars` and
+ * `lazy_counts`. A `mask` can't be combined with `symmetry` or `solvable_from`.
+ */
+pub struct FieldBuilder {
+    width: u32,
+    height: u32,
+    mines: Option<u32>,
+    density: Option<f64>,
+    seed: Option<u64>,
+    first_click_safe: bool,
+    safe_opening: bool,
+    symmetry: Option<Symmetry>,
+    safe_corner: Option<Corner>,
+    solvable_from: Option<(u32, u32)>,
+    topology: Option<Arc<dyn Topology>>,
+    liars: Option<f64>,
+    anti_mines: Option<u32>,
+    lazy_counts: bool,
+    mask: Option<Vec<Vec<bool>>>,
+}
+
+impl Default for FieldBuilder {
+    fn default() -> Self {
+        FieldBuilder {
+            width: 10,
+            height: 10,
+            mines: None,
+            density: None,
+            seed: None,
+            first_click_safe: false,
+            safe_opening: false,
+            symmetry: None,
+            safe_corner: None,
+            solvable_from: None,
+            topology: None,
+            liars: None,
+            anti_mines: None,
+            lazy_counts: false,
+            mask: None,
+        }
+    }
+}
+
+impl FieldBuilder {
+    pub fn width(mut self, width: u32) -> Self {
+        self.width = width;
+        self
+    }
+
+    pub fn height(mut self, height: u32) -> Self {
+        self.height = height;
+        self
+    }
+
+    /**
+     * The number of mines, 10 unless set otherwise
+     */
+    pub fn mines(mut self, num_mines: u32) -> Self {
+        self.mines = Some(num_mines);
+        self
+    }
+
+    /**
+     * The fraction of zones containing mines, instead of a number of mines
+     * (see `Field::with_density`)
+     */
+    pub fn density(mut self, density: f64) -> Self {
+        self.density = Some(density);
+        self
+    }
+
+    /**
+     * The seed determining the mine layout, randomly chosen unless set.
+     * Without the `std` feature there is nothing to choose it with, and a
+     * Field built without a seed has the layout of seed 0.
+     */
+    pub fn seed(mut self, seed: u64) -> Self {
+        self.seed = Some(seed);
+        self
+    }
+
+    /**
+     * If true, mines are not placed until the first reveal, which never
+     * hits one (see `Field::new_deferred`)
+     */
+    pub fn first_click_safe(mut self, first_click_safe: bool) -> Self {
+        self.first_click_safe = first_click_safe;
+        self
+    }
+
+    /**
+     * If true, the first reveal also has no adjacent mines when there is
+     * room for that (see `GenerationOptions`). Requires `first_click_safe`.
+     */
+    pub fn safe_opening(mut self, safe_opening: bool) -> Self {
+        self.safe_opening = safe_opening;
+        self
+    }
+
+    /**
+     * Arranges the mines with the given symmetry (see `Field::new_symmetric`)
+     */
+    pub fn symmetry(mut self, symmetry: Symmetry) -> Self {
+        self.symmetry = Some(symmetry);
+        self
+    }
+
+    /**
+     * Keeps the given corner free of mines (see `Field::new_safe_corner`)
+     */
+    pub fn safe_corner(mut self, corner: Corner) -> Self {
+        self.safe_corner = Some(corner);
+        self
+    }
+
+    /**
+     * Only builds a Field which can be solved without guessing, starting by
+     * revealing the given position (see `Field::new_solvable`)
+     */
+    pub fn solvable_from(mut self, first_click: (u32, u32)) -> Self {
+        self.solvable_from = Some(first_click);
+        self
+    }
+
+    /**
+     * Decides which positions are adjacent (see `Field::new_with_topology`),
+     * `Standard8` unless set otherwise
+     */
+    pub fn topology(mut self, topology: impl Topology + 'static) -> Self {
+        self.topology = Some(Arc::new(topology));
+        self
+    }
+
+    /**
+     * Makes the given fraction of the numbered, mine-free zones lie: each of
+     * them displays a count that is off by exactly one, up or down (see
+     * `Field::displayed_count_at`). The liars are determined by the seed.
+     * A zone without adjacent mines never lies, and a lie never displays 0,
+     * so the zones displaying 0 are exactly the ones that cascade.
+     */
+    pub fn liars(mut self, fraction: f64) -> Self {
+        self.liars = Some(fraction);
+        self
+    }
+
+    /**
+     * Turns the given number of the mines into anti-mines, which subtract one
+     * from the counts around them instead of adding one
+     * (see `Field::with_anti_mine_placements`). Which mines are turned is
+     * determined by the seed. Counts the mined zones, not the mines, when
+     * zones can hold several.
+     */
+    pub fn anti_mines(mut self, count: u32) -> Self {
+        self.anti_mines = Some(count);
+        self
+    }
+
+    /**
+     * Counts the mines adjacent to a zone only when asked to, or when it is
+     * revealed, instead of counting them for every zone up front, which
+     * makes building a very large Field much faster (see `Field::lazy_counts`).
+     * Off unless set otherwise.
+     */
+    pub fn lazy_counts(mut self, lazy_counts: bool) -> Self {
+        self.lazy_counts = lazy_counts;
+        self
+    }
+
+    /**
+     * Gives the Field an irregular shape (see `Field::with_mask`). A density
+     * is then a fraction of the positions left on the board.
+     */
+    pub fn mask(mut self, mask: &[Vec<bool>]) -> Self {
+        self.mask = Some(mask.to_vec());
+        self
+    }
+
+    /**
+     * Creates the Field, failing if the options don't fit together
+     * or if the Field can't be created with them
+     */
+    pub fn build(&self) -> Result<Field, CreationError> {
+        self.check_options()?;
+        let (width, height) = (self.width, self.height);
+        let num_mines = self.num_mines()?;
+        #[cfg(feature = "std")]
+        let seed = self.seed.unwrap_or_else(|| rand::thread_rng().gen());
+        #[cfg(not(feature = "std"))]
+        let seed = self.seed.unwrap_or(0);
+        let topology = self.topology.clone().unwrap_or_else(|| Arc::new(Standard8));
+        let inactive = self.inactive_positions()?;
+        let num_inactive = inactive.len() as u32;
+        if self.first_click_safe {
+            Field::check_capacity(width, height, num_mines, num_inactive + 1)?;
+            let mut field = Field::build_with(
+                width,
+                height,
+                core::iter::empty(),
+                topology,
+                self.lazy_counts,
+            )?;
+            field.deactivate(&inactive);
+            field.num_mines = num_mines;
+            field.seed = Some(seed);
+            field.placement_pending = true;
+            field.generation_options = GenerationOptions {
+                safe_opening: self.safe_opening,
+            };
+            return Ok(field);
+        }
+        let mut field = if let Some(symmetry) = self.symmetry {
+            let placements = Field::place_with_strategy(
+                width,
+                height,
+                num_mines,
+                &mut Symmetric(symmetry),
+                seed,
+            )?;
+            Field::build_with(width, height, placements, topology, self.lazy_counts)?
+        } else if let Some(first_click) = self.solvable_from {
+            // the solver needs the counts up front anyway
+            let mut field =
+                Field::solvable_layout(width, height, num_mines, first_click, seed, topology)?;
+            field.lazy_counts = self.lazy_counts;
+            field
+        } else {
+            let mut excluded = match self.safe_corner {
+                Some(corner) => {
+                    Field::check_capacity(width, height, num_mines, num_inactive + 1)?;
+                    let (x, y) = corner.position(width, height);
+                    let num_reserved = num_mines + num_inactive;
+                    Field::mine_free_area(x, y, width, height, num_reserved, &*topology)
+                }
+                None => {
+                    Field::check_capacity(width, height, num_mines, num_inactive)?;
+                    PositionSet::new()
+                }
+            };
+            excluded.extend(inactive.iter().copied());
+            let placements = Field::generate_placements(
+                num_mines,
+                width,
+                height,
+                &mut SeededRng::seed_from_u64(seed),
+                &excluded,
+            );
+            let mut field =
+                Field::build_with(width, height, placements, topology, self.lazy_counts)?;
+            field.deactivate(&inactive);
+            field
+        };
+        field.seed = Some(seed);
+        if let Some(fraction) = self.liars {
+            field.choose_liars(fraction, seed);
+        }
+        if let Some(count) = self.anti_mines {
+            field.choose_anti_mines(count, seed)?;
+        }
+        Ok(field)
+    }
+
+    fn check_options(&self) -> Result<(), CreationError> {
+        if self.mines.is_some() && self.density.is_some() {
+            return Err(CreationError::ConflictingOptions {
+                first: "mines",
+                second: "density",
+            });
+        }
+        let placement_options = [
+            ("symmetry", self.symmetry.is_some()),
+            ("safe_corner", self.safe_corner.is_some()),
+            ("solvable_from", self.solvable_from.is_some()),
+        ];
+        let mut placing_up_front = placement_options
+            .iter()
+            .filter(|&&(_, set)| set)
+            .map(|&(name, _)| name);
+        let first = if self.first_click_safe {
+            Some("first_click_safe")
+        } else {
+            placing_up_front.next()
+        };
+        if let (Some(first), Some(second)) = (first, placing_up_front.next()) {
+            return Err(CreationError::ConflictingOptions { first, second });
+        }
+        if let Some(fraction) = self.liars {
+            if self.first_click_safe {
+                return Err(CreationError::ConflictingOptions {
+                    first: "first_click_safe",
+                    second: "liars",
+                });
+            }
+            if self.lazy_counts {
+                return Err(CreationError::ConflictingOptions {
+                    first: "liars",
+                    second: "lazy_counts",
+                });
+            }
+            if !(0.0..=1.0).contains(&fraction) {
+                return Err(CreationError::InvalidLiarFraction);
+            }
+        }
+        if self.anti_mines.is_some() {
+            if self.first_click_safe {
+                return Err(CreationError::ConflictingOptions {
+                    first: "first_click_safe",
+                    second: "anti_mines",
+                });
+            }
+            if self.liars.is_some() {
+                return Err(CreationError::ConflictingOptions {
+                    first: "liars",
+                    second: "anti_mines",
+                });
+            }
+        }
+        if self.mask.is_some() {
+            let placing_elsewhere = [
+                ("symmetry", self.symmetry.is_some()),
+                ("solvable_from", self.solvable_from.is_some()),
+            ];
+            if let Some(&(second, _)) = placing_elsewhere.iter().find(|&&(_, set)| set) {
+                return Err(CreationError::ConflictingOptions {
+                    first: "mask",
+                    second,
+                });
+            }
+        }
+        if self.safe_opening && !self.first_click_safe {
+            return Err(CreationError::MissingOption {
+                option: "first_click_safe",
+                required_by: "safe_opening",
+            });
+        }
+        Ok(())
+    }
+
+    fn num_mines(&self) -> Result<u32, CreationError> {
+        match self.density {
+            Some(density) => {
+                Field::check_capacity(self.width, self.height, 0, 0)?;
+                if !(0.0..=1.0).contains(&density) {
+                    return Err(CreationError::InvalidDensity);
+                }
+                let num_zones = self.width * self.height - self.inactive_positions()?.len() as u32;
+                // rounded half up, as f64::round needs std
+                Ok(((density * num_zones as f64 + 0.5) as u32).min(num_zones))
+            }
+            None => Ok(self.mines.unwrap_or(10)),
+        }
+    }
+
+    /**
+     * The positions the mask leaves out of the Field, if there is one,
+     * failing if it doesn't fit the dimensions
+     */
+    fn inactive_positions(&self) -> Result<PositionSet, CreationError> {
+        let mask = match &self.mask {
+            Some(mask) => mask,
+            None => return Ok(PositionSet::new()),
+        };
+        let (width, height) = (self.width, self.height);
+        if mask.len() != height as usize || mask.iter().any(|row| row.len() != width as usize) {
+            return Err(CreationError::InvalidMask);
+        }
+        Ok((0..width)
+            .flat_map(|x| (0..height).map(move |y| (x, y)))
+            .filter(|&(x, y)| !mask[y as usize][x as usize])
+            .collect())
+    }
+}
+
+#[derive(Clone)]
+// derived into the inherent `Field::serialize` and `Field::deserialize`,
+// which the checked Serialize and Deserialize impls below call
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(remote = "Self")
+)]
+pub struct Field {
+    num_mines: u32,
+    num_flagged: u32,
+    // see `set_flag_limit`
+    flag_limit: Option<u32>,
+    num_revealed: u32,
+    // revealed zones without a mine
+    num_revealed_safe: u32,
+    // active zones without a mine, and with one, recounted whenever the
+    // mines are laid out
+    num_safe_zones: u32,
+    num_mined_zones: u32,
+    // see `expose_mines`
+    num_exposed: u32,
+    num_flagged_exposed: u32,
+    width: u32,
+    height: u32,
+    // every zone, column by column: indexed by x * height + y (see `zone_index`)
+    grid: Zones,
+    // the index of every revealed zone, in the order they were revealed
+    // (see `reveal_order_at`)
+    reveal_order: Vec<u32>,
+    // the place of every zone in reveal_order, indexed like the grid, or
+    // UNSTAMPED if it is hidden; left empty until the first reveal
+    #[cfg_attr(feature = "serde", serde(skip))]
+    reveal_stamps: Vec<u32>,
+    // the index of every mine revealed by `reveal_at`, in the order they
+    // exploded (see `exploded_at`)
+    exploded: Vec<u32>,
+    seed: Option<u64>,
+    // true while mines are yet to be placed, see `new_deferred`
+    placement_pending: bool,
+    generation_options: GenerationOptions,
+    // decides which positions are adjacent, see `new_with_topology`
+    #[cfg_attr(feature = "serde", serde(with = "crate::topology::serde_topology"))]
+    topology: Arc<dyn Topology>,
+    // see `with_mine_counts`
+    max_mines_per_zone: u8,
+    // see `lazy_counts`
+    lazy_counts: bool,
+    // see `row_mine_counts` and `col_mine_counts`, recounted along with the
+    // adjacent mine counts
+    row_mine_counts: Vec<u32>,
+    col_mine_counts: Vec<u32>,
+    // opening ids for every position, indexed by x * height + y,
+    // built on first use and tied to the current mine layout
+    #[cfg_attr(feature = "serde", serde(skip))]
+    opening_ids: OnceCell<Vec<Option<u32>>>,
+    // what has happened since the last `drain_events`, oldest first
+    #[cfg_attr(feature = "serde", serde(skip))]
+    events: Vec<ModelEvent>,
+}
+
+#[cfg(feature = "serde")]
+impl serde::Serialize for Field {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        Field::serialize(self, serializer)
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<'de> serde::Deserialize<'de> for Field {
+    /**
+     * Fails unless every count agrees with the zones and every zone is in a
+     * state some moves could have left it in (see `Field::check_invariants`),
+     * so that a tampered save is rejected rather than trusted
+     */
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let mut field = Field::deserialize(deserializer)?;
+        field.check_invariants().map_err(serde::de::Error::custom)?;
+        field.stamp_reveals();
+        Ok(field)
+    }
+}
+
+impl Field {
+    /**
+     * A FieldBuilder, for creating a Field from any combination of options
+     */
+    pub fn builder() -> FieldBuilder {
+        FieldBuilder::default()
+    }
+
+    #[cfg(feature = "std")]
+    /**
+     * Create a new Field from a randomly chosen seed, which can be
+     * retrieved afterwards with `seed`.
+     */
+    pub fn new(width: u32, height: u32, num_mines: u32) -> Result<Self, CreationError> {
+        Self::builder()
+            .width(width)
+            .height(height)
+            .mines(num_mines)
+            .build()
+    }
+
+    /**
+     * Create a new Field whose mine layout is determined by the given seed.
+     * The same seed and arguments always produce the same mine layout.
+     */
+    pub fn new_with_seed(
+        width: u32,
+        height: u32,
+        num_mines: u32,
+        seed: u64,
+    ) -> Result<Self, CreationError> {
+        Self::builder()
+            .width(width)
+            .height(height)
+            .mines(num_mines)
+            .seed(seed)
+            .build()
+    }
+
+    #[cfg(feature = "std")]
+    /**
+     * Create a new Field in which the given fraction of zones contain mines,
+     * rounded to the nearest whole number of mines. The resolved number of
+     * mines is available afterwards from `num_mines`.
+     * Fails if density is NaN or not between 0 and 1 (inclusive).
+     */
+    pub fn with_density(width: u32, height: u32, density: f64) -> Result<Self, CreationError> {
+        Self::builder()
+            .width(width)
+            .height(height)
+            .density(density)
+            .build()
+    }
+
+    #[cfg(feature = "std")]
+    /**
+     * Create a new Field whose mines are not placed until the first call to
+     * `reveal_at`, at which point they are placed anywhere except for the
+     * revealed zone, so that the first reveal never hits a mine.
+     * Until then, no zone has a mine or any adjacent mines, though
+     * `num_mines` already reports the number of mines to be placed.
+     * Because the first zone revealed must be free, num_mines must be
+     * less than width * height.
+     */
+    pub fn new_deferred(width: u32, height: u32, num_mines: u32) -> Result<Self, CreationError> {
+        Self::builder()
+            .width(width)
+            .height(height)
+            .mines(num_mines)
+            .first_click_safe(true)
+            .build()
+    }
+
+    /**
+     * Like `new_deferred`, but the mine layout is determined by the given
+     * seed together with the coordinates of the first reveal.
+     */
+    pub fn new_deferred_with_seed(
+        width: u32,
+        height: u32,
+        num_mines: u32,
+        seed: u64,
+    ) -> Result<Self, CreationError> {
+        Self::new_deferred_with_options(
+            width,
+            height,
+            num_mines,
+            seed,
+            GenerationOptions::default(),
+        )
+    }
+
+    /**
+     * Like `new_deferred_with_seed`, but mines are placed according to the given options
+     */
+    pub fn new_deferred_with_options(
+        width: u32,
+        height: u32,
+        num_mines: u32,
+        seed: u64,
+        options: GenerationOptions,
+    ) -> Result<Self, CreationError> {
+        Self::builder()
+            .width(width)
+            .height(height)
+            .mines(num_mines)
+            .seed(seed)
+            .first_click_safe(true)
+            .safe_opening(options.safe_opening)
+            .build()
+    }
+
+    /**
+     * Create a new Field, placing mines using the given random number generator.
+     */
+    pub fn new_with_rng(
+        rng: &mut impl Rng,
+        width: u32,
+        height: u32,
+        num_mines: u32,
+    ) -> Result<Self, CreationError> {
+        Self::check_capacity(width, height, num_mines, 0)?;
+        let mine_placements =
+            Self::generate_placements(num_mines, width, height, rng, &PositionSet::new());
+        Self::with_mine_placements(width, height, mine_placements)
+    }
+
+    /**
+     * Create a new Field in which the given corner is guaranteed not to
+     * contain a mine. If there is enough room, none of the positions
+     * adjacent to that corner will contain a mine either.
+     * The same seed and arguments always produce the same mine layout.
+     */
+    pub fn new_safe_corner(
+        width: u32,
+        height: u32,
+        num_mines: u32,
+        corner: Corner,
+        seed: u64,
+    ) -> Result<Self, CreationError> {
+        Self::builder()
+            .width(width)
+            .height(height)
+            .mines(num_mines)
+            .seed(seed)
+            .safe_corner(corner)
+            .build()
+    }
+
+    /**
+     * Create a new Field which can be completely solved by logic alone,
+     * without any guessing, starting by revealing `first_click`
+     * (see `solver::is_solvable`). The first click is always mine-free,
+     * as are its neighbors when there is enough room.
+     * The same seed and arguments always produce the same mine layout.
+     * Fails if first_click is out of bounds, or if no solvable layout
+     * was found within a fixed number of attempts.
+     */
+    pub fn new_solvable(
+        width: u32,
+        height: u32,
+        num_mines: u32,
+        first_click: (u32, u32),
+        seed: u64,
+    ) -> Result<Self, CreationError> {
+        Self::builder()
+            .width(width)
+            .height(height)
+            .mines(num_mines)
+            .seed(seed)
+            .solvable_from(first_click)
+            .build()
+    }
+
+    /**
+     * Create a new Field whose mine layout has the given symmetry.
+     * Positions which are their own mirror image (such as the center of a
+     * board with odd dimensions under rotational symmetry) hold a single mine,
+     * any other mine comes with a second mine at its mirror image.
+     * The same seed and arguments always produce the same mine layout.
+     * Fails if num_mines can't be arranged with the given symmetry, e.g. an
+     * odd number of mines when no position is its own mirror image.
+     */
+    pub fn new_symmetric(
+        width: u32,
+        height: u32,
+        num_mines: u32,
+        symmetry: Symmetry,
+        seed: u64,
+    ) -> Result<Self, CreationError> {
+        Self::builder()
+            .width(width)
+            .height(height)
+            .mines(num_mines)
+            .seed(seed)
+            .symmetry(symmetry)
+            .build()
+    }
+
+    /**
+     * Create a new Field whose mines are placed by the given strategy,
+     * which draws its randomness from a generator seeded with the given seed.
+     * The same seed and arguments always produce the same mine layout,
+     * as long as the strategy itself is deterministic.
+     * Fails if the strategy fails, or if it doesn't place exactly num_mines
+     * mines within the given dimensions.
+     */
+    pub fn new_with_strategy(
+        width: u32,
+        height: u32,
+        num_mines: u32,
+        strategy: &mut impl PlacementStrategy,
+        seed: u64,
+    ) -> Result<Self, CreationError> {
+        let placements = Self::place_with_strategy(width, height, num_mines, strategy, seed)?;
+        let mut field = Self::with_mine_placements(width, height, placements)?;
+        field.seed = Some(seed);
+        Ok(field)
+    }
+
+    #[cfg(feature = "std")]
+    /**
+     * Create a new Field whose mines are distributed according to one of the
+     * built-in weight profiles (see `new_weighted`).
+     */
+    pub fn new_with_profile(
+        width: u32,
+        height: u32,
+        num_mines: u32,
+        profile: WeightProfile,
+        seed: u64,
+    ) -> Result<Self, CreationError> {
+        Self::new_weighted(
+            width,
+            height,
+            num_mines,
+            |x, y| profile.weight(x, y, width, height),
+            seed,
+        )
+    }
+
+    #[cfg(feature = "std")]
+    /**
+     * Create a new Field whose mines are placed with probabilities proportional
+     * to the weight of each position, as given by `weight(x, y)`. Positions
+     * with a weight of zero never receive a mine.
+     * The same seed, arguments and weights always produce the same mine layout.
+     * Fails if any weight is negative or not finite, or if fewer than
+     * num_mines positions have a positive weight.
+     * Needs the `std` feature, for the powers the weights are drawn with.
+     */
+    pub fn new_weighted(
+        width: u32,
+        height: u32,
+        num_mines: u32,
+        weight: impl Fn(u32, u32) -> f64,
+        seed: u64,
+    ) -> Result<Self, CreationError> {
+        Self::check_capacity(width, height, num_mines, 0)?;
+        let mut rng = SeededRng::seed_from_u64(seed);
+        // weighted sampling without replacement (Efraimidis & Spirakis):
+        // the positions with the largest keys u^(1/weight) are chosen
+        let mut keyed = Vec::new();
+        for x in 0..width {
+            for y in 0..height {
+                let w = weight(x, y);
+                if !w.is_finite() || w < 0.0 {
+                    return Err(CreationError::InvalidWeight {
+                        position: Position::new(x, y),
+                    });
+                }
+                let u: f64 = rng.gen();
+                if w > 0.0 {
+                    keyed.push((u.powf(1.0 / w), (x, y)));
+                }
+            }
+        }
+        if keyed.len() < num_mines as usize {
+            return Err(CreationError::TooManyMines {
+                requested: num_mines,
+                capacity: keyed.len() as u32,
+            });
+        }
+        keyed.sort_by(|a, b| b.0.partial_cmp(&a.0).unwrap());
+        let placements = keyed
+            .into_iter()
+            .take(num_mines as usize)
+            .map(|(_, pos)| pos);
+        Self::with_mine_placements(width, height, placements)
+    }
+
+    /**
+     * Create a new Field whose 3BV (see `board_3bv`) is within the given
+     * inclusive range, generating up to `attempts` random layouts from the
+     * given seed. The same seed and arguments always produce the same layout.
+     * Fails if no generated layout was in range.
+     */
+    pub fn new_with_3bv_range(
+        width: u32,
+        height: u32,
+        num_mines: u32,
+        min_3bv: u32,
+        max_3bv: u32,
+        seed: u64,
+        attempts: u32,
+    ) -> Result<Self, CreationError> {
+        Self::check_capacity(width, height, num_mines, 0)?;
+        let mut rng = SeededRng::seed_from_u64(seed);
+        for _ in 0..attempts {
+            let mine_placements =
+                Self::generate_placements(num_mines, width, height, &mut rng, &PositionSet::new());
+            let field = Self::with_mine_placements(width, height, mine_placements)?;
+            if (min_3bv..=max_3bv).contains(&field.board_3bv()) {
+                return Ok(field);
+            }
+        }
+        Err(CreationError::AttemptsExhausted { attempts })
+    }
+
+    /**
+     * Create a new Field of hexagonal cells (see `Tiling::Hex`).
+     * The same seed and arguments always produce the same mine layout.
+     */
+    pub fn new_hex(
+        width: u32,
+        height: u32,
+        num_mines: u32,
+        seed: u64,
+    ) -> Result<Self, CreationError> {
+        Self::new_with_topology(width, height, num_mines, Hex6, seed)
+    }
+
+    /**
+     * Create a new Field with mines at exactly the given coordinates.
+     * Duplicate coordinates are treated as a single mine.
+     * Fails with every coordinate that is not within the given dimensions.
+     */
+    pub fn with_mine_placements(
+        width: u32,
+        height: u32,
+        placements: impl IntoIterator<Item = (u32, u32)>,
+    ) -> Result<Self, CreationError> {
+        Self::build(width, height, placements, Arc::new(Standard8))
+    }
+
+    /**
+     * Like `with_mine_placements`, but for a Field of hexagonal cells
+     */
+    pub fn with_hex_mine_placements(
+        width: u32,
+        height: u32,
+        placements: impl IntoIterator<Item = (u32, u32)>,
+    ) -> Result<Self, CreationError> {
+        Self::with_topology_mine_placements(width, height, placements, Hex6)
+    }
+
+    /**
+     * Create a new Field shaped like a torus: the left edge borders the right
+     * edge and the top edge borders the bottom, so no position is on an edge.
+     * The same seed and arguments always produce the same mine layout.
+     */
+    pub fn new_toroidal(
+        width: u32,
+        height: u32,
+        num_mines: u32,
+        seed: u64,
+    ) -> Result<Self, CreationError> {
+        Self::new_with_topology(width, height, num_mines, Torus8, seed)
+    }
+
+    /**
+     * Like `with_mine_placements`, but for a Field shaped like a torus
+     * (see `new_toroidal`)
+     */
+    pub fn with_toroidal_mine_placements(
+        width: u32,
+        height: u32,
+        placements: impl IntoIterator<Item = (u32, u32)>,
+    ) -> Result<Self, CreationError> {
+        Self::with_topology_mine_placements(width, height, placements, Torus8)
+    }
+
+    /**
+     * Create a new Field made of `depth` layers of `width` by `height` zones
+     * stacked on top of each other, where each zone also borders the zones
+     * directly above and below it and their neighbors, for up to 26 in all.
+     * The layers are laid out one after the other along the y axis, so every
+     * other method sees a Field `height * depth` zones tall, in which (x, y)
+     * of layer z is at (x, z * height + y) (see `flatten_position`).
+     * A Field with a depth of 1 is an ordinary Field.
+     * The same seed and arguments always produce the same mine layout.
+     */
+    pub fn new_layered(
+        width: u32,
+        height: u32,
+        depth: u32,
+        num_mines: u32,
+        seed: u64,
+    ) -> Result<Self, CreationError> {
+        let total_height = Self::layered_height(width, height, depth)?;
+        Self::new_with_topology(width, total_height, num_mines, Layered { depth }, seed)
+    }
+
+    /**
+     * Like `with_mine_placements`, but for a Field made of layers
+     * (see `new_layered`), with mines at the given (x, y, z) coordinates.
+     * Fails with the (x, y) of every placement that is out of bounds.
+     */
+    pub fn with_layered_mine_placements(
+        width: u32,
+        height: u32,
+        depth: u32,
+        placements: impl IntoIterator<Item = (u32, u32, u32)>,
+    ) -> Result<Self, CreationError> {
+        let total_height = Self::layered_height(width, height, depth)?;
+        let mut out_of_bounds = Vec::new();
+        let mut flattened = Vec::new();
+        for (x, y, z) in placements {
+            if x >= width || y >= height || z >= depth {
+                out_of_bounds.push((x, y));
+            } else {
+                flattened.push((x, z * height + y));
+            }
+        }
+        if !out_of_bounds.is_empty() {
+            out_of_bounds.sort_unstable();
+            out_of_bounds.dedup();
+            return Err(CreationError::PlacementsOutOfBounds(
+                out_of_bounds.into_iter().map(Position::from).collect(),
+            ));
+        }
+        Self::with_topology_mine_placements(width, total_height, flattened, Layered { depth })
+    }
+
+    /**
+     * Create a new Field in which the positions adjacent to each other are
+     * decided by the given Kernel, rather than by being next to each other.
+     * The same seed and arguments always produce the same mine layout.
+     */
+    pub fn new_with_kernel(
+        width: u32,
+        height: u32,
+        num_mines: u32,
+        kernel: Kernel,
+        seed: u64,
+    ) -> Result<Self, CreationError> {
+        Self::new_with_topology(width, height, num_mines, kernel, seed)
+    }
+
+    /**
+     * Like `with_mine_placements`, but with adjacency decided by the given
+     * Kernel (see `new_with_kernel`)
+     */
+    pub fn with_kernel_mine_placements(
+        width: u32,
+        height: u32,
+        placements: impl IntoIterator<Item = (u32, u32)>,
+        kernel: Kernel,
+    ) -> Result<Self, CreationError> {
+        Self::with_topology_mine_placements(width, height, placements, kernel)
+    }
+
+    /**
+     * Create a new Field in which the positions adjacent to each other are
+     * decided by the given Topology, for counting adjacent mines as well as
+     * for cascading reveals. Only built-in topologies (see `BuiltinTopology`)
+     * can be saved along with the Field.
+     * The same seed and arguments always produce the same mine layout.
+     */
+    pub fn new_with_topology(
+        width: u32,
+        height: u32,
+        num_mines: u32,
+        topology: impl Topology + 'static,
+        seed: u64,
+    ) -> Result<Self, CreationError> {
+        Self::builder()
+            .width(width)
+            .height(height)
+            .mines(num_mines)
+            .seed(seed)
+            .topology(topology)
+            .build()
+    }
+
+    /**
+     * Like `with_mine_placements`, but with adjacency decided by the given
+     * Topology (see `new_with_topology`)
+     */
+    pub fn with_topology_mine_placements(
+        width: u32,
+        height: u32,
+        placements: impl IntoIterator<Item = (u32, u32)>,
+        topology: impl Topology + 'static,
+    ) -> Result<Self, CreationError> {
+        Self::build(width, height, placements, Arc::new(topology))
+    }
+
+    /**
+     * Create a new Field in which each zone can hold up to max_per_zone mines,
+     * so that adjacent mine counts can go past 8. Mines are spread out as if
+     * each zone had max_per_zone slots, every one of them equally likely to
+     * receive a mine. `num_mines` counts every mine, not every mined zone.
+     * The same seed and arguments always produce the same mine layout.
+     */
+    pub fn new_multi_mine(
+        width: u32,
+        height: u32,
+        num_mines: u32,
+        max_per_zone: u8,
+        seed: u64,
+    ) -> Result<Self, CreationError> {
+        Self::check_capacity(width, height, 0, 0)?;
+        let capacity = (width as u64 * height as u64 * max_per_zone as u64).min(u32::MAX as u64);
+        if num_mines as u64 > capacity {
+            return Err(CreationError::TooManyMines {
+                requested: num_mines,
+                capacity: capacity as u32,
+            });
+        }
+        // A partial Fisher-Yates shuffle of the slots, column by column, with
+        // only the slots it has moved kept in `moved`
+        let num_slots = width as u64 * height as u64 * max_per_zone as u64;
+        let mut moved: BTreeMap<u64, u64> = BTreeMap::new();
+        let mut rng = SeededRng::seed_from_u64(seed);
+        let picks = (0..num_mines as u64).map(|i| {
+            let j = rng.gen_range(i, num_slots);
+            let displaced = moved.remove(&i).unwrap_or(i);
+            let slot = if j == i {
+                displaced
+            } else {
+                moved.insert(j, displaced).unwrap_or(j)
+            };
+            let zone = slot / max_per_zone as u64;
+            let pos = ((zone / height as u64) as u32, (zone % height as u64) as u32);
+            (pos, 1)
+        });
+        let mut field = Self::with_mine_counts(width, height, picks)?;
+        field.max_mines_per_zone = max_per_zone;
+        field.seed = Some(seed);
+        Ok(field)
+    }
+
+    /**
+     * Create a new Field in which zones can hold several mines, burying the
+     * given number of mines at each of the given coordinates. The mines of
+     * duplicate coordinates are added together.
+     * `max_mines_per_zone` is afterwards the most mines in any one zone.
+     * Fails with every coordinate that is not within the given dimensions,
+     * or if a zone would hold more than 255 mines.
+     */
+    pub fn with_mine_counts(
+        width: u32,
+        height: u32,
+        placements: impl IntoIterator<Item = ((u32, u32), u8)>,
+    ) -> Result<Self, CreationError> {
+        let mut counts: BTreeMap<(u32, u32), u8> = BTreeMap::new();
+        for (position, count) in placements {
+            let total = counts.entry(position).or_insert(0);
+            *total = total
+                .checked_add(count)
+                .ok_or(CreationError::ZoneOverfilled {
+                    position: position.into(),
+                })?;
+        }
+        counts.retain(|_, &mut count| count > 0);
+        let mut field = Self::with_mine_placements(width, height, counts.keys().copied())?;
+        for (&(x, y), &count) in &counts {
+            let index = field.zone_index(x, y);
+            field.grid.update(index, |zone| zone.mine_count = count);
+        }
+        field.num_mines = counts.values().map(|&count| count as u32).sum();
+        field.max_mines_per_zone = counts.values().copied().max().unwrap_or(1);
+        field.set_adj_counts();
+        Ok(field)
+    }
+
+    /**
+     * Create a new Field with mines at exactly the given coordinates, and
+     * anti-mines at exactly the given anti_placements. An anti-mine is as
+     * lethal as a mine, and must be flagged like one, but subtracts one from
+     * the counts around it instead of adding one (see `net_count_at`), so that
+     * counts can be negative, or 0 next to a mine and an anti-mine.
+     * Cascades still only spread from zones with neither kind adjacent.
+     * Duplicate coordinates are treated as a single mine or anti-mine.
+     * Fails with every coordinate that is not within the given dimensions,
+     * or if a coordinate is to hold both kinds.
+     */
+    pub fn with_anti_mine_placements(
+        width: u32,
+        height: u32,
+        placements: impl IntoIterator<Item = (u32, u32)>,
+        anti_placements: impl IntoIterator<Item = (u32, u32)>,
+    ) -> Result<Self, CreationError> {
+        let placements: PositionSet = placements.into_iter().collect();
+        let anti_placements: PositionSet = anti_placements.into_iter().collect();
+        if let Some(&position) = placements.iter().find(|pos| anti_placements.contains(pos)) {
+            return Err(CreationError::AmbiguousMine {
+                position: position.into(),
+            });
+        }
+        let mut field = Self::with_mine_placements(
+            width,
+            height,
+            placements.iter().chain(&anti_placements).copied(),
+        )?;
+        for &(x, y) in &anti_placements {
+            let index = field.zone_index(x, y);
+            field.grid.update(index, |zone| zone.set_anti(true));
+        }
+        field.set_adj_counts();
+        Ok(field)
+    }
+
+    #[cfg(feature = "std")]
+    /**
+     * Create a new Field from a 1-bit PBM image, in either the ASCII (P1) or
+     * the binary (P4) encoding: the image's dimensions become the Field's,
+     * and every black pixel becomes a mine.
+     */
+    pub fn from_pbm(reader: impl BufRead) -> Result<Self, PbmError> {
+        let image = pbm::read_pbm(reader)?;
+        Ok(Self::with_mine_placements(
+            image.width,
+            image.height,
+            image.black_pixels,
+        )?)
+    }
+
+    /**
+     * Create a new Field from a board in the MBF format shared by other
+     * minesweeper clients (see `mbf::read_mbf`), with a mine at every
+     * position it lists.
+     * Fails if the board is cut short, lists a mine twice or lists a mine
+     * outside of its dimensions.
+     */
+    pub fn from_mbf(bytes: &[u8]) -> Result<Self, MbfError> {
+        let board = mbf::read_mbf(bytes)?;
+        Ok(Self::with_mine_placements(
+            board.width,
+            board.height,
+            board.mines,
+        )?)
+    }
+
+    /**
+     * Create a new Field from a share code made by `to_share_code`,
+     * with its mine layout and nothing revealed.
+     * Fails if the code has a typo, is incomplete, or is for a board with
+     * more than `share::MAX_ZONES` zones.
+     */
+    pub fn from_share_code(code: &str) -> Result<Self, ShareCodeError> {
+        let board = share::decode(code)?;
+        Ok(Self::with_mine_placements(
+            board.width,
+            board.height,
+            board.mines,
+        )?)
+    }
+
+    #[cfg(feature = "std")]
+    /**
+     * Create a new Field from a save written by `write_save`, with the
+     * zones it records as revealed and flagged. Zones are revealed in
+     * row-major order, so nothing cascades and none counts as exploded.
+     * A save of a Field whose mines were yet to be placed (see `new_deferred`)
+     * is read back as one, whose mines will be placed afresh.
+     * Fails if the save is of an unknown version, is cut short, doesn't
+     * match its checksum, or holds a board no moves could have left as it
+     * is (see `check_invariants`).
+     */
+    pub fn read_save(reader: impl Read) -> Result<Self, SaveError> {
+        let save = save::read_save(reader)?;
+        let width = save.width;
+        let positions = || {
+            (0..save.height)
+                .flat_map(move |y| (0..width).map(move |x| (x, y)))
+                .enumerate()
+        };
+        let mine_placements: Vec<_> = positions()
+            .filter(|&(index, _)| SaveData::bit(&save.mines, index))
+            .map(|(_, position)| position)
+            .collect();
+        let found = mine_placements.len() as u32;
+        let nothing_revealed = save.revealed.iter().all(|&byte| byte == 0);
+        let mut field = if found == 0 && save.num_mines > 0 && nothing_revealed {
+            Self::new_deferred(save.width, save.height, save.num_mines)?
+        } else if found == save.num_mines {
+            Self::with_mine_placements(save.width, save.height, mine_placements)?
+        } else {
+            return Err(SaveError::MineCountMismatch {
+                expected: save.num_mines,
+                found,
+            });
+        };
+        for (index, (x, y)) in positions() {
+            let zone_index = field.zone_index(x, y);
+            let mut zone = field.grid[zone_index];
+            if SaveData::bit(&save.flagged, index) {
+                zone.set_flagged(true);
+                field.num_flagged += 1;
+            }
+            if SaveData::bit(&save.revealed, index) {
+                zone.set_revealed(true);
+                field.record_reveal(zone_index as u32);
+                field.num_revealed += 1;
+                if !zone.has_mine() {
+                    field.num_revealed_safe += 1;
+                }
+            }
+            field.grid.set(zone_index, zone);
+        }
+        field.check_invariants().map_err(SaveError::Invalid)?;
+        Ok(field)
+    }
+
+    /**
+     * Shared by the `with_*mine_placements` constructors
+     */
+    fn build(
+        width: u32,
+        height: u32,
+        placements: impl IntoIterator<Item = (u32, u32)>,
+        topology: Arc<dyn Topology>,
+    ) -> Result<Self, CreationError> {
+        Self::build_with(width, height, placements, topology, false)
+    }
+
+    /**
+     * `build`, counting adjacent mines lazily if lazy_counts is true
+     * (see `FieldBuilder::lazy_counts`)
+     */
+    fn build_with(
+        width: u32,
+        height: u32,
+        placements: impl IntoIterator<Item = (u32, u32)>,
+        topology: Arc<dyn Topology>,
+        lazy_counts: bool,
+    ) -> Result<Self, CreationError> {
+        Self::check_capacity(width, height, 0, 0)?;
+        let placements: PositionSet = placements.into_iter().collect();
+        let mut out_of_bounds: Vec<_> = placements
+            .iter()
+            .copied()
+            .filter(|&(x, y)| x >= width || y >= height)
+            .collect();
+        if !out_of_bounds.is_empty() {
+            out_of_bounds.sort_unstable();
+            return Err(CreationError::PlacementsOutOfBounds(
+                out_of_bounds.into_iter().map(Position::from).collect(),
+            ));
+        }
+        let mut freshly_made = Field {
+            num_mines: placements.len() as u32,
+            num_flagged: 0,
+            flag_limit: None,
+            num_revealed: 0,
+            num_revealed_safe: 0,
+            num_safe_zones: 0,
+            num_mined_zones: 0,
+            num_exposed: 0,
+            num_flagged_exposed: 0,
+            width,
+            height,
+            grid: Self::generate_grid(width, height, &placements),
+            reveal_order: Vec::new(),
+            reveal_stamps: Vec::new(),
+            exploded: Vec::new(),
+            seed: None,
+            placement_pending: false,
+            generation_options: GenerationOptions::default(),
+            topology,
+            max_mines_per_zone: 1,
+            lazy_counts,
+            row_mine_counts: Vec::new(),
+            col_mine_counts: Vec::new(),
+            opening_ids: OnceCell::new(),
+            events: Vec::new(),
+        };
+        freshly_made.set_adj_counts();
+        Ok(freshly_made)
+    }
+
+    #[cfg(feature = "std")]
+    /**
+     * Create a new Field of an irregular shape. `mask` is indexed row-major
+     * (`mask[y][x]`) and must have `height` rows of `width` entries each.
+     * Positions whose entry is false are not part of the board: they never
+     * contain mines, and are treated as out of bounds by every other method.
+     * Fails if the mask has the wrong dimensions, or if there are more mines
+     * than active positions.
+     */
+    pub fn with_mask(
+        width: u32,
+        height: u32,
+        mask: &[Vec<bool>],
+        num_mines: u32,
+    ) -> Result<Self, CreationError> {
+        Self::builder()
+            .width(width)
+            .height(height)
+            .mask(mask)
+            .mines(num_mines)
+            .build()
+    }
+
+    /**
+     * Create a new Field of an irregular shape, like `with_mask`, whose mine
+     * layout is determined by the given seed
+     */
+    pub fn with_mask_with_seed(
+        width: u32,
+        height: u32,
+        mask: &[Vec<bool>],
+        num_mines: u32,
+        seed: u64,
+    ) -> Result<Self, CreationError> {
+        Self::builder()
+            .width(width)
+            .height(height)
+            .mask(mask)
+            .mines(num_mines)
+            .seed(seed)
+            .build()
+    }
+
+    /**
+     * Create a new Field from a grid of mines drawn row by row: `rows[y][x]`
+     * is true if (x, y) contains a mine. So the grid
+     *     vec![vec![true, false, false],
+     *          vec![false, false, true]]
+     * makes a Field 3 wide and 2 tall, with mines at (0, 0) and (2, 1).
+     * Fails if there are no rows, if the rows are empty,
+     * or if any row is not as long as the first.
+     */
+    pub fn from_bool_grid(rows: &[Vec<bool>]) -> Result<Self, CreationError> {
+        let height = u32::try_from(rows.len()).unwrap_or(u32::MAX);
+        let width = rows
+            .first()
+            .map_or(0, |row| u32::try_from(row.len()).unwrap_or(u32::MAX));
+        Self::check_capacity(width, height, 0, 0)?;
+        if let Some(row) = rows.iter().position(|row| row.len() != width as usize) {
+            return Err(CreationError::UnevenRows { row });
+        }
+        let mine_placements = rows.iter().enumerate().flat_map(|(y, row)| {
+            row.iter()
+                .enumerate()
+                .filter(|&(_, &has_mine)| has_mine)
+                .map(move |(x, _)| (x as u32, y as u32))
+        });
+        Self::with_mine_placements(width, height, mine_placements)
+    }
+
+    /**
+     * The height of this Field
+     */
+    pub fn height(&self) -> u32 {
+        self.height
+    }
+
+    /**
+     * The width of this Field
+     */
+    pub fn width(&self) -> u32 {
+        self.width
+    }
+
+    /**
+     * The seed this Field's mine layout was generated from,
+     * if it was created with `new` or `new_with_seed`
+     */
+    pub fn seed(&self) -> Option<u64> {
+        self.seed
+    }
+
+    /**
+     * true if this Field was created with `new_deferred` and
+     * its mines have not been placed yet
+     */
+    pub fn is_placement_pending(&self) -> bool {
+        self.placement_pending
+    }
+
+    /**
+     * The shape of the cells making up this Field
+     */
+    pub fn tiling(&self) -> Tiling {
+        self.topology.tiling()
+    }
+
+    /**
+     * true if opposite edges of this Field border each other (see `new_toroidal`)
+     */
+    pub fn wraps(&self) -> bool {
+        self.topology.wraps()
+    }
+
+    /**
+     * The number of layers in this Field (see `new_layered`), 1 unless it has several
+     */
+    pub fn depth(&self) -> u32 {
+        self.topology.depth()
+    }
+
+    /**
+     * The Topology deciding which positions of this Field are adjacent
+     */
+    pub fn topology(&self) -> &dyn Topology {
+        &*self.topology
+    }
+
+    /**
+     * The height of each layer of this Field, which is the whole height
+     * unless it has several layers
+     */
+    pub fn layer_height(&self) -> u32 {
+        self.height() / self.depth()
+    }
+
+    /**
+     * The position of (x, y) in layer z, as seen by every other method
+     * (see `new_layered`). None if that is out of bounds.
+     */
+    pub fn flatten_position(&self, x: u32, y: u32, z: u32) -> Option<(u32, u32)> {
+        if x >= self.width() || y >= self.layer_height() || z >= self.depth() {
+            return None;
+        }
+        Some((x, z * self.layer_height() + y))
+    }
+
+    /**
+     * The opposite of `flatten_position`: which (x, y) of which layer z the
+     * given position is. None if it is out of bounds.
+     */
+    pub fn layer_position(&self, x: u32, y: u32) -> Option<(u32, u32, u32)> {
+        if x >= self.width() || y >= self.height() {
+            return None;
+        }
+        let layer_height = self.layer_height();
+        Some((x, y % layer_height, y / layer_height))
+    }
+
+    /**
+     * false if the given position is out of bounds or masked out of this Field
+     * (see `with_mask`)
+     */
+    pub fn is_active_at(&self, x: u32, y: u32) -> bool {
+        self.zone_at(x, y).is_some()
+    }
+
+    /**
+     * The number of positions that are part of this Field, which is
+     * width * height unless some of them were masked out
+     */
+    pub fn num_active(&self) -> u32 {
+        self.grid.iter().filter(|z| z.active()).count() as u32
+    }
+
+    /**
+     * Every position that is part of this Field, scanning x-major
+     */
+    pub fn active_positions(&self) -> impl Iterator<Item = (u32, u32)> + '_ {
+        (0..self.width())
+            .flat_map(move |x| (0..self.height()).map(move |y| (x, y)))
+            .filter(move |&(x, y)| self.is_active_at(x, y))
+    }
+
+    /**
+     * Every position of this Field, including any masked out of it, in row-major
+     * order: left to right along the top row (y = 0), then along each row below
+     * it in turn. Yields width * height positions.
+     */
+    pub fn positions(&self) -> impl Iterator<Item = (u32, u32)> {
+        let width = self.width();
+        (0..self.height()).flat_map(move |y| (0..width).map(move |x| (x, y)))
+    }
+
+    /**
+     * Every zone of this Field with what a player can see of it
+     * (see `zone_state_at`), in the row-major order of `positions`.
+     * Positions masked out of the Field are skipped.
+     */
+    pub fn zones(&self) -> impl Iterator<Item = ((u32, u32), ZoneState)> + '_ {
+        self.positions()
+            .filter_map(move |(x, y)| self.zone_state_at(x, y).map(|state| ((x, y), state)))
+    }
+
+    /**
+     * Every zone yet to be revealed, flagged or not,
+     * in the row-major order of `positions`
+     */
+    pub fn hidden_positions(&self) -> impl Iterator<Item = (u32, u32)> + '_ {
+        self.positions()
+            .filter(move |&(x, y)| self.is_revealed_at(x, y) == Some(false))
+    }
+
+    /**
+     * Every zone with a flag, in the row-major order of `positions`
+     */
+    pub fn flagged_positions(&self) -> impl Iterator<Item = (u32, u32)> + '_ {
+        self.positions()
+            .filter(move |&(x, y)| self.is_flagged_at(x, y) == Some(true))
+    }
+
+    /**
+     * Every hidden zone adjacent (diagonals included, see `adjacent_positions`)
+     * to at least one revealed zone, in the row-major order of `positions`.
+     * These are the zones the revealed counts say something about.
+     */
+    pub fn frontier_positions(&self) -> impl Iterator<Item = (u32, u32)> + '_ {
+        self.hidden_positions().filter(move |&(x, y)| {
+            self.adjacent_iter(x, y, true)
+                .any(|(adj_x, adj_y)| self.is_revealed_at(adj_x, adj_y).unwrap())
+        })
+    }
+
+    /**
+     * Counts the flagged, hidden and revealed zones adjacent to the given
+     * coordinates (diagonals included, see `adjacent_positions`), along with
+     * the count shown on the zone there. Fewer zones are counted on the edges
+     * of the board. None if the coordinates are outside the board.
+     */
+    pub fn neighborhood_summary(&self, x: u32, y: u32) -> Option<NeighborhoodSummary> {
+        let mut summary = NeighborhoodSummary {
+            flagged: 0,
+            hidden: 0,
+            revealed: 0,
+            adjacent: self.get(x, y)?.adjacent_mines(),
+        };
+        for (adj_x, adj_y) in self.adjacent_iter(x, y, true) {
+            match self.zone_state_at(adj_x, adj_y).unwrap() {
+                ZoneState::Hidden { flagged: true, .. }
+                | ZoneState::Hidden { exposed: true, .. } => summary.flagged += 1,
+                ZoneState::Hidden { .. } => summary.hidden += 1,
+                ZoneState::Revealed { .. } | ZoneState::RevealedMine => summary.revealed += 1,
+            }
+        }
+        Some(summary)
+    }
+
+    /**
+     * The constraints the revealed counts put on the hidden zones, for solvers
+     * of one's own. Every revealed zone without a mine gives one over its
+     * hidden, unflagged neighbors, with its count less the mines known around
+     * it: flags, mines shown from the start (see `expose_mines`) and revealed
+     * mines. Zones without such neighbors give none, and a count with more
+     * known mines around it than it shows gives 0.
+     * If `include_total` is true, the last constraint is over every hidden,
+     * unflagged zone, with the mines not yet known. Only what a player can see
+     * goes into the constraints, so wrong flags make them wrong too.
+     */
+    pub fn constraints(&self, include_total: bool) -> Vec<Constraint> {
+        let is_unknown = |x: u32, y: u32| {
+            matches!(
+                self.zone_state_at(x, y),
+                Some(ZoneState::Hidden {
+                    flagged: false,
+                    exposed: false,
+                    ..
+                })
+            )
+        };
+        let mut constraints = Vec::new();
+        for ((x, y), state) in self.zones() {
+            let count = match state {
+                ZoneState::Revealed { adjacent } => adjacent,
+                _ => continue,
+            };
+            let mut cells = Vec::new();
+            let mut known_mines = 0;
+            for (adj_x, adj_y) in self.adjacent_iter(x, y, true) {
+                match self.zone_state_at(adj_x, adj_y).unwrap() {
+                    ZoneState::Revealed { .. } => (),
+                    _ if is_unknown(adj_x, adj_y) => cells.push((adj_x, adj_y)),
+                    _ => known_mines += 1,
+                }
+            }
+            if !cells.is_empty() {
+                constraints.push(Constraint {
+                    cells,
+                    mines: (count - known_mines).max(0) as u32,
+                });
+            }
+        }
+        if include_total {
+            let cells: Vec<_> = self
+                .positions()
+                .filter(|&(x, y)| is_unknown(x, y))
+                .collect();
+            let known_mines = self
+                .zones()
+                .filter(|(_, state)| match state {
+                    ZoneState::Hidden {
+                        flagged, exposed, ..
+                    } => *flagged || *exposed,
+                    ZoneState::RevealedMine => true,
+                    ZoneState::Revealed { .. } => false,
+                })
+                .count() as u32;
+            constraints.push(Constraint {
+                cells,
+                mines: self.num_mines.saturating_sub(known_mines),
+            });
+        }
+        constraints
+    }
+
+    /**
+     * true if this Field counts the mines adjacent to a zone only when asked
+     * to (see `FieldBuilder::lazy_counts`). Such a Field behaves just like
+     * any other, except that `mines_adjacent_to` and co. take a look at the
+     * neighbors of a hidden zone every time they are called on it, and that
+     * it is never equal to a Field counting up front. The counts of revealed
+     * zones are kept, and cascades only ask for the counts of the zones they
+     * reach. Until then, a zone holds nothing but its mine and its marks, in
+     * a single byte unless the Field needs more (see `Zones`).
+     */
+    pub fn lazy_counts(&self) -> bool {
+        self.lazy_counts
+    }
+
+    /**
+     * The number of mines buried in this Field
+     */
+    pub fn num_mines(&self) -> u32 {
+        self.num_mines
+    }
+
+    /**
+     * The number of mines in each row of this Field, indexed by y, for an
+     * assist mode showing them alongside the board. Counts every mine of a
+     * zone holding several, and anti-mines like mines. All 0 while the
+     * mines are yet to be placed (see `new_deferred`).
+     * These give away a lot about where the mines are, so it is up to the
+     * frontend to only show them when asked to.
+     */
+    pub fn row_mine_counts(&self) -> Vec<u32> {
+        self.row_mine_counts.clone()
+    }
+
+    /**
+     * The number of mines in each column of this Field, indexed by x.
+     * See `row_mine_counts`.
+     */
+    pub fn col_mine_counts(&self) -> Vec<u32> {
+        self.col_mine_counts.clone()
+    }
+
+    /**
+     * The total number of flags planted on this Field
+     */
+    pub fn num_flagged(&self) -> u32 {
+        self.num_flagged
+    }
+
+    /**
+     * Limits the number of flags that can be planted by `change_flag_at`
+     * (and `cycle_mark_at`) to the given number, e.g. `num_mines` for a
+     * stricter game, or removes the limit if None, as it is unless set
+     * otherwise. Flags already planted stay where they are, even beyond
+     * the limit; `flag_all_mines` pays no attention to it either.
+     */
+    pub fn set_flag_limit(&mut self, limit: Option<u32>) {
+        self.flag_limit = limit;
+    }
+
+    pub fn flag_limit(&self) -> Option<u32> {
+        self.flag_limit
+    }
+
+    /**
+     * The number of mines minus the number of flags, as shown on a mine
+     * counter. Goes negative when more flags are planted than there are
+     * mines, and says nothing about whether the flags are right.
+     */
+    pub fn mines_remaining(&self) -> i64 {
+        self.num_mines as i64 - self.num_flagged as i64
+    }
+
+    /**
+     * The number of zones revealed so far, with or without mines
+     */
+    pub fn num_revealed(&self) -> u32 {
+        self.num_revealed
+    }
+
+    /**
+     * The number of zones without a mine which are yet to be revealed.
+     * The game is cleared once this reaches 0.
+     */
+    pub fn remaining_safe(&self) -> u32 {
+        let num_safe_zones = if self.placement_pending {
+            // the grid holds no mines yet, but will hold one per zone
+            self.num_safe_zones - self.num_mines
+        } else {
+            self.num_safe_zones
+        };
+        num_safe_zones - self.num_revealed_safe
+    }
+
+    /**
+     * true if every zone without a mine has been revealed, whether or not
+     * any mine has been too (see `any_mine_revealed`). Flags make no
+     * difference. Takes constant time.
+     */
+    pub fn is_cleared(&self) -> bool {
+        self.remaining_safe() == 0
+    }
+
+    /**
+     * true if any zone with a mine has been revealed. Takes constant time.
+     */
+    pub fn any_mine_revealed(&self) -> bool {
+        self.num_revealed > self.num_revealed_safe
+    }
+
+    /**
+     * The number of positions on the board, width by height,
+     * including any masked out of it (see `num_active`)
+     */
+    pub fn total_cells(&self) -> u64 {
+        self.width() as u64 * self.height() as u64
+    }
+
+    /**
+     * boolean indicating if there is a flag planted at the given coordinates
+     */
+    pub fn is_flagged_at(&self, x: u32, y: u32) -> Option<bool> {
+        self.zone_at(x, y).map(|z| z.flagged())
+    }
+
+    /**
+     * if `add` is true, adds a flag, otherwise removes a flag
+     * if trying to add a flag to a zone that is already flagged,
+     * or trying to remove a flag from a zone without a flag, then
+     * nothing will be done and Err(ErrorKind::NoOp) will be returned.
+     * Adding a flag replaces a question mark. Adding a flag fails with
+     * Err(ErrorKind::FlagLimitReached) if no flags are left
+     * (see `set_flag_limit`), while removing one always works.
+     */
+    pub fn change_flag_at(&mut self, x: u32, y: u32, new_flag_value: bool) -> ModelResult<()> {
+        let out_of_flags = self.flag_limit.filter(|&limit| self.num_flagged >= limit);
+        let mut zone = *self.zone_at(x, y).ok_or_else(|| self.out_of_bounds(x, y))?;
+        if zone.flagged() == new_flag_value || (zone.revealed() && new_flag_value) {
+            let op = if new_flag_value {
+                Operation::Flag
+            } else {
+                Operation::Unflag
+            };
+            return Err(ErrorKind::NoOp { op });
+        }
+        if let (true, Some(limit)) = (new_flag_value, out_of_flags) {
+            return Err(ErrorKind::FlagLimitReached { limit });
+        }
+        zone.set_flagged(new_flag_value);
+        zone.set_questioned(false);
+        self.set_zone_at(x, y, zone);
+        let pos = Position::new(x, y);
+        if new_flag_value {
+            self.num_flagged += 1;
+            self.num_flagged_exposed += zone.exposed() as u32;
+            self.events.push(ModelEvent::Flagged { pos });
+        } else {
+            self.num_flagged -= 1;
+            self.num_flagged_exposed -= zone.exposed() as u32;
+            self.events.push(ModelEvent::Unflagged { pos });
+        }
+        self.debug_check_invariants();
+        Ok(())
+    }
+
+    /**
+     * Plants a flag on every hidden zone with a mine, replacing any question
+     * mark, as the final board of a won game shows. Revealed zones are left
+     * alone. This gives away where every mine is; see
+     * `MinesweeperController::flag_all_mines` for a version which waits for
+     * the game to be over.
+     */
+    pub fn flag_all_mines(&mut self) {
+        let height = self.height;
+        for index in 0..self.grid.len() {
+            let (x, y) = (index as u32 / height, index as u32 % height);
+            let mut zone = self.grid[index];
+            if zone.active() && !zone.revealed() && !zone.flagged() && zone.has_mine() {
+                zone.set_flagged(true);
+                zone.set_questioned(false);
+                self.grid.set(index, zone);
+                self.num_flagged += 1;
+                self.num_flagged_exposed += zone.exposed() as u32;
+                let pos = Position::new(x, y);
+                self.events.push(ModelEvent::Flagged { pos });
+            }
+        }
+    }
+
+    /**
+     * Removes every flag. Question marks are left alone.
+     */
+    pub fn clear_flags(&mut self) {
+        let height = self.height;
+        for index in 0..self.grid.len() {
+            let (x, y) = (index as u32 / height, index as u32 % height);
+            if self.grid[index].flagged() {
+                self.grid.update(index, |zone| zone.set_flagged(false));
+                let pos = Position::new(x, y);
+                self.events.push(ModelEvent::Unflagged { pos });
+            }
+        }
+        self.num_flagged = 0;
+        self.num_flagged_exposed = 0;
+    }
+
+    /**
+     * boolean indicating if there is a question mark at the given coordinates
+     */
+    pub fn is_questioned_at(&self, x: u32, y: u32) -> Option<bool> {
+        self.zone_at(x, y).map(|z| z.questioned())
+    }
+
+    /**
+     * The mark on the zone at the given coordinates. Revealed zones are unmarked.
+     */
+    pub fn mark_at(&self, x: u32, y: u32) -> Option<Mark> {
+        self.zone_at(x, y).map(|z| {
+            if z.flagged() {
+                Mark::Flagged
+            } else if z.questioned() {
+                Mark::Questioned
+            } else {
+                Mark::Unmarked
+            }
+        })
+    }
+
+    /**
+     * Moves the mark on the zone at the given coordinates one step along the
+     * classic cycle: unmarked, then flagged, then questioned, then unmarked again.
+     * On success, returns the new mark.
+     * Fails with NoOp if the zone has already been revealed.
+     */
+    pub fn cycle_mark_at(&mut self, x: u32, y: u32) -> ModelResult<Mark> {
+        let mark = self.mark_at(x, y).ok_or_else(|| self.out_of_bounds(x, y))?;
+        if self.is_revealed_at(x, y).unwrap() {
+            return Err(ErrorKind::NoOp {
+                op: Operation::Mark,
+            });
+        }
+        let new_mark = match mark {
+            Mark::Unmarked => {
+                self.change_flag_at(x, y, true)?;
+                Mark::Flagged
+            }
+            Mark::Flagged => {
+                self.change_flag_at(x, y, false)?;
+                let index = self.zone_index(x, y);
+                self.grid.update(index, |zone| zone.set_questioned(true));
+                let pos = Position::new(x, y);
+                self.events.push(ModelEvent::Questioned { pos });
+                Mark::Questioned
+            }
+            Mark::Questioned => {
+                let index = self.zone_index(x, y);
+                self.grid.update(index, |zone| zone.set_questioned(false));
+                let pos = Position::new(x, y);
+                self.events.push(ModelEvent::Unquestioned { pos });
+                Mark::Unmarked
+            }
+        };
+        Ok(new_mark)
+    }
+
+    /**
+     * Ok if the given coordinates are within the dimensions of the Field and
+     * not masked out of it, and otherwise the ErrorKind::OutOfBounds that
+     * any action there fails with
+     */
+    pub fn check_bounds(&self, x: u32, y: u32) -> ModelResult<()> {
+        match self.zone_at(x, y) {
+            Some(_) => Ok(()),
+            None => Err(self.out_of_bounds(x, y)),
+        }
+    }
+
+    pub fn is_revealed_at(&self, x: u32, y: u32) -> Option<bool> {
+        self.zone_at(x, y).map(|z| z.revealed())
+    }
+
+    /**
+     * if the given coordinates are within the dimensions of the Field,
+     * returns Some(n) if the zone there was the nth to be revealed since the
+     * game began (counting from 0), or None if it is still hidden.
+     * Every reveal takes the next stamp, so the zones of a cascade are
+     * stamped in the order `reveal_area` revealed them, and so are the zones
+     * revealed at once by `reveal_mines` and co., by x and then by y.
+     * Stamps are cleared by `reset`, and put back by `restore`.
+     * See `revealed_in_order` for all of them at once.
+     */
+    pub fn reveal_order_at(&self, x: u32, y: u32) -> Option<u32> {
+        self.zone_at(x, y)?;
+        self.reveal_stamps
+            .get(self.zone_index(x, y))
+            .copied()
+            .filter(|&stamp| stamp != UNSTAMPED)
+    }
+
+    /**
+     * Every position revealed since the game began, in the order they were
+     * revealed (see `reveal_order_at`)
+     */
+    pub fn revealed_in_order(&self) -> impl Iterator<Item = (u32, u32)> + '_ {
+        self.reveal_order
+            .iter()
+            .map(move |&index| self.zone_position(index as usize))
+    }
+
+    /**
+     * The position of the mine that blew up: the first zone with a mine to
+     * be revealed by `reveal_at` (or `reveal_area`), or None if there is none.
+     * Mines revealed by `reveal_mines` and co. to show the final board
+     * don't explode.
+     */
+    pub fn exploded_at(&self) -> Option<(u32, u32)> {
+        self.exploded
+            .first()
+            .map(|&index| self.zone_position(index as usize))
+    }
+
+    /**
+     * if the given coordinates are within the dimensions of the Field,
+     * returns everything a player can see of the zone there, in one call.
+     * Hidden mines are never distinguishable from hidden safe zones,
+     * unless they were exposed.
+     */
+    pub fn zone_state_at(&self, x: u32, y: u32) -> Option<ZoneState> {
+        self.get(x, y).map(ZoneView::state)
+    }
+
+    /**
+     * A snapshot of everything a player can currently see of this Field,
+     * which stays as it is while the Field changes. Costs one pass over the
+     * zones, so it can be taken anew every turn.
+     */
+    pub fn player_view(&self) -> PlayerView {
+        PlayerView {
+            width: self.width(),
+            height: self.height(),
+            num_mines: self.num_mines,
+            num_flagged: self.num_flagged,
+            max_mines_per_zone: self.max_mines_per_zone,
+            has_liars: self.has_liars(),
+            has_anti_mines: self.has_anti_mines(),
+            states: (0..self.width())
+                .flat_map(|x| (0..self.height()).map(move |y| (x, y)))
+                .map(|(x, y)| self.zone_state_at(x, y))
+                .collect(),
+            topology: Arc::clone(&self.topology),
+        }
+    }
+
+    /**
+     * A read-only view of the zone at the given coordinates, or None if they
+     * are outside the dimensions of the Field or masked out of it.
+     * `field[(x, y)]` does the same, but panics instead of returning None.
+     */
+    pub fn get(&self, x: u32, y: u32) -> Option<&ZoneView> {
+        self.zone_at(x, y).map(ZoneView::of)
+    }
+
+    /**
+     * returns:
+     *  on success, returns a boolean indicating if the revealed zone
+     *     contains a mine, which then counts as exploded (see `exploded_at`).
+     *     A question mark on the zone is cleared.
+     *  ErrorKind::NoOp indicates that the zone at the given
+     *      coordinates has already been revealed
+     *  ErrorKind::Flagged indicates that the zone at the given
+     *      coordinates has a flag, which is left in place
+     */
+    pub fn reveal_at(&mut self, x: u32, y: u32) -> ModelResult<bool> {
+        if self
+            .zone_at(x, y)
+            .ok_or_else(|| self.out_of_bounds(x, y))?
+            .flagged()
+        {
+            return Err(ErrorKind::Flagged);
+        }
+        if self.placement_pending {
+            self.place_deferred_mines(x, y);
+        }
+        self.memoize_counts(x, y);
+        let mut zone = *self.zone_at(x, y).unwrap();
+        if zone.revealed() {
+            Err(ErrorKind::NoOp {
+                op: Operation::Reveal,
+            })
+        } else {
+            zone.set_revealed(true);
+            zone.set_questioned(false);
+            self.set_zone_at(x, y, zone);
+            let index = self.zone_index(x, y) as u32;
+            self.record_reveal(index);
+            let has_mine = zone.has_mine();
+            if has_mine {
+                self.exploded.push(index);
+            }
+            let event = Self::reveal_event(x, y, &zone);
+            self.num_revealed += 1;
+            if !has_mine {
+                self.num_revealed_safe += 1;
+            }
+            self.events.push(event);
+            Ok(has_mine)
+        }
+    }
+
+    /**
+     * Reveals the zone at the given coordinates just like `reveal_at`, and if
+     * it has neither a mine nor adjacent mines, cascades through the zones
+     * around it (see `cascade_positions`), on through every zone without
+     * adjacent mines that it reaches, revealing the numbered zones around
+     * each of those too. Flagged zones stop the cascade, and it never
+     * reveals a mine.
+     * Fails just like `reveal_at`, without revealing anything.
+     * On success, returns every position revealed, in the order they were
+     * revealed, starting with the given one; whether that one had a mine is
+     * then told by `has_mine_at`.
+     */
+    pub fn reveal_area(&mut self, x: u32, y: u32) -> ModelResult<Vec<(u32, u32)>> {
+        let has_mine = self.reveal_at(x, y)?;
+        let mut revealed = vec![(x, y)];
+        if has_mine || self.mines_adjacent_to(x, y).unwrap() != 0 {
+            self.debug_check_invariants();
+            return Ok(revealed);
+        }
+        let mut stack: Vec<_> = self.cascade_iter(x, y).collect();
+        while let Some((x, y)) = stack.pop() {
+            match self.reveal_at(x, y) {
+                Ok(_) => {
+                    revealed.push((x, y));
+                    // only zones without adjacent mines spread the cascade,
+                    // which reveals the numbered zones at its edge as well
+                    if self.mines_adjacent_to(x, y).unwrap() == 0 {
+                        stack.extend(
+                            self.cascade_iter(x, y)
+                                .filter(|&(x, y)| !self.is_revealed_at(x, y).unwrap())
+                                .filter(|&(x, y)| !self.has_mine_at(x, y).unwrap()),
+                        )
+                    }
+                }
+                // flags stop the cascade
+                Err(ErrorKind::NoOp { .. }) | Err(ErrorKind::Flagged) => continue,
+                Err(e @ ErrorKind::OutOfBounds { .. }) => panic!("{}", e),
+                Err(e) => unreachable!("revealing can't fail with {:?}", e),
+            }
+        }
+        self.debug_check_invariants();
+        Ok(revealed)
+    }
+
+    /**
+     * Reveals the zone at the given coordinates just like `reveal_at`, but
+     * also checks that the zone's contents match the given prediction.
+     * The zone stays revealed even when the prediction was wrong.
+     */
+    pub fn reveal_expecting(
+        &mut self,
+        x: u32,
+        y: u32,
+        expect_mine: bool,
+    ) -> Result<(), PredictionError> {
+        let has_mine = self.reveal_at(x, y).map_err(PredictionError::Model)?;
+        if has_mine == expect_mine {
+            Ok(())
+        } else {
+            Err(PredictionError::Mispredicted { has_mine })
+        }
+    }
+
+    pub fn has_mine_at(&self, x: u32, y: u32) -> Option<bool> {
+        self.zone_at(x, y).map(|z| z.has_mine())
+    }
+
+    /**
+     * The number of mines buried at the given position, which is never more
+     * than 1 unless this Field allows several mines per zone
+     * (see `with_mine_counts`)
+     */
+    pub fn mine_count_at(&self, x: u32, y: u32) -> Option<u8> {
+        self.zone_at(x, y).map(|z| z.mine_count)
+    }
+
+    /**
+     * The most mines a single zone of this Field may hold, as far as a player
+     * knows: 1, unless created with `new_multi_mine` or `with_mine_counts`
+     */
+    pub fn max_mines_per_zone(&self) -> u8 {
+        self.max_mines_per_zone
+    }
+
+    /**
+     * The number of zones containing at least one mine,
+     * which is `num_mines` unless zones can hold several mines
+     */
+    pub fn num_mined_zones(&self) -> u32 {
+        self.num_mined_zones
+    }
+
+    /**
+     * Shows up to `count` hidden mines to the player from the start, as a
+     * handicap for teaching (see `MineSelection`). An exposed mine is still
+     * lethal when revealed, and must still be flagged like any other.
+     * Random selections are determined by the given seed.
+     * returns the number of mines newly exposed, which is less than count
+     * if there weren't enough hidden mines left to expose.
+     * returns Err(ErrorKind::NoOp) if this Field's mines have not been placed yet.
+     */
+    pub fn expose_mines(
+        &mut self,
+        count: u32,
+        selection: MineSelection,
+        seed: u64,
+    ) -> ModelResult<u32> {
+        if self.placement_pending {
+            return Err(ErrorKind::NoOp {
+                op: Operation::ExposeMines,
+            });
+        }
+        let mut candidates: Vec<_> = self
+            .active_positions()
+            .filter(|&(x, y)| {
+                let zone = &self.grid[self.zone_index(x, y)];
+                zone.has_mine() && !zone.revealed() && !zone.exposed()
+            })
+            .collect();
+        let count = (count as usize).min(candidates.len());
+        match selection {
+            MineSelection::Random => {
+                let mut rng = SeededRng::seed_from_u64(seed);
+                for i in 0..count {
+                    let j = rng.gen_range(i, candidates.len());
+                    candidates.swap(i, j);
+                }
+            }
+            MineSelection::MostIsolated => {
+                candidates.sort_by_key(|&(x, y)| self.mines_adjacent_to(x, y).unwrap())
+            }
+        }
+        for &(x, y) in &candidates[..count] {
+            let index = self.zone_index(x, y);
+            self.grid.update(index, |zone| zone.set_exposed(true));
+            self.num_exposed += 1;
+            self.num_flagged_exposed += self.grid[index].flagged() as u32;
+            let pos = Position::new(x, y);
+            self.events.push(ModelEvent::MineExposed { pos });
+        }
+        Ok(count as u32)
+    }
+
+    /**
+     * if the given coordinates are within the dimensions of the Field,
+     * returns true if the zone there holds a mine shown to the player
+     * from the start (see `expose_mines`)
+     */
+    pub fn is_exposed_at(&self, x: u32, y: u32) -> Option<bool> {
+        self.zone_at(x, y).map(|z| z.exposed())
+    }
+
+    /**
+     * The number of mines shown to the player from the start (see `expose_mines`)
+     */
+    pub fn num_exposed(&self) -> u32 {
+        self.num_exposed
+    }
+
+    /**
+     * The number of mines shown to the player from the start that have
+     * since been flagged (see `expose_mines`)
+     */
+    pub fn num_flagged_exposed(&self) -> u32 {
+        self.num_flagged_exposed
+    }
+
+    /**
+     * Buries a mine at the given position, updating `num_mines` and the
+     * adjacent mine counts of its neighbors.
+     * returns Err(ErrorKind::NoOp) if the position already contains a mine,
+     * has been revealed, or if this Field's mines have not been placed yet.
+     */
+    pub fn add_mine_at(&mut self, x: u32, y: u32) -> ModelResult<()> {
+        self.change_mine_at(x, y, true)
+    }
+
+    /**
+     * Removes the mine (or every mine) at the given position, updating
+     * `num_mines` and the adjacent mine counts of its neighbors.
+     * returns Err(ErrorKind::NoOp) if the position doesn't contain a mine,
+     * has been revealed, or if this Field's mines have not been placed yet.
+     */
+    pub fn remove_mine_at(&mut self, x: u32, y: u32) -> ModelResult<()> {
+        self.change_mine_at(x, y, false)
+    }
+
+    /**
+     * Reveals every zone with a mine, flagged or not, for showing the board
+     * once the game is lost. Flags stay where they are, and zones without
+     * a mine are left alone.
+     * (Not to be confused with `expose_mines`, which shows some mines as a
+     * handicap without revealing them.)
+     * Does nothing if this Field's mines have not been placed yet.
+     */
+    pub fn reveal_mines(&mut self) {
+        self.reveal_where(Zone::has_mine);
+    }
+
+    /**
+     * Reveals every zone without a mine, flagged or not, for showing the
+     * board once the game is won, which leaves this Field cleared (see
+     * `is_cleared`). A flag comes off each zone as it is revealed, since no
+     * revealed zone without a mine can hold one, and mines are left hidden.
+     * Does nothing if this Field's mines have not been placed yet.
+     */
+    pub fn reveal_safe_zones(&mut self) {
+        self.reveal_where(|zone| !zone.has_mine());
+    }
+
+    /**
+     * Reveals every zone, flagged or not, for showing the board once the
+     * game is over. Flags stay on mines, and come off the zones without a
+     * mine as they are revealed.
+     * Does nothing if this Field's mines have not been placed yet.
+     */
+    pub fn reveal_all(&mut self) {
+        self.reveal_where(|_| true);
+    }
+
+    /**
+     * Covers every zone back up and removes every flag and question mark,
+     * leaving the mines where they are, so that the same board can be played
+     * again from the start. No mine counts as exploded anymore.
+     */
+    pub fn reset(&mut self) {
+        for index in 0..self.grid.len() {
+            self.grid.update(index, |zone| {
+                zone.set_revealed(false);
+                zone.set_flagged(false);
+                zone.set_questioned(false);
+            });
+        }
+        self.reveal_order.clear();
+        self.reveal_stamps.clear();
+        self.exploded.clear();
+        self.num_flagged = 0;
+        self.num_flagged_exposed = 0;
+        self.num_revealed = 0;
+        self.num_revealed_safe = 0;
+        self.events.push(ModelEvent::Reset);
+        self.debug_check_invariants();
+    }
+
+    /**
+     * A copy of this Field turned a quarter turn clockwise, so that its
+     * width and height trade places: the zone at (x, y) ends up at
+     * (height - 1 - y, x), with its mines, flags, question marks and
+     * whether it's been revealed.
+     * Like every transform, the counters (`num_flagged`, `num_revealed`...)
+     * carry over unchanged, and adjacent mine counts are counted afresh on
+     * the new layout, since a topology like hex tiles isn't preserved by the
+     * transform. The layers of a Field with several (see `new_layered`) are
+     * each transformed on their own and keep their place in the stack, so
+     * that `height` is the height of a layer and only the width of a layer
+     * trades places with it. The copy forgets the seed its mines were
+     * generated from, unless they are yet to be placed, and starts without
+     * pending events (see `drain_events`).
+     */
+    pub fn rotated_90(&self) -> Field {
+        self.transformed(true, |x, y, _, height| (height - 1 - y, x))
+    }
+
+    /**
+     * A copy of this Field turned half a turn: the zone at (x, y) ends up
+     * at (width - 1 - x, height - 1 - y). See `rotated_90`.
+     */
+    pub fn rotated_180(&self) -> Field {
+        self.transformed(false, |x, y, width, height| (width - 1 - x, height - 1 - y))
+    }
+
+    /**
+     * A copy of this Field with its x coordinates mirrored, i.e. flipped
+     * left to right: the zone at (x, y) ends up at (width - 1 - x, y).
+     * See `rotated_90`.
+     */
+    pub fn mirrored_x(&self) -> Field {
+        self.transformed(false, |x, y, width, _| (width - 1 - x, y))
+    }
+
+    /**
+     * A copy of this Field with its y coordinates mirrored, i.e. flipped
+     * top to bottom: the zone at (x, y) ends up at (x, height - 1 - y).
+     * See `rotated_90`.
+     */
+    pub fn mirrored_y(&self) -> Field {
+        self.transformed(false, |x, y, _, height| (x, height - 1 - y))
+    }
+
+    /**
+     * A copy of this Field flipped along its main diagonal, so that its
+     * width and height trade places: the zone at (x, y) ends up at (y, x).
+     * See `rotated_90`.
+     */
+    pub fn transposed(&self) -> Field {
+        self.transformed(true, |x, y, _, _| (y, x))
+    }
+
+    /**
+     * A standalone copy of the rectangle of this Field which is `width` wide
+     * and `height` tall, with its top left corner at (x0, y0): the zone at
+     * (x0 + x, y0 + y) ends up at (x, y), with its mines, flags, question
+     * marks and whether it's been revealed.
+     * Adjacent mine counts are counted afresh from the mines inside the
+     * rectangle alone, so a zone along the cut edge shows fewer mines than
+     * it does here if some of its neighbors were cut off. The counters
+     * (`num_mines`, `num_flagged`, `num_revealed`...) are those of the
+     * rectangle too. The copy keeps this Field's topology, flag limit and
+     * masked out positions, forgets its seed, and starts without pending
+     * events (see `drain_events`). The rectangle of a Field made of layers
+     * (see `new_layered`) must either hold whole layers, which the copy is
+     * made of, or lie inside a single layer, which the copy is.
+     * Fails with OutOfBounds if the rectangle is empty or doesn't fit inside
+     * this Field, with SplitsLayers if it cuts across a layer otherwise, or
+     * with NoOp if this Field's mines have not been placed yet.
+     */
+    pub fn subfield(&self, x0: u32, y0: u32, width: u32, height: u32) -> ModelResult<Field> {
+        let fits = |start: u32, length: u32, limit: u32| {
+            length > 0 && start as u64 + length as u64 <= limit as u64
+        };
+        if !fits(x0, width, self.width()) || !fits(y0, height, self.height()) {
+            // the far corner of the rectangle
+            let x = x0.saturating_add(width.saturating_sub(1));
+            let y = y0.saturating_add(height.saturating_sub(1));
+            return Err(self.out_of_bounds(x, y));
+        }
+        let layer_height = self.layer_height();
+        let depth = if y0.is_multiple_of(layer_height) && height.is_multiple_of(layer_height) {
+            height / layer_height
+        } else if y0 / layer_height == (y0 + height - 1) / layer_height {
+            1
+        } else {
+            return Err(ErrorKind::SplitsLayers { layer_height });
+        };
+        if self.placement_pending {
+            return Err(ErrorKind::NoOp {
+                op: Operation::Crop,
+            });
+        }
+        let topology = if self.depth() > 1 {
+            Arc::new(Layered { depth })
+        } else {
+            self.topology.clone()
+        };
+        let grid: Zones = (x0..x0 + width)
+            .flat_map(|x| (y0..y0 + height).map(move |y| (x, y)))
+            .map(|(x, y)| self.grid[self.zone_index(x, y)])
+            .collect();
+        // the reveals and explosions inside the rectangle, in their order here
+        let cropped = |indices: &[u32]| -> Vec<u32> {
+            indices
+                .iter()
+                .map(|&index| self.zone_position(index as usize))
+                .filter(|&(x, y)| x >= x0 && x < x0 + width && y >= y0 && y < y0 + height)
+                .map(|(x, y)| (x - x0) * height + (y - y0))
+                .collect()
+        };
+        let zones = || grid.iter().filter(|zone| zone.active());
+        let num_revealed = zones().filter(|zone| zone.revealed()).count() as u32;
+        let num_revealed_safe = zones()
+            .filter(|zone| zone.revealed() && !zone.has_mine())
+            .count() as u32;
+        let mut field = Field {
+            num_mines: zones().map(|zone| zone.mine_count as u32).sum(),
+            num_flagged: zones().filter(|zone| zone.flagged()).count() as u32,
+            num_revealed,
+            num_revealed_safe,
+            seed: None,
+            opening_ids: OnceCell::new(),
+            events: Vec::new(),
+            width,
+            height,
+            grid,
+            topology,
+            reveal_order: cropped(&self.reveal_order),
+            reveal_stamps: Vec::new(),
+            exploded: cropped(&self.exploded),
+            ..self.clone()
+        };
+        field.set_adj_counts();
+        field.stamp_reveals();
+        Ok(field)
+    }
+
+    /**
+     * Recounts everything this Field keeps count of from the zones
+     * themselves: the adjacent mine counts of every zone (every revealed
+     * zone, if they are counted lazily, see `lazy_counts`), the numbers of
+     * mines, flags, revealed zones and zones without a mine, and the mine
+     * counts of every row and column. Fails with the first count that
+     * disagrees. Takes a pass over the zones and their neighbors.
+     * A Field whose mines are yet to be placed (see `new_deferred`) holds
+     * more mines than its zones, so its mine counts aren't checked.
+     * Before any of that, checks that there are as many zones and row and
+     * column counts as the width and height call for, and that no zone is
+     * in a state no move could have left it in, so that a Field from an
+     * untrusted source (see `Deserialize`) can be checked without panicking.
+     * Debug builds check after every change made through `change_flag_at`,
+     * `reveal_area`, `add_mine_at`, `remove_mine_at`, `reset` and `restore`,
+     * and panic if anything is off.
+     */
+    pub fn check_invariants(&self) -> Result<(), InvariantViolation> {
+        let num_zones = (self.width as usize).checked_mul(self.height as usize);
+        if self.width == 0
+            || self.height == 0
+            || num_zones != Some(self.grid.len())
+            || self.row_mine_counts.len() != self.height as usize
+            || self.col_mine_counts.len() != self.width as usize
+        {
+            return Err(InvariantViolation::Dimensions);
+        }
+        for (index, zone) in self.grid.iter().enumerate() {
+            if !self.is_well_formed(zone) {
+                let (x, y) = (index as u32 / self.height, index as u32 % self.height);
+                return Err(InvariantViolation::MalformedZone {
+                    position: Position::new(x, y),
+                });
+            }
+        }
+        self.check_reveal_order()?;
+        for (x, y) in self.active_positions() {
+            let zone = &self.grid[self.zone_index(x, y)];
+            if self.lazy_counts && !zone.revealed() {
+                // keeps no counts to check
+                continue;
+            }
+            let (mine_count, net_count) = self.count_adjacent(x, y);
+            let position = Position::new(x, y);
+            if zone.adj_mine_count != mine_count {
+                return Err(InvariantViolation::AdjacentMines {
+                    position,
+                    stored: zone.adj_mine_count,
+                    actual: mine_count,
+                });
+            }
+            if zone.adj_net_count != net_count {
+                return Err(InvariantViolation::AdjacentNet {
+                    position,
+                    stored: zone.adj_net_count,
+                    actual: net_count,
+                });
+            }
+        }
+        let zones = || self.grid.iter().filter(|zone| zone.active());
+        let count = |predicate: fn(&Zone) -> bool| zones().filter(|z| predicate(z)).count() as u32;
+        let num_flagged = count(|z| z.flagged());
+        if self.num_flagged != num_flagged {
+            return Err(InvariantViolation::Flagged {
+                stored: self.num_flagged,
+                actual: num_flagged,
+            });
+        }
+        let num_revealed = count(|z| z.revealed());
+        if self.num_revealed != num_revealed {
+            return Err(InvariantViolation::Revealed {
+                stored: self.num_revealed,
+                actual: num_revealed,
+            });
+        }
+        let num_revealed_safe = count(|z| z.revealed() && !z.has_mine());
+        if self.num_revealed_safe != num_revealed_safe {
+            return Err(InvariantViolation::RevealedSafe {
+                stored: self.num_revealed_safe,
+                actual: num_revealed_safe,
+            });
+        }
+        let num_exposed = count(|z| z.exposed());
+        if self.num_exposed != num_exposed {
+            return Err(InvariantViolation::Exposed {
+                stored: self.num_exposed,
+                actual: num_exposed,
+            });
+        }
+        let num_flagged_exposed = count(|z| z.exposed() && z.flagged());
+        if self.num_flagged_exposed != num_flagged_exposed {
+            return Err(InvariantViolation::FlaggedExposed {
+                stored: self.num_flagged_exposed,
+                actual: num_flagged_exposed,
+            });
+        }
+        if self.placement_pending {
+            return Ok(());
+        }
+        let num_mines = zones().map(|z| z.mine_count as u32).sum();
+        if self.num_mines != num_mines {
+            return Err(InvariantViolation::Mines {
+                stored: self.num_mines,
+                actual: num_mines,
+            });
+        }
+        let num_safe_zones = count(|z| !z.has_mine());
+        if self.num_safe_zones != num_safe_zones {
+            return Err(InvariantViolation::SafeZones {
+                stored: self.num_safe_zones,
+                actual: num_safe_zones,
+            });
+        }
+        let num_mined_zones = count(|z| z.has_mine());
+        if self.num_mined_zones != num_mined_zones {
+            return Err(InvariantViolation::MinedZones {
+                stored: self.num_mined_zones,
+                actual: num_mined_zones,
+            });
+        }
+        for (y, &stored) in self.row_mine_counts.iter().enumerate() {
+            let actual = (0..self.width)
+                .map(|x| self.grid[self.zone_index(x, y as u32)].mine_count as u32)
+                .sum();
+            if stored != actual {
+                let index = y as u32;
+                return Err(InvariantViolation::RowMines {
+                    index,
+                    stored,
+                    actual,
+                });
+            }
+        }
+        for (x, &stored) in self.col_mine_counts.iter().enumerate() {
+            let actual = (0..self.height)
+                .map(|y| self.grid[self.zone_index(x as u32, y)].mine_count as u32)
+                .sum();
+            if stored != actual {
+                let index = x as u32;
+                return Err(InvariantViolation::ColMines {
+                    index,
+                    stored,
+                    actual,
+                });
+            }
+        }
+        Ok(())
+    }
+
+    /**
+     * Takes everything a player could see change since the last call, in the
+     * order it happened, including every zone revealed by a cascade (see
+     * `reveal_area`), so that a frontend can redraw just those zones.
+     * Events are kept until they are drained. Changes to the mines
+     * themselves (see `add_mine_at`) aren't events.
+     */
+    pub fn drain_events(&mut self) -> Vec<ModelEvent> {
+        core::mem::take(&mut self.events)
+    }
+
+    /**
+     * Draws this Field as text, one row per line, each ending in a line break.
+     * With xray, every zone is drawn in the symbols of `AsciiZone`, hidden
+     * mines and all, as Display does and FromStr reads back. Only what the
+     * symbols tell is kept: the reveal order, flags left on revealed zones,
+     * several mines to a zone, anti-mines, exposed mines, liars, the topology
+     * and the seed are lost, and mines yet to be placed aren't drawn.
+     * Without xray, it is what a player can see, headed by a line holding
+     * `ascii::VIEW_HEADER` so that FromStr refuses it: hidden zones are drawn
+     * as if none had a mine, apart from exposed mines (see `expose_mines`),
+     * and revealed zones without a mine as their displayed count (see
+     * `displayed_count_at`), or `+` or `-` if it isn't a single digit.
+     */
+    pub fn to_ascii(&self, xray: bool) -> String {
+        let mut text = String::new();
+        if !xray {
+            text.push_str(ascii::VIEW_HEADER);
+            text.push('\n');
+        }
+        for y in 0..self.height {
+            for x in 0..self.width {
+                let index = self.zone_index(x, y);
+                let zone = &self.grid[index];
+                let symbol = match self.ascii_zone(index) {
+                    AsciiZone::Hidden { mark, .. } if !xray => {
+                        let mine = zone.exposed() && zone.has_mine();
+                        AsciiZone::Hidden { mine, mark }.symbol()
+                    }
+                    AsciiZone::Revealed if !xray => match self.displayed_count_at(x, y).unwrap() {
+                        count if count < 0 => '-',
+                        count => core::char::from_digit(count as u32, 10).unwrap_or('+'),
+                    },
+                    ascii_zone => ascii_zone.symbol(),
+                };
+                text.push(symbol);
+            }
+            text.push('\n');
+        }
+        text
+    }
+
+    /**
+     * A short code for pasting this Field's mine layout into a chat, for
+     * `from_share_code` to read back (see `share::encode`). It says nothing
+     * of what has been revealed or flagged, so sharing a game in progress
+     * doesn't give it away. Masked out positions, several mines to a zone
+     * and anti-mines are lost, and a Field whose mines are yet to be placed
+     * (see `new_deferred`) is shared as a board without mines.
+     */
+    pub fn to_share_code(&self) -> String {
+        let mines = self
+            .positions()
+            .map(|(x, y)| self.has_mine_at(x, y) == Some(true));
+        share::encode(self.width, self.height, mines)
+    }
+
+    /**
+     * The mine layout of this Field in the MBF format (see `from_mbf`),
+     * mines listed row by row. MBF describes nothing but the mines: what has
+     * been revealed or flagged is left out, and a zone with several mines
+     * or an anti-mine is listed like any other mine.
+     * Fails if this Field is more than `mbf::MAX_DIMENSION` zones wide or
+     * tall, has more than `mbf::MAX_MINES` mined zones, or has no mines
+     * placed yet (see `new_deferred`).
+     */
+    pub fn to_mbf(&self) -> Result<Vec<u8>, MbfError> {
+        if self.placement_pending {
+            return Err(MbfError::MinesNotPlaced);
+        }
+        mbf::write_mbf(&MbfBoard {
+            width: self.width,
+            height: self.height,
+            mines: self
+                .positions()
+                .filter(|&(x, y)| self.has_mine_at(x, y) == Some(true))
+                .collect(),
+        })
+    }
+
+    #[cfg(feature = "std")]
+    /**
+     * Writes this Field in the compact binary format of `save::write_save`,
+     * for `read_save` to read back: the dimensions, which zones have mines,
+     * and which are revealed and flagged. Question marks, the reveal order,
+     * which mine exploded, masked out positions, several mines to a zone,
+     * anti-mines, exposed mines, liars, the topology and the seed are lost.
+     */
+    pub fn write_save(&self, writer: impl Write) -> io::Result<()> {
+        let bitmap_len = SaveData::bitmap_len(self.width, self.height);
+        let mut save = SaveData {
+            width: self.width,
+            height: self.height,
+            num_mines: if self.placement_pending {
+                self.num_mines
+            } else {
+                self.num_mined_zones()
+            },
+            mines: vec![0; bitmap_len],
+            revealed: vec![0; bitmap_len],
+            flagged: vec![0; bitmap_len],
+        };
+        let positions = (0..self.height).flat_map(|y| (0..self.width).map(move |x| (x, y)));
+        for (index, (x, y)) in positions.enumerate() {
+            let zone = &self.grid[self.zone_index(x, y)];
+            if zone.has_mine() {
+                SaveData::set_bit(&mut save.mines, index);
+            }
+            if zone.revealed() {
+                SaveData::set_bit(&mut save.revealed, index);
+            }
+            if zone.flagged() {
+                SaveData::set_bit(&mut save.flagged, index);
+            }
+        }
+        save::write_save(writer, &save)
+    }
+
+    /**
+     * Identifies this Field's mine layout, for telling whether a saved game,
+     * a replay or a snapshot (see `restore`) belongs to it.
+     * Depends on nothing but the width, the height and which positions have
+     * mines: not on what has been revealed or flagged, how many mines a zone
+     * holds or whether they are anti-mines. A Field whose mines are yet to be
+     * placed (see `new_deferred`) has the fingerprint of a Field without mines.
+     * The fingerprint is the 64-bit FNV-1a hash of the width and the height,
+     * followed by the x and y of every position with a mine, ordered by x and
+     * then by y, each as 4 little-endian bytes. It stays the same across runs,
+     * platforms and versions: an empty 1x1 Field has the fingerprint
+     * 0x29c7dd317360ac35, and a 4x3 Field with mines at (0, 0) and (3, 2)
+     * has 0xea5ae7d1d4482153.
+     */
+    pub fn layout_fingerprint(&self) -> u64 {
+        const OFFSET_BASIS: u64 = 0xcbf2_9ce4_8422_2325;
+        const PRIME: u64 = 0x0100_0000_01b3;
+        let mine_positions = self
+            .active_positions()
+            .filter(|&(x, y)| self.has_mine_at(x, y).unwrap());
+        core::iter::once((self.width(), self.height()))
+            .chain(mine_positions)
+            .flat_map(|(a, b)| IntoIterator::into_iter((a as u64 | (b as u64) << 32).to_le_bytes()))
+            .fold(OFFSET_BASIS, |hash, byte| {
+                (hash ^ byte as u64).wrapping_mul(PRIME)
+            })
+    }
+
+    /**
+     * Captures what has been revealed, flagged and question-marked so far,
+     * along with the order of the reveals and which mine exploded,
+     * for `restore` to return to later, e.g. to undo a move.
+     */
+    pub fn snapshot(&self) -> FieldSnapshot {
+        let marks = self
+            .grid
+            .iter()
+            .map(|zone| {
+                let mut marks = 0;
+                if zone.flagged() {
+                    marks |= FieldSnapshot::FLAGGED;
+                }
+                if zone.questioned() {
+                    marks |= FieldSnapshot::QUESTIONED;
+                }
+                if zone.revealed() {
+                    marks |= FieldSnapshot::REVEALED;
+                }
+                marks
+            })
+            .collect();
+        FieldSnapshot {
+            layout_fingerprint: self.layout_fingerprint(),
+            num_flagged: self.num_flagged,
+            num_revealed: self.num_revealed,
+            num_revealed_safe: self.num_revealed_safe,
+            marks,
+            reveal_order: self.reveal_order.clone(),
+            exploded: self.exploded.clone(),
+        }
+    }
+
+    /**
+     * Puts back every reveal, flag and question mark as they were when the
+     * given snapshot was taken, and the counters with them.
+     * Fails with ErrorKind::LayoutMismatch, leaving this Field as it is, if
+     * the snapshot was taken of a board with other dimensions or other mines,
+     * as told by their layout fingerprints. That includes this very Field
+     * before its mines were placed (see `new_deferred`).
+     */
+    pub fn restore(&mut self, snapshot: &FieldSnapshot) -> ModelResult<()> {
+        let num_zones = self.width() as usize * self.height() as usize;
+        if snapshot.layout_fingerprint != self.layout_fingerprint()
+            || snapshot.marks.len() != num_zones
+        {
+            return Err(ErrorKind::LayoutMismatch);
+        }
+        for (index, &marks) in snapshot.marks.iter().enumerate() {
+            self.grid.update(index, |zone| {
+                zone.set_flagged(marks & FieldSnapshot::FLAGGED != 0);
+                zone.set_questioned(marks & FieldSnapshot::QUESTIONED != 0);
+                zone.set_revealed(marks & FieldSnapshot::REVEALED != 0);
+            });
+        }
+        self.reveal_order = snapshot.reveal_order.clone();
+        self.stamp_reveals();
+        self.exploded = snapshot.exploded.clone();
+        self.count_zones();
+        if self.lazy_counts {
+            // zones hidden until now may have missed changes to the mines
+            let revealed: Vec<_> = self
+                .active_positions()
+                .filter(|&(x, y)| self.is_revealed_at(x, y).unwrap())
+                .collect();
+            for (x, y) in revealed {
+                self.memoize_counts(x, y);
+            }
+        }
+        self.num_flagged = snapshot.num_flagged;
+        self.num_revealed = snapshot.num_revealed;
+        self.num_revealed_safe = snapshot.num_revealed_safe;
+        self.events.push(ModelEvent::Reset);
+        self.debug_check_invariants();
+        Ok(())
+    }
+
+    /**
+     * if the given coordinates are within the dimensions of the Field,
+     * returns the number of positions adjacent to the given position
+     * which contain a buried mine, of either kind on a Field with anti-mines
+     * (see `net_count_at`)
+     */
+    pub fn mines_adjacent_to(&self, x: u32, y: u32) -> Option<u32> {
+        self.adjacent_counts(x, y)
+            .map(|(mine_count, _, _)| mine_count)
+    }
+
+    /**
+     * if the given coordinates are within the dimensions of the Field,
+     * returns the number of mines adjacent to the given position minus the
+     * number of anti-mines adjacent to it (see `with_anti_mine_placements`),
+     * which is `mines_adjacent_to` on a Field without anti-mines
+     */
+    pub fn net_count_at(&self, x: u32, y: u32) -> Option<i32> {
+        self.adjacent_counts(x, y)
+            .map(|(_, net_count, _)| net_count)
+    }
+
+    /**
+     * if the given coordinates are within the dimensions of the Field,
+     * returns the count shown to the player there: `net_count_at`,
+     * off by one if the zone lies (see `FieldBuilder::liars`)
+     */
+    pub fn displayed_count_at(&self, x: u32, y: u32) -> Option<i32> {
+        self.adjacent_counts(x, y)
+            .map(|(_, net_count, lie)| net_count + lie as i32)
+    }
+
+    /**
+     * if the given coordinates are within the dimensions of the Field,
+     * returns true if the zone there displays a wrong count
+     * (see `FieldBuilder::liars`)
+     */
+    pub fn is_liar_at(&self, x: u32, y: u32) -> Option<bool> {
+        self.zone_at(x, y).map(|z| z.lie != 0)
+    }
+
+    /**
+     * if the given coordinates are within the dimensions of the Field,
+     * returns true if the zone there contains an anti-mine
+     * (see `with_anti_mine_placements`)
+     */
+    pub fn is_anti_mine_at(&self, x: u32, y: u32) -> Option<bool> {
+        self.zone_at(x, y).map(|z| z.has_mine() && z.anti())
+    }
+
+    /**
+     * true if any zone of this Field contains an anti-mine
+     */
+    pub fn has_anti_mines(&self) -> bool {
+        self.grid.iter().any(|z| z.has_mine() && z.anti())
+    }
+
+    /**
+     * true if any zone of this Field displays a wrong count
+     */
+    pub fn has_liars(&self) -> bool {
+        self.grid.iter().any(|z| z.lie != 0)
+    }
+
+    /**
+     * Produces a vector containing all valid, in-bounds (x, y) coordinate pairs
+     * that are adjacent to the given coordinates, as decided by this Field's
+     * Topology (see `new_with_topology`).
+     * If include_diag is true, then diagonal adjacencies will be included.
+     * Hexagonal cells have no diagonals, so all of their (up to 6) neighbors
+     * are included either way, and a Kernel ignores include_diag.
+     * If this Field wraps, coordinates past an edge continue from the opposite
+     * edge instead of being left out. On a Field only one or two positions
+     * wide or tall, the same neighbor can be reached in more than one
+     * direction; it is still only produced once, and a position is never
+     * adjacent to itself.
+     * If this Field has several layers, the positions directly above and below
+     * are adjacent too, and with include_diag the neighbors of those as well.
+     */
+    pub fn adjacent_positions(&self, x: u32, y: u32, include_diag: bool) -> Vec<(u32, u32)> {
+        self.adjacent_iter(x, y, include_diag).collect()
+    }
+
+    /**
+     * The same positions as `adjacent_positions`, in the same order, produced
+     * one at a time. Nothing is allocated unless the Field's Topology can't
+     * give its neighbors as fixed offsets (see `Topology::neighbor_offsets`).
+     */
+    pub fn adjacent_iter(&self, x: u32, y: u32, include_diag: bool) -> AdjacentPositions<'_> {
+        let topology = &*self.topology;
+        if include_diag {
+            self.neighbors_iter(x, y, topology.neighbor_offsets(), || {
+                topology.neighbors(x, y, self.width(), self.height())
+            })
+        } else {
+            self.neighbors_iter(x, y, topology.orthogonal_offsets(), || {
+                topology.orthogonal_neighbors(x, y, self.width(), self.height())
+            })
+        }
+    }
+
+    /**
+     * The positions a cascading reveal spreads to from (x, y), as decided by
+     * this Field's Topology
+     */
+    pub fn cascade_positions(&self, x: u32, y: u32) -> Vec<(u32, u32)> {
+        self.cascade_iter(x, y).collect()
+    }
+
+    /**
+     * The same positions as `cascade_positions`, produced one at a time
+     * like those of `adjacent_iter`
+     */
+    pub fn cascade_iter(&self, x: u32, y: u32) -> AdjacentPositions<'_> {
+        let topology = &*self.topology;
+        self.neighbors_iter(x, y, topology.cascade_offsets(), || {
+            topology.cascade_neighbors(x, y, self.width(), self.height())
+        })
+    }
+
+    /**
+     * The number of distinct adjacent mine counts (1 through 8, or -8 through
+     * 8 but 0 with anti-mines) currently shown among the revealed, mine-free
+     * zones of this Field
+     */
+    pub fn distinct_visible_counts(&self) -> usize {
+        let mut counts: Vec<_> = self
+            .grid
+            .iter()
+            .filter(|z| z.revealed() && !z.has_mine() && z.displayed_count() != 0)
+            .map(|z| z.displayed_count())
+            .collect();
+        counts.sort_unstable();
+        counts.dedup();
+        counts.len()
+    }
+
+    /**
+     * The number of revealed, numbered zones which still have at least one
+     * unflagged mine hidden among their adjacent positions.
+     * Reaches zero once every mine bordering the revealed area is flagged.
+     */
+    pub fn adjacent_danger_count(&self) -> u32 {
+        let mut count = 0;
+        for (x, y) in self.active_positions() {
+            let zone = self.zone_at(x, y).unwrap();
+            if !zone.revealed() || zone.has_mine() || zone.adj_mine_count == 0 {
+                continue;
+            }
+            let in_danger = self
+                .adjacent_iter(x, y, true)
+                .map(|(adj_x, adj_y)| self.zone_at(adj_x, adj_y).unwrap())
+                .any(|adj| adj.has_mine() && !adj.revealed() && !adj.flagged());
+            if in_danger {
+                count += 1;
+            }
+        }
+        count
+    }
+
+    /**
+     * Produces a numeric snapshot of what a player can see on this Field,
+     * indexed row-major (`view[y][x]`). Each entry is one of:
+     *  0..=8  a revealed zone, holding its displayed adjacent mine count
+     *  -1     a hidden zone without a flag
+     *  -2     a hidden zone with a flag
+     *  -3     a revealed zone containing a mine
+     *  -4     a position masked out of the Field (see `with_mask`)
+     *  -5     a hidden mine shown to the player from the start, without a flag
+     *         (see `expose_mines`)
+     *  -11..  a revealed zone with a negative count (see `with_anti_mine_placements`),
+     *         -10 minus its magnitude, so -11 for -1 and -18 for -8
+     * Other hidden mines are never distinguishable from hidden safe zones.
+     */
+    pub fn numeric_view(&self) -> Vec<Vec<i8>> {
+        (0..self.height())
+            .map(|y| {
+                (0..self.width())
+                    .map(|x| {
+                        let zone = match self.zone_at(x, y) {
+                            Some(zone) => zone,
+                            None => return -4,
+                        };
+                        if !zone.revealed() {
+                            if zone.flagged() {
+                                -2
+                            } else if zone.exposed() {
+                                -5
+                            } else {
+                                -1
+                            }
+                        } else if zone.has_mine() {
+                            -3
+                        } else {
+                            match zone.displayed_count() {
+                                count if count < 0 => (-10 + count) as i8,
+                                count => count as i8,
+                            }
+                        }
+                    })
+                    .collect()
+            })
+            .collect()
+    }
+
+    /**
+     * returns true if every position without a mine can be reached from
+     * every other position without a mine by only stepping through
+     * positions without mines (diagonal steps included).
+     * A Field with no mine-free positions is trivially connected, and so is
+     * one whose mines have not been placed yet.
+     */
+    pub fn safe_cells_connected(&self) -> bool {
+        if self.placement_pending {
+            return true;
+        }
+        let num_safe = self.num_safe_zones as usize;
+        let start = self
+            .active_positions()
+            .find(|&(x, y)| !self.has_mine_at(x, y).unwrap());
+        let start = match start {
+            Some(pos) => pos,
+            None => return true,
+        };
+        let mut visited = vec![false; self.grid.len()];
+        visited[self.zone_index(start.0, start.1)] = true;
+        let mut num_visited = 1;
+        let mut stack = vec![start];
+        while let Some((x, y)) = stack.pop() {
+            for pos in self.adjacent_iter(x, y, true) {
+                let index = self.zone_index(pos.0, pos.1);
+                if !self.has_mine_at(pos.0, pos.1).unwrap() && !visited[index] {
+                    visited[index] = true;
+                    num_visited += 1;
+                    stack.push(pos);
+                }
+            }
+        }
+        num_visited == num_safe
+    }
+
+    /**
+     * An opening is a maximal group of mine-free positions with no adjacent
+     * mines, connected to each other through adjacent (including diagonal)
+     * positions. Revealing any position in an opening cascades to reveal the
+     * whole opening, along with the numbered positions bordering it.
+     * Returns an identifier shared by every position in the same opening, or
+     * None if the given position is out of bounds or not part of an opening.
+     * Identifiers are computed once per mine layout, after which lookups are O(1).
+     */
+    pub fn opening_id_at(&self, x: u32, y: u32) -> Option<u32> {
+        self.zone_at(x, y)?;
+        self.opening_ids()[self.zone_index(x, y)]
+    }
+
+    /**
+     * Every opening of this Field (see `opening_id_at`) with its border,
+     * indexed by opening identifier. Independent of what has been revealed
+     * or flagged. Empty if no position is free of adjacent mines.
+     */
+    pub fn openings(&self) -> Vec<Opening> {
+        let num_openings = self
+            .opening_ids()
+            .iter()
+            .flatten()
+            .max()
+            .map_or(0, |&id| id as usize + 1);
+        let mut openings = vec![Opening::default(); num_openings];
+        for (x, y) in self.active_positions() {
+            if let Some(id) = self.opening_id_at(x, y) {
+                openings[id as usize].cells.push((x, y));
+            } else if !self.has_mine_at(x, y).unwrap() {
+                let mut ids: Vec<_> = self
+                    .adjacent_iter(x, y, true)
+                    .filter_map(|(adj_x, adj_y)| self.opening_id_at(adj_x, adj_y))
+                    .collect();
+                ids.sort_unstable();
+                ids.dedup();
+                for id in ids {
+                    openings[id as usize].border.push((x, y));
+                }
+            }
+        }
+        openings
+    }
+
+    /**
+     * returns true if revealing the position `from` would also reveal the
+     * position `to`, either because they are the same position, or because
+     * `from` is part of an opening which contains or borders `to`.
+     * Out of bounds positions are never reached.
+     */
+    pub fn cascade_would_reach(&self, from: (u32, u32), to: (u32, u32)) -> bool {
+        if self.zone_at(from.0, from.1).is_none() || self.zone_at(to.0, to.1).is_none() {
+            return false;
+        }
+        if from == to {
+            return true;
+        }
+        let id = match self.opening_id_at(from.0, from.1) {
+            Some(id) => id,
+            None => return false,
+        };
+        self.opening_id_at(to.0, to.1) == Some(id)
+            || (!self.has_mine_at(to.0, to.1).unwrap()
+                && self
+                    .adjacent_iter(to.0, to.1, true)
+                    .any(|(adj_x, adj_y)| self.opening_id_at(adj_x, adj_y) == Some(id)))
+    }
+
+    /**
+     * The 3BV (Bechtel's Board Benchmark Value) of this Field: the minimum
+     * number of clicks needed to reveal every mine-free position without
+     * flagging, i.e. the number of openings plus the number of numbered
+     * mine-free positions that don't border any opening.
+     * Independent of what has been revealed or flagged.
+     * A Field without mines has a 3BV of 1, and a Field without openings
+     * has as large a 3BV as it has mine-free positions.
+     * This is the number speedrun scoring (see
+     * `MinesweeperController::final_score`) and board filtering
+     * (see `new_with_3bv_range`) go by.
+     */
+    pub fn board_3bv(&self) -> u32 {
+        let num_openings = self
+            .opening_ids()
+            .iter()
+            .flatten()
+            .max()
+            .map_or(0, |&id| id + 1);
+        let mut num_isolated = 0;
+        for (x, y) in self.active_positions() {
+            if self.has_mine_at(x, y).unwrap() || self.opening_id_at(x, y).is_some() {
+                continue;
+            }
+            let borders_opening = self
+                .adjacent_iter(x, y, true)
+                .any(|(adj_x, adj_y)| self.opening_id_at(adj_x, adj_y).is_some());
+            if !borders_opening {
+                num_isolated += 1;
+            }
+        }
+        num_openings + num_isolated
+    }
+
+    /**
+     * The fraction of mine-free positions that would be revealed by clicking
+     * on some opening, i.e. positions that are part of or border an opening.
+     * High coverage means many positions are revealed for free.
+     * A Field without any mine-free positions has a coverage of 0.
+     */
+    pub fn opening_coverage(&self) -> f64 {
+        let mut num_safe = 0;
+        let mut num_covered = 0;
+        for (x, y) in self.active_positions() {
+            if self.has_mine_at(x, y).unwrap() {
+                continue;
+            }
+            num_safe += 1;
+            let covered = self.opening_id_at(x, y).is_some()
+                || self
+                    .adjacent_iter(x, y, true)
+                    .any(|(adj_x, adj_y)| self.opening_id_at(adj_x, adj_y).is_some());
+            if covered {
+                num_covered += 1;
+            }
+        }
+        if num_safe == 0 {
+            0.0
+        } else {
+            num_covered as f64 / num_safe as f64
+        }
+    }
+
+    /**
+     * returns true if revealing a single position would reveal every mine-free
+     * position on this Field, either because one opening covers the whole Field
+     * or because there is only one mine-free position to begin with.
+     */
+    pub fn is_single_click_solvable(&self) -> bool {
+        let mut num_safe = 0;
+        let mut coverage_by_opening = BTreeMap::new();
+        for (x, y) in self.active_positions() {
+            if self.has_mine_at(x, y).unwrap() {
+                continue;
+            }
+            num_safe += 1;
+            let mut ids: Vec<_> = self
+                .adjacent_iter(x, y, true)
+                .filter_map(|(adj_x, adj_y)| self.opening_id_at(adj_x, adj_y))
+                .collect();
+            ids.extend(self.opening_id_at(x, y));
+            ids.sort_unstable();
+            ids.dedup();
+            for id in ids {
+                *coverage_by_opening.entry(id).or_insert(0) += 1;
+            }
+        }
+        num_safe == 1
+            || coverage_by_opening
+                .values()
+                .any(|&covered| covered == num_safe)
+    }
+
+    /**
+     * Analyzes the mine layout of this Field, with one connected-component
+     * pass over its numbered zones on top of its openings (see `openings`).
+     * The difficulty is Trivial if one click clears the board
+     * (see `is_single_click_solvable`), and otherwise goes by the 3BV per
+     * mine-free zone: Easy below 0.26, Medium below 0.31 and Hard beyond,
+     * roughly where the classic beginner, intermediate and expert boards
+     * fall on average.
+     */
+    pub fn analyze(&self) -> BoardAnalysis {
+        let openings = self.openings();
+        let borders_opening = |x: u32, y: u32| {
+            self.adjacent_iter(x, y, true)
+                .any(|(adj_x, adj_y)| self.opening_id_at(adj_x, adj_y).is_some())
+        };
+        let is_isolated = |x: u32, y: u32| {
+            !self.has_mine_at(x, y).unwrap()
+                && self.opening_id_at(x, y).is_none()
+                && !borders_opening(x, y)
+        };
+        let mut num_islands = 0;
+        let mut visited = vec![false; self.grid.len()];
+        for (x, y) in self.active_positions() {
+            let index = self.zone_index(x, y);
+            if !is_isolated(x, y) || visited[index] {
+                continue;
+            }
+            visited[index] = true;
+            num_islands += 1;
+            let mut stack = vec![(x, y)];
+            while let Some((x, y)) = stack.pop() {
+                for adj in self.adjacent_iter(x, y, true) {
+                    let index = self.zone_index(adj.0, adj.1);
+                    if is_isolated(adj.0, adj.1) && !visited[index] {
+                        visited[index] = true;
+                        stack.push(adj);
+                    }
+                }
+            }
+        }
+        let bbbv = self.board_3bv();
+        let num_safe = self.num_active() - self.num_mined_zones();
+        // a Field that is all mines has nothing left to clear
+        let difficulty = if num_safe == 0 || self.is_single_click_solvable() {
+            Difficulty::Trivial
+        } else {
+            match bbbv as f64 / num_safe as f64 {
+                ratio if ratio < 0.26 => Difficulty::Easy,
+                ratio if ratio < 0.31 => Difficulty::Medium,
+                _ => Difficulty::Hard,
+            }
+        };
+        BoardAnalysis {
+            bbbv,
+            num_openings: openings.len() as u32,
+            num_islands,
+            largest_opening: openings
+                .iter()
+                .map(|opening| opening.cells.len() as u32)
+                .max()
+                .unwrap_or(0),
+            mine_density: self.num_mines as f64 / self.num_active() as f64,
+            difficulty,
+        }
+    }
+
+    #[cfg(feature = "std")]
+    /**
+     * Estimates how far a player clicking at random would get on this Field:
+     * plays `samples` games on copies of this Field, each time revealing hidden
+     * positions in a random order until a mine is hit or every mine-free
+     * position is revealed, and averages the number of mine-free positions
+     * revealed per game. Returns 0 if `samples` is 0.
+     */
+    pub fn expected_random_clicks(&self, samples: usize) -> f64 {
+        if samples == 0 {
+            return 0.0;
+        }
+        let mut rng = rand::thread_rng();
+        let mut hidden: Vec<_> = self
+            .active_positions()
+            .filter(|&(x, y)| !self.is_revealed_at(x, y).unwrap())
+            .collect();
+        let mut unflagged = self.clone();
+        // a random clicker pays no attention to flags
+        unflagged.clear_flags();
+        let mut total_safe_reveals = 0;
+        for _ in 0..samples {
+            let mut field = unflagged.clone();
+            hidden.shuffle(&mut rng);
+            for &(x, y) in &hidden {
+                if field.reveal_at(x, y).unwrap() {
+                    break;
+                }
+                total_safe_reveals += 1;
+            }
+        }
+        total_safe_reveals as f64 / samples as f64
+    }
+
+    // whether some sequence of moves could have left a zone of this Field
+    // as it is, see `check_invariants`
+    fn is_well_formed(&self, zone: &Zone) -> bool {
+        let known_bits = Zone::FLAGGED
+            | Zone::QUESTIONED
+            | Zone::REVEALED
+            | Zone::ACTIVE
+            | Zone::EXPOSED
+            | Zone::ANTI;
+        zone.bits & !known_bits == 0
+            && !(zone.flagged() && zone.questioned())
+            && !(zone.flagged() && zone.revealed() && !zone.has_mine())
+            && zone.mine_count <= self.max_mines_per_zone
+    }
+
+    // whether every revealed zone was revealed exactly once, and every
+    // mine exploded exactly once and was revealed, see `check_invariants`.
+    // A zone left out of the reveal order, or listed where it shouldn't be,
+    // is malformed.
+    fn check_reveal_order(&self) -> Result<(), InvariantViolation> {
+        let malformed = |index: usize| {
+            let (x, y) = self.zone_position(index);
+            InvariantViolation::MalformedZone {
+                position: Position::new(x, y),
+            }
+        };
+        let mut listed = vec![false; self.grid.len()];
+        for &index in &self.reveal_order {
+            let index = index as usize;
+            if index >= self.grid.len() {
+                return Err(InvariantViolation::Dimensions);
+            }
+            if listed[index] || !self.grid[index].revealed() || !self.grid[index].active() {
+                return Err(malformed(index));
+            }
+            listed[index] = true;
+        }
+        if let Some(index) = (0..self.grid.len()).find(|&i| self.grid[i].revealed() && !listed[i]) {
+            return Err(malformed(index));
+        }
+        for &index in &self.exploded {
+            let index = index as usize;
+            if index >= self.grid.len() {
+                return Err(InvariantViolation::Dimensions);
+            }
+            // listed turns false once a zone's explosion has been seen
+            if !listed[index] || !self.grid[index].has_mine() {
+                return Err(malformed(index));
+            }
+            listed[index] = false;
+        }
+        Ok(())
+    }
+
+    /**
+     * The index of the zone at the given coordinates in the grid,
+     * which are assumed to be within the dimensions of the Field
+     */
+    fn zone_index(&self, x: u32, y: u32) -> usize {
+        x as usize * self.height as usize + y as usize
+    }
+
+    /**
+     * The coordinates of the zone at the given index in the grid
+     * (see `zone_index`)
+     */
+    fn zone_position(&self, index: usize) -> (u32, u32) {
+        let height = self.height as usize;
+        ((index / height) as u32, (index % height) as u32)
+    }
+
+    fn opening_ids(&self) -> &[Option<u32>] {
+        self.opening_ids.get_or_init(|| self.compute_opening_ids())
+    }
+
+    /**
+     * Labels every opening using a union-find over the positions of this Field.
+     * Identifiers are assigned in order of each opening's first position,
+     * scanning x-major.
+     */
+    fn compute_opening_ids(&self) -> Vec<Option<u32>> {
+        fn find(parents: &mut [usize], mut i: usize) -> usize {
+            while parents[i] != i {
+                parents[i] = parents[parents[i]];
+                i = parents[i];
+            }
+            i
+        }
+        let is_empty = |x: u32, y: u32| {
+            !self.has_mine_at(x, y).unwrap() && self.mines_adjacent_to(x, y).unwrap() == 0
+        };
+        let num_positions = (self.width() * self.height()) as usize;
+        let mut parents: Vec<usize> = (0..num_positions).collect();
+        for (x, y) in self.active_positions() {
+            if !is_empty(x, y) {
+                continue;
+            }
+            for (adj_x, adj_y) in self.adjacent_iter(x, y, true) {
+                if is_empty(adj_x, adj_y) {
+                    let a = find(&mut parents, self.zone_index(x, y));
+                    let b = find(&mut parents, self.zone_index(adj_x, adj_y));
+                    parents[a] = b;
+                }
+            }
+        }
+        let mut root_ids = BTreeMap::new();
+        let mut ids = vec![None; num_positions];
+        for (x, y) in self.active_positions() {
+            if is_empty(x, y) {
+                let index = self.zone_index(x, y);
+                let root = find(&mut parents, index);
+                let next_id = root_ids.len() as u32;
+                ids[index] = Some(*root_ids.entry(root).or_insert(next_id));
+            }
+        }
+        ids
+    }
+
+    /**
+     * The neighbors at the given offsets from (x, y) if there are any,
+     * and otherwise the ones listed by the given function
+     */
+    fn neighbors_iter<'a>(
+        &'a self,
+        x: u32,
+        y: u32,
+        offsets: Option<&'a [(i64, i64)]>,
+        list: impl FnOnce() -> Vec<(u32, u32)>,
+    ) -> AdjacentPositions<'a> {
+        AdjacentPositions {
+            field: self,
+            cursor: NeighborCursor::new(x, y, offsets, list),
+        }
+    }
 
-impl Zone {
-    fn new(has_mine: bool) -> Self {
-        Zone {
-            flagged: false,
-            revealed: false,
-            has_mine,
-            adj_mine_count: 0,
+    /**
+     * Reveals every hidden zone matching the given predicate, flagged or
+     * not, and keeping the counters up to date. Flags come off the zones
+     * without a mine, which no move can leave both revealed and flagged.
+     */
+    fn reveal_where(&mut self, predicate: impl Fn(&Zone) -> bool) {
+        if self.placement_pending {
+            return;
+        }
+        let height = self.height;
+        let indices: Vec<_> = self
+            .grid
+            .iter()
+            .enumerate()
+            .filter(|(_, zone)| zone.active() && !zone.revealed() && predicate(zone))
+            .map(|(index, _)| index)
+            .collect();
+        for index in indices {
+            let (x, y) = (index as u32 / height, index as u32 % height);
+            self.memoize_counts(x, y);
+            let mut zone = self.grid[index];
+            if zone.flagged() && !zone.has_mine() {
+                zone.set_flagged(false);
+                self.num_flagged -= 1;
+                let pos = Position::new(x, y);
+                self.events.push(ModelEvent::Unflagged { pos });
+            }
+            zone.set_revealed(true);
+            zone.set_questioned(false);
+            self.grid.set(index, zone);
+            self.record_reveal(index as u32);
+            self.num_revealed += 1;
+            if !zone.has_mine() {
+                self.num_revealed_safe += 1;
+            }
+            self.events.push(Self::reveal_event(x, y, &zone));
         }
     }
-}
 
-pub struct Field {
-    num_mines: u32,
-    num_flagged: u32,
-    grid: Vec<Vec<Zone>>,
-}
+    /**
+     * The mines adjacent to the given position, of either kind and net
+     * (see `net_count_at`), counted afresh from its neighbors
+     */
+    fn count_adjacent(&self, x: u32, y: u32) -> (u32, i32) {
+        let (mut mine_count, mut net_count) = (0, 0);
+        for (adj_x, adj_y) in self.adjacent_iter(x, y, true) {
+            let adj = &self.grid[self.zone_index(adj_x, adj_y)];
+            mine_count += adj.mine_count as u32;
+            // anti-mines subtract from the net count
+            net_count += adj.net_mines();
+        }
+        (mine_count, net_count)
+    }
 
-impl Field {
     /**
-     * Create a new Field. width and height must be greater than 0.
+     * The adjacent counts of the zone at the given coordinates, as kept by
+     * the zone unless they are counted lazily and it is still hidden
      */
-    pub fn new(width: u32, height: u32, num_mines: u32) -> Option<Self> {
-        if num_mines > width * height {
-            None
+    fn adjacent_counts(&self, x: u32, y: u32) -> Option<(u32, i32, i8)> {
+        let zone = self.zone_at(x, y)?;
+        if self.lazy_counts && !zone.revealed() {
+            let (mine_count, net_count) = self.count_adjacent(x, y);
+            Some((mine_count, net_count, zone.lie))
         } else {
-            let mine_placements = Self::generate_placements(num_mines, width, height);
-            Self::with_mine_placements(width, height, mine_placements)
+            Some((zone.adj_mine_count, zone.adj_net_count, zone.lie))
         }
     }
 
-    pub fn with_mine_placements(
-        width: u32,
-        height: u32,
-        placements: impl IntoIterator<Item=(u32, u32)>,
-    ) -> Option<Self> {
-        let placements: HashSet<_> = placements.into_iter().collect();
-        if placements.len() > (width * height) as usize {
-            return None;
+    /**
+     * Has the zone at the given coordinates keep its adjacent counts,
+     * if they are counted lazily, for when it is revealed
+     */
+    fn memoize_counts(&mut self, x: u32, y: u32) {
+        if !self.lazy_counts {
+            return;
         }
-        let mut freshly_made = Field {
-            num_mines: placements.len() as u32,
-            num_flagged: 0,
-            grid: Self::generate_grid(width, height, &placements),
+        let (mine_count, net_count) = self.count_adjacent(x, y);
+        let index = self.zone_index(x, y);
+        self.grid.update(index, |zone| {
+            zone.adj_mine_count = mine_count;
+            zone.adj_net_count = net_count;
+        });
+    }
+
+    /**
+     * The event of the zone at the given coordinates having been revealed
+     */
+    fn reveal_event(x: u32, y: u32, zone: &Zone) -> ModelEvent {
+        let pos = Position::new(x, y);
+        if zone.has_mine() {
+            ModelEvent::MineRevealed { pos }
+        } else {
+            ModelEvent::Revealed {
+                pos,
+                adjacent: zone.displayed_count(),
+            }
+        }
+    }
+
+    /**
+     * Places the mines of a deferred Field anywhere but the given coordinates
+     * (and their neighbors, if the generation options ask for a safe opening)
+     */
+    fn place_deferred_mines(&mut self, x: u32, y: u32) {
+        let inactive: PositionSet = (0..self.width())
+            .flat_map(|x| (0..self.height()).map(move |y| (x, y)))
+            .filter(|&(x, y)| !self.grid[self.zone_index(x, y)].active())
+            .collect();
+        let mut excluded = if self.generation_options.safe_opening {
+            let num_reserved = self.num_mines + inactive.len() as u32;
+            Self::mine_free_area(
+                x,
+                y,
+                self.width(),
+                self.height(),
+                num_reserved,
+                &*self.topology,
+            )
+        } else {
+            core::iter::once((x, y)).collect()
         };
-        freshly_made.set_adj_counts(placements);
-        Some(freshly_made)
+        excluded.extend(inactive);
+        let placements = Self::generate_placements(
+            self.num_mines,
+            self.width(),
+            self.height(),
+            &mut SeededRng::seed_from_u64(self.seed.unwrap()),
+            &excluded,
+        );
+        for (mine_x, mine_y) in placements {
+            let index = self.zone_index(mine_x, mine_y);
+            self.grid.update(index, |zone| zone.mine_count = 1);
+        }
+        self.set_adj_counts();
+        self.placement_pending = false;
+        self.opening_ids = OnceCell::new();
     }
 
     /**
-     * The height of this Field
+     * Takes back up the mines of a deferred Field placed by its first reveal,
+     * so that the next reveal places them afresh, the same way given the same
+     * coordinates. Meant for a Field that has just been `reset`.
      */
-    pub fn height(&self) -> u32 {
-        self.grid[0].len() as u32
+    #[cfg(feature = "std")]
+    pub(crate) fn unplace_deferred_mines(&mut self) {
+        for index in 0..self.grid.len() {
+            self.grid.update(index, |zone| zone.mine_count = 0);
+        }
+        self.set_adj_counts();
+        self.placement_pending = true;
+        self.opening_ids = OnceCell::new();
+        self.debug_check_invariants();
     }
 
     /**
-     * The width of this Field
+     * The height of the Field holding `depth` layers of the given dimensions
      */
-    pub fn width(&self) -> u32 {
-        self.grid.len() as u32
+    fn layered_height(width: u32, height: u32, depth: u32) -> Result<u32, CreationError> {
+        if depth == 0 {
+            return Err(CreationError::ZeroDepth);
+        }
+        height
+            .checked_mul(depth)
+            .ok_or(CreationError::TooLarge { width, height })
     }
 
     /**
-     * The number of mines buried in this Field
+     * Turns count of the mined zones into anti-mines (see `FieldBuilder::anti_mines`),
+     * drawing from yet another stream of the seed's generator
      */
-    pub fn num_mines(&self) -> u32 {
-        self.num_mines
+    fn choose_anti_mines(&mut self, count: u32, seed: u64) -> Result<(), CreationError> {
+        let mut mined: Vec<_> = self
+            .active_positions()
+            .filter(|&(x, y)| self.grid[self.zone_index(x, y)].has_mine())
+            .collect();
+        if count as usize > mined.len() {
+            return Err(CreationError::TooManyMines {
+                requested: count,
+                capacity: mined.len() as u32,
+            });
+        }
+        let mut rng = SeededRng::seed_from_u64(seed);
+        rng.set_stream(2);
+        for i in 0..count as usize {
+            let j = rng.gen_range(i, mined.len());
+            mined.swap(i, j);
+        }
+        for &(x, y) in &mined[..count as usize] {
+            let index = self.zone_index(x, y);
+            self.grid.update(index, |zone| zone.set_anti(true));
+        }
+        self.set_adj_counts();
+        Ok(())
     }
 
     /**
-     * The total number of flags planted on this Field
+     * Makes round(fraction * candidates) of the numbered, mine-free zones lie
+     * (see `FieldBuilder::liars`). Drawn from a stream of the seed's generator
+     * separate from the one placing mines, so liars don't change the layout.
      */
-    pub fn num_flagged(&self) -> u32 {
-        self.num_flagged
+    fn choose_liars(&mut self, fraction: f64, seed: u64) {
+        let mut rng = SeededRng::seed_from_u64(seed);
+        rng.set_stream(1);
+        // the directions each candidate can lie in, without displaying 0
+        // or more mines than it has neighbors
+        let mut candidates: Vec<_> = self
+            .active_positions()
+            .filter_map(|(x, y)| {
+                let zone = &self.grid[self.zone_index(x, y)];
+                if zone.has_mine() || zone.adj_mine_count == 0 {
+                    return None;
+                }
+                let num_adjacent = self.adjacent_iter(x, y, true).count() as u32;
+                let up = zone.adj_mine_count < num_adjacent;
+                let down = zone.adj_mine_count > 1;
+                if up || down {
+                    Some(((x, y), up, down))
+                } else {
+                    None
+                }
+            })
+            .collect();
+        let num_liars = ((fraction * candidates.len() as f64 + 0.5) as usize).min(candidates.len());
+        for i in 0..num_liars {
+            let j = rng.gen_range(i, candidates.len());
+            candidates.swap(i, j);
+        }
+        for &((x, y), up, down) in &candidates[..num_liars] {
+            let lie = if up && (!down || rng.gen()) { 1 } else { -1 };
+            let index = self.zone_index(x, y);
+            self.grid.update(index, |zone| zone.lie = lie);
+        }
     }
 
     /**
-     * boolean indicating if there is a flag planted at the given coordinates
+     * Places mines with the given strategy, checking that it placed exactly
+     * num_mines of them (see `new_with_strategy`)
      */
-    pub fn is_flagged_at(&self, x: u32, y: u32) -> Option<bool> {
-        self.zone_at(x, y).map(|z| z.flagged)
+    fn place_with_strategy(
+        width: u32,
+        height: u32,
+        num_mines: u32,
+        strategy: &mut impl PlacementStrategy,
+        seed: u64,
+    ) -> Result<PositionSet, CreationError> {
+        Self::check_capacity(width, height, num_mines, 0)?;
+        let mut rng = SeededRng::seed_from_u64(seed);
+        let placements = strategy.place(width, height, num_mines, &mut rng)?;
+        if placements.len() != num_mines as usize {
+            return Err(CreationError::WrongMineCount {
+                requested: num_mines,
+                placed: placements.len() as u32,
+            });
+        }
+        Ok(placements)
     }
 
     /**
-     * if `add` is true, adds a flag, otherwise removes a flag
-     * if trying to add a flag to a zone that is already flagged,
-     * or trying to remove a flag from a zone without a flag, then
-     * nothing will be done and Err(ErrorKind::NoOp) will be returned.
+     * Generates layouts until one can be solved without guessing
+     * (see `new_solvable`)
      */
-    pub fn change_flag_at(&mut self, x: u32, y: u32, new_flag_value: bool) -> ModelResult<()> {
-        let zone = self.zone_at_mut(x, y).ok_or(ErrorKind::OutOfBounds)?;
-        if zone.flagged == new_flag_value || (zone.revealed && new_flag_value) {
-            return Err(ErrorKind::NoOp);
+    fn solvable_layout(
+        width: u32,
+        height: u32,
+        num_mines: u32,
+        first_click: (u32, u32),
+        seed: u64,
+        topology: Arc<dyn Topology>,
+    ) -> Result<Self, CreationError> {
+        Self::check_capacity(width, height, num_mines, 1)?;
+        let (x, y) = first_click;
+        if x >= width || y >= height {
+            return Err(CreationError::FirstClickOutOfBounds);
         }
-        zone.flagged = new_flag_value;
-        if new_flag_value {
-            self.num_flagged += 1;
-        } else {
-            self.num_flagged -= 1;
+        let excluded = Self::mine_free_area(x, y, width, height, num_mines, &*topology);
+        let mut rng = SeededRng::seed_from_u64(seed);
+        for _ in 0..MAX_SOLVABLE_ATTEMPTS {
+            let mine_placements =
+                Self::generate_placements(num_mines, width, height, &mut rng, &excluded);
+            let field = Self::build(width, height, mine_placements, topology.clone())?;
+            if solver::is_solvable(&field, first_click) {
+                return Ok(field);
+            }
+        }
+        Err(CreationError::AttemptsExhausted {
+            attempts: MAX_SOLVABLE_ATTEMPTS,
+        })
+    }
+
+    /**
+     * Checks that the given dimensions are non-zero and not too large, and that
+     * there is room for num_mines mines on a Field of those dimensions while
+     * keeping `reserved` zones free of mines.
+     * Once this has passed, `width * height` can't overflow a u32.
+     */
+    fn check_capacity(
+        width: u32,
+        height: u32,
+        num_mines: u32,
+        reserved: u32,
+    ) -> Result<(), CreationError> {
+        if width == 0 {
+            return Err(CreationError::ZeroWidth);
+        }
+        if height == 0 {
+            return Err(CreationError::ZeroHeight);
+        }
+        let num_zones = u32::try_from(width as u64 * height as u64)
+            .map_err(|_| CreationError::TooLarge { width, height })?;
+        let capacity = num_zones.saturating_sub(reserved);
+        if num_mines > capacity {
+            return Err(CreationError::TooManyMines {
+                requested: num_mines,
+                capacity,
+            });
         }
         Ok(())
     }
 
-    pub fn is_revealed_at(&self, x: u32, y: u32) -> Option<bool> {
-        self.zone_at(x, y).map(|z| z.revealed)
+    /**
+     * The positions to keep free of mines when the given position should start
+     * an opening: the position together with every position the given
+     * Topology makes adjacent to it, or only the position itself if that
+     * leaves too little room for num_mines mines.
+     */
+    fn mine_free_area(
+        x: u32,
+        y: u32,
+        width: u32,
+        height: u32,
+        num_mines: u32,
+        topology: &dyn Topology,
+    ) -> PositionSet {
+        let neighborhood: PositionSet = core::iter::once((x, y))
+            .chain(topology.neighbors(x, y, width, height))
+            .collect();
+        if num_mines as usize + neighborhood.len() <= (width * height) as usize {
+            neighborhood
+        } else {
+            core::iter::once((x, y)).collect()
+        }
     }
 
     /**
-     * returns:
-     *  on success, returns a boolean indicating if the revealed zone
-     *     contains a mine
-     *  ErrorKind::NoOp indicates that the zone at the given
-     *      coordinates has already been revealed
+     * Shared by `add_mine_at` and `remove_mine_at`: gives the zone at the
+     * given position a mine if has_mine is true, or removes all of its mines
+     * otherwise, updating `num_mines` to match.
      */
-    pub fn reveal_at(&mut self, x: u32, y: u32) -> ModelResult<bool> {
-        let zone = self.zone_at_mut(x, y).ok_or(ErrorKind::OutOfBounds)?;
-        if zone.revealed {
-            Err(ErrorKind::NoOp)
+    fn change_mine_at(&mut self, x: u32, y: u32, has_mine: bool) -> ModelResult<()> {
+        let mut zone = *self.zone_at(x, y).ok_or_else(|| self.out_of_bounds(x, y))?;
+        if zone.has_mine() == has_mine || zone.revealed() || self.placement_pending {
+            let op = if has_mine {
+                Operation::AddMine
+            } else {
+                Operation::RemoveMine
+            };
+            return Err(ErrorKind::NoOp { op });
+        }
+        let removed = zone.mine_count as u32;
+        let net_removed = zone.net_mines();
+        if zone.exposed() {
+            self.num_exposed -= 1;
+            self.num_flagged_exposed -= zone.flagged() as u32;
+        }
+        zone.mine_count = has_mine as u8;
+        zone.set_exposed(false);
+        zone.set_anti(false);
+        self.set_zone_at(x, y, zone);
+        // the neighbors change as they are walked, so the walk borrows the
+        // topology rather than this Field
+        let topology = Arc::clone(&self.topology);
+        let (width, height) = (self.width(), self.height());
+        let mut adjacent = NeighborCursor::new(x, y, topology.neighbor_offsets(), || {
+            topology.neighbors(x, y, width, height)
+        });
+        while let Some((adj_x, adj_y)) = adjacent.next_on(self) {
+            let index = self.zone_index(adj_x, adj_y);
+            let mut zone = self.grid[index];
+            if self.lazy_counts && !zone.revealed() {
+                // keeps no counts to update
+                continue;
+            }
+            if has_mine {
+                zone.adj_mine_count += 1;
+                zone.adj_net_count += 1;
+            } else {
+                zone.adj_mine_count -= removed;
+                zone.adj_net_count -= net_removed;
+            }
+            // whatever it lied about has changed
+            zone.lie = 0;
+            self.grid.set(index, zone);
+        }
+        if has_mine {
+            self.num_mines += 1;
+            self.num_safe_zones -= 1;
+            self.num_mined_zones += 1;
+            self.row_mine_counts[y as usize] += 1;
+            self.col_mine_counts[x as usize] += 1;
         } else {
-            zone.revealed = true;
-            Ok(zone.has_mine)
+            self.num_mines -= removed;
+            self.num_safe_zones += 1;
+            self.num_mined_zones -= 1;
+            self.row_mine_counts[y as usize] -= removed;
+            self.col_mine_counts[x as usize] -= removed;
         }
+        self.opening_ids = OnceCell::new();
+        self.debug_check_invariants();
+        Ok(())
     }
 
-    pub fn has_mine_at(&self, x: u32, y: u32) -> Option<bool> {
-        self.zone_at(x, y).map(|z| z.has_mine)
+    /**
+     * A copy of this Field where each zone has been moved to the position
+     * the given function maps its own to, given the width and height of its
+     * layer. The function must map the positions of a layer one to one onto
+     * those of a layer as wide as the old one was tall and as tall as it was
+     * wide if `swaps_sides`, or else of the same size. Each zone stays in its
+     * own layer.
+     */
+    fn transformed(
+        &self,
+        swaps_sides: bool,
+        to: impl Fn(u32, u32, u32, u32) -> (u32, u32),
+    ) -> Field {
+        let (layer_width, layer_height) = (self.width(), self.layer_height());
+        let (width, new_layer_height) = if swaps_sides {
+            (layer_height, layer_width)
+        } else {
+            (layer_width, layer_height)
+        };
+        let height = new_layer_height * self.depth();
+        // indexed like any grid `height` tall
+        let moved = |index: usize| {
+            let (x, y) = (index as u32 / self.height, index as u32 % self.height);
+            let (z, layer_y) = (y / layer_height, y % layer_height);
+            let (new_x, new_y) = to(x, layer_y, layer_width, layer_height);
+            new_x as usize * height as usize + (z * new_layer_height + new_y) as usize
+        };
+        let mut grid = self.grid.clone();
+        for (index, &zone) in self.grid.iter().enumerate() {
+            grid.set(moved(index), zone);
+        }
+        let moved_all = |indices: &[u32]| -> Vec<u32> {
+            indices
+                .iter()
+                .map(|&index| moved(index as usize) as u32)
+                .collect()
+        };
+        let mut field = Field {
+            width,
+            height,
+            grid,
+            reveal_order: moved_all(&self.reveal_order),
+            reveal_stamps: Vec::new(),
+            exploded: moved_all(&self.exploded),
+            // the mines of the copy weren't generated where they now are
+            seed: self.seed.filter(|_| self.placement_pending),
+            opening_ids: OnceCell::new(),
+            events: Vec::new(),
+            ..self.clone()
+        };
+        field.set_adj_counts();
+        field.stamp_reveals();
+        field
     }
 
     /**
-     * if the given coordinates are within the dimensions of the Field,
-     * returns the number of positions adjacent to the given position
-     * which contain a buried mine
+     * Adds the zone at the given index to the end of the reveal order,
+     * stamping it with its place there
      */
-    pub fn mines_adjacent_to(&self, x: u32, y: u32) -> Option<u32> {
-        self.zone_at(x, y).map(|z| z.adj_mine_count)
+    fn record_reveal(&mut self, index: u32) {
+        if self.reveal_stamps.is_empty() {
+            self.reveal_stamps.resize(self.grid.len(), UNSTAMPED);
+        }
+        self.reveal_stamps[index as usize] = self.reveal_order.len() as u32;
+        self.reveal_order.push(index);
     }
 
     /**
-     * Produces a vector containing all valid, in-bounds (x, y) coordinate pairs
-     * that are adjacent to the given coordinates.
-     * If include_diag is true, then diagonal adjacencies will be included.
-     * TODO: expound
+     * Stamps every revealed zone afresh with its place in the reveal order
      */
-    pub fn adjacent_positions(&self, x: u32, y: u32, include_diag: bool) -> Vec<(u32, u32)> {
-        let x = x as i32;
-        let y = y as i32;
-        let mut positions = Vec::with_capacity(8);
-        positions.push((x, y - 1));
-        positions.push((x - 1, y));
-        positions.push((x + 1, y));
-        positions.push((x, y + 1));
-        if include_diag {
-            positions.push((x - 1, y - 1));
-            positions.push((x - 1, y + 1));
-            positions.push((x + 1, y - 1));
-            positions.push((x + 1, y + 1));
+    fn stamp_reveals(&mut self) {
+        self.reveal_stamps.clear();
+        if !self.reveal_order.is_empty() {
+            self.reveal_stamps.resize(self.grid.len(), UNSTAMPED);
+        }
+        for (stamp, &index) in self.reveal_order.iter().enumerate() {
+            self.reveal_stamps[index as usize] = stamp as u32;
         }
-        positions
-            .into_iter()
-            // safely convert back to u32
-            .filter(|&(x, y)| x >= 0 && y >= 0)
-            .map(|(x, y)| (x as u32, y as u32))
-            // filter out coordinates that aren't in-bounds
-            .filter(|&(x, y)| self.zone_at(x, y).is_some())
-            .collect()
     }
 
     /**
-     * Updates the adjacent mine counts for Zones in this Field.
-     * Shouldn't be called more than once.
+     * Updates the adjacent mine counts for Zones in this Field, and the
+     * mine counts of its rows and columns, to match the mines currently
+     * buried in it.
      */
-    fn set_adj_counts(&mut self, mine_placements: HashSet<(u32, u32)>) {
-        // reset all counts to 0:
-        // for column in self.grid.iter_mut() {
-        //     for zone in column.iter_mut() {
-        //         zone.adj_mine_count = 0;
-        //     }
-        // }
-        for (x, y) in mine_placements {
-            for (adj_x, adj_y) in self.adjacent_positions(x, y, true) {
-                let zone = &mut self.grid[adj_x as usize][adj_y as usize];
-                // increment mine count
-                zone.adj_mine_count += 1;
+    fn set_adj_counts(&mut self) {
+        self.row_mine_counts = vec![0; self.height() as usize];
+        self.col_mine_counts = vec![0; self.width() as usize];
+        for (index, zone) in self.grid.iter().enumerate() {
+            let (x, y) = (index / self.height as usize, index % self.height as usize);
+            self.row_mine_counts[y] += zone.mine_count as u32;
+            self.col_mine_counts[x] += zone.mine_count as u32;
+        }
+        for index in 0..self.grid.len() {
+            self.grid.update(index, |zone| {
+                zone.adj_mine_count = 0;
+                zone.adj_net_count = 0;
+            });
+        }
+        // adjacency goes both ways, so counting the mines around each zone
+        // counts every mine once for each of its neighbors.
+        // Lazy counts are only kept by revealed zones.
+        // Counting reads only mine counts, so zones are updated as they go.
+        let height = self.height as usize;
+        for index in 0..self.grid.len() {
+            let zone = &self.grid[index];
+            if !zone.active() || (self.lazy_counts && !zone.revealed()) {
+                continue;
+            }
+            let (x, y) = ((index / height) as u32, (index % height) as u32);
+            let (mine_count, net_count) = self.count_adjacent(x, y);
+            self.grid.update(index, |zone| {
+                zone.adj_mine_count = mine_count;
+                zone.adj_net_count = net_count;
+            });
+        }
+        self.count_zones();
+    }
+
+    /**
+     * Masks the given positions out of this Field, which hold no mines
+     */
+    fn deactivate(&mut self, inactive: &PositionSet) {
+        for &(x, y) in inactive {
+            let index = self.zone_index(x, y);
+            self.grid.update(index, |zone| zone.set_active(false));
+        }
+        self.count_zones();
+    }
+
+    /**
+     * Recounts the zones without a mine and with one, and the exposed mines
+     */
+    fn count_zones(&mut self) {
+        let grid = &self.grid;
+        let zones = || grid.iter().filter(|z| z.active());
+        let count = |predicate: fn(&Zone) -> bool| zones().filter(|z| predicate(z)).count() as u32;
+        self.num_safe_zones = count(|z| !z.has_mine());
+        self.num_mined_zones = count(|z| z.has_mine());
+        self.num_exposed = count(|z| z.exposed());
+        self.num_flagged_exposed = count(|z| z.exposed() && z.flagged());
+    }
+
+    /**
+     * How the zone at the given index in the grid is drawn by `to_ascii`
+     * with xray
+     */
+    fn ascii_zone(&self, index: usize) -> AsciiZone {
+        let zone = &self.grid[index];
+        if !zone.active() {
+            AsciiZone::Masked
+        } else if zone.revealed() && zone.has_mine() {
+            AsciiZone::RevealedMine {
+                exploded: self.exploded.contains(&(index as u32)),
+            }
+        } else if zone.revealed() {
+            AsciiZone::Revealed
+        } else {
+            let mark = if zone.flagged() {
+                Mark::Flagged
+            } else if zone.questioned() {
+                Mark::Questioned
+            } else {
+                Mark::Unmarked
+            };
+            AsciiZone::Hidden {
+                mine: zone.has_mine(),
+                mark,
+            }
+        }
+    }
+
+    /**
+     * Panics if `check_invariants` fails, in debug builds only
+     */
+    fn debug_check_invariants(&self) {
+        if cfg!(debug_assertions) {
+            if let Err(violation) = self.check_invariants() {
+                panic!("{}", violation);
             }
         }
     }
 
+    /**
+     * The ErrorKind::OutOfBounds for the given coordinates
+     */
+    fn out_of_bounds(&self, x: u32, y: u32) -> ErrorKind {
+        ErrorKind::OutOfBounds {
+            pos: Position::new(x, y),
+            width: self.width(),
+            height: self.height(),
+        }
+    }
+
     /**
      * TODO
      */
     fn zone_at(&self, x: u32, y: u32) -> Option<&Zone> {
-        self.grid.get(x as usize)?.get(y as usize)
+        if x >= self.width || y >= self.height {
+            return None;
+        }
+        Some(&self.grid[self.zone_index(x, y)]).filter(|zone| zone.active())
     }
 
     /**
-     * TODO
+     * Stores the given zone at the given coordinates, which are assumed to
+     * be within the dimensions of the Field
      */
-    fn zone_at_mut(&mut self, x: u32, y: u32) -> Option<&mut Zone> {
-        self.grid.get_mut(x as usize)?.get_mut(y as usize)
+    fn set_zone_at(&mut self, x: u32, y: u32, zone: Zone) {
+        let index = self.zone_index(x, y);
+        self.grid.set(index, zone);
     }
 
     /**
-     * TODO
+     * Randomly chooses num_mines distinct coordinates within the given bounds,
+     * never choosing any of the coordinates in `excluded`.
+     * When at most a quarter of the candidates are to be chosen, coordinates
+     * are drawn until enough distinct ones turn up, so that a large, sparse
+     * Field doesn't need a list of every candidate.
+     * pre-condition: num_mines <= upper_x_bound * upper_y_bound - excluded.len()
      */
     fn generate_placements(
         num_mines: u32,
         upper_x_bound: u32,
         upper_y_bound: u32,
-    ) -> HashSet<(u32, u32)> {
+        rng: &mut impl Rng,
+        excluded: &PositionSet,
+    ) -> PositionSet {
         let num_mines = num_mines as usize;
-        let mut rng = rand::thread_rng();
-        let mut coordinates = HashSet::with_capacity(num_mines);
-        while coordinates.len() < num_mines {
-            let x = rng.gen_range(0, upper_x_bound);
-            let y = rng.gen_range(0, upper_y_bound);
-            coordinates.insert((x, y));
+        let num_candidates =
+            (upper_x_bound as usize * upper_y_bound as usize).saturating_sub(excluded.len());
+        if num_mines <= num_candidates / 4 {
+            // a bit for every candidate, set once it has been drawn
+            let mut drawn =
+                vec![0u64; (upper_x_bound as usize * upper_y_bound as usize).div_ceil(64)];
+            let mut placements = Vec::with_capacity(num_mines);
+            while placements.len() < num_mines {
+                let x = rng.gen_range(0, upper_x_bound);
+                let y = rng.gen_range(0, upper_y_bound);
+                let index = x as usize * upper_y_bound as usize + y as usize;
+                if drawn[index / 64] & 1 << (index % 64) == 0 && !excluded.contains(&(x, y)) {
+                    drawn[index / 64] |= 1 << (index % 64);
+                    placements.push((x, y));
+                }
+            }
+            return placements.into_iter().collect();
+        }
+        let mut candidates: Vec<_> = (0..upper_x_bound)
+            .flat_map(|x| (0..upper_y_bound).map(move |y| (x, y)))
+            .filter(|pos| !excluded.contains(pos))
+            .collect();
+        debug_assert!(num_mines <= candidates.len());
+        // partial Fisher-Yates shuffle: after each step i, the first i
+        // candidates are a uniformly random selection of all of them
+        for i in 0..num_mines {
+            let j = rng.gen_range(i, candidates.len());
+            candidates.swap(i, j);
         }
-        coordinates
+        candidates.truncate(num_mines);
+        candidates.into_iter().collect()
     }
 
     /**
-     * TODO
+     * The zones of a Field of the given dimensions, with a mine at each of
+     * the given placements, packed into a byte each (see `Zones`)
      */
-    fn generate_grid(
-        width: u32,
-        height: u32,
-        mine_placements: &HashSet<(u32, u32)>,
-    ) -> Vec<Vec<Zone>> {
-        let mut grid = Vec::with_capacity(width as usize);
-        for x in 0..width {
-            let mut column = Vec::with_capacity(height as usize);
-            for y in 0..height {
-                let has_mine = mine_placements.contains(&(x, y));
-                column.push(Zone::new(has_mine));
-            }
-            grid.push(column);
+    fn generate_grid(width: u32, height: u32, mine_placements: &PositionSet) -> Zones {
+        let empty = Zone::new(0).pack().unwrap();
+        let mut grid = Zones::Packed(vec![empty; width as usize * height as usize]);
+        for &(x, y) in mine_placements {
+            let index = x as usize * height as usize + y as usize;
+            grid.update(index, |zone| zone.mine_count = 1);
         }
         grid
     }
 }
+
+/**
+ * The positions adjacent to a position of a Field,
+ * produced by `Field::adjacent_iter` and `Field::cascade_iter`
+ */
+pub struct AdjacentPositions<'a> {
+    field: &'a Field,
+    cursor: NeighborCursor<'a>,
+}
+
+// the walk behind AdjacentPositions, borrowing only the offsets of a topology
+// and given the Field at each step, so that a Field can change its zones
+// while walking their neighbors (see `Field::change_mine_at`)
+struct NeighborCursor<'a> {
+    x: u32,
+    y: u32,
+    source: Neighbors<'a>,
+}
+
+// where AdjacentPositions gets its candidates from, before leaving out
+// positions off the board or masked out of it
+enum Neighbors<'a> {
+    Offsets(core::slice::Iter<'a, (i64, i64)>),
+    Listed(alloc::vec::IntoIter<(u32, u32)>),
+}
+
+impl Iterator for AdjacentPositions<'_> {
+    type Item = (u32, u32);
+
+    fn next(&mut self) -> Option<(u32, u32)> {
+        self.cursor.next_on(self.field)
+    }
+}
+
+impl<'a> NeighborCursor<'a> {
+    // the neighbors at the given offsets from (x, y) if there are any,
+    // and otherwise the ones listed by the given function
+    fn new(
+        x: u32,
+        y: u32,
+        offsets: Option<&'a [(i64, i64)]>,
+        list: impl FnOnce() -> Vec<(u32, u32)>,
+    ) -> Self {
+        let source = match offsets {
+            Some(offsets) => Neighbors::Offsets(offsets.iter()),
+            None => Neighbors::Listed(list().into_iter()),
+        };
+        NeighborCursor { x, y, source }
+    }
+
+    // the next neighbor that is a position of the given Field
+    fn next_on(&mut self, field: &Field) -> Option<(u32, u32)> {
+        loop {
+            let (x, y) = match &mut self.source {
+                Neighbors::Offsets(offsets) => {
+                    let &(dx, dy) = offsets.next()?;
+                    let adj_x = u32::try_from(self.x as i64 + dx);
+                    let adj_y = u32::try_from(self.y as i64 + dy);
+                    match (adj_x, adj_y) {
+                        (Ok(adj_x), Ok(adj_y)) => (adj_x, adj_y),
+                        _ => continue,
+                    }
+                }
+                Neighbors::Listed(positions) => positions.next()?,
+            };
+            if field.zone_at(x, y).is_some() {
+                return Some((x, y));
+            }
+        }
+    }
+}
+
+impl PartialEq for Field {
+    /**
+     * Fields are equal if they have the same mines, flags, question marks,
+     * reveals and counts, on the same topology, regardless of the seed they
+     * came from. Topologies that aren't built in (see `Topology::builtin`)
+     * are only equal to themselves.
+     */
+    fn eq(&self, other: &Self) -> bool {
+        let same_topology = match (self.topology.builtin(), other.topology.builtin()) {
+            (Some(topology), Some(other_topology)) => topology == other_topology,
+            _ => Arc::ptr_eq(&self.topology, &other.topology),
+        };
+        same_topology
+            && self.num_mines == other.num_mines
+            && self.num_flagged == other.num_flagged
+            && self.flag_limit == other.flag_limit
+            && self.num_revealed == other.num_revealed
+            && self.num_revealed_safe == other.num_revealed_safe
+            && self.num_safe_zones == other.num_safe_zones
+            && self.placement_pending == other.placement_pending
+            && self.generation_options == other.generation_options
+            && self.max_mines_per_zone == other.max_mines_per_zone
+            && self.lazy_counts == other.lazy_counts
+            && self.width == other.width
+            && self.grid == other.grid
+    }
+}
+
+impl FromStr for Field {
+    type Err = AsciiError;
+
+    /**
+     * Reads a board drawn one row per line in the symbols of `AsciiZone`,
+     * as written by Display: "....\n.*..\n" is a Field 4 wide and 2 tall
+     * with a mine at (1, 1). Marks are put on, and then zones revealed one
+     * at a time, row by row, so nothing cascades.
+     * Fails with the line and column of the first symbol that isn't a zone,
+     * the line of the first row that isn't as long as the first, or if the
+     * text is a player's view of a board (see `Field::to_ascii`).
+     */
+    fn from_str(text: &str) -> Result<Self, AsciiError> {
+        let board = ascii::read_ascii(text)?;
+        let width = board.width;
+        let zones = || {
+            (0..board.height)
+                .flat_map(move |y| (0..width).map(move |x| (x, y)))
+                .zip(board.zones.iter().copied())
+        };
+        let mine_placements = zones()
+            .filter(|(_, zone)| zone.has_mine())
+            .map(|(position, _)| position);
+        let mut field = Self::with_mine_placements(board.width, board.height, mine_placements)?;
+        for ((x, y), _) in zones().filter(|&(_, zone)| zone == AsciiZone::Masked) {
+            let index = field.zone_index(x, y);
+            field.grid.update(index, |zone| zone.set_active(false));
+        }
+        field.count_zones();
+        for ((x, y), zone) in zones() {
+            match zone {
+                AsciiZone::Hidden {
+                    mark: Mark::Flagged,
+                    ..
+                } => field.change_flag_at(x, y, true).unwrap(),
+                AsciiZone::Hidden {
+                    mark: Mark::Questioned,
+                    ..
+                } => {
+                    let index = field.zone_index(x, y);
+                    field.grid.update(index, |zone| zone.set_questioned(true));
+                }
+                _ => (),
+            }
+        }
+        for ((x, y), zone) in zones() {
+            match zone {
+                AsciiZone::Revealed => {
+                    field.reveal_at(x, y).unwrap();
+                }
+                AsciiZone::RevealedMine { exploded } => {
+                    field.reveal_at(x, y).unwrap();
+                    if !exploded {
+                        // reveal_at counted it as exploded
+                        field.exploded.pop();
+                    }
+                }
+                _ => (),
+            }
+        }
+        Ok(field)
+    }
+}
+
+impl fmt::Display for Field {
+    /**
+     * The whole board, hidden mines and all, as read by FromStr
+     * (see `Field::to_ascii`)
+     */
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(&self.to_ascii(true))
+    }
+}
+
+impl fmt::Debug for Field {
+    /**
+     * A drawing of what a player can see of the Field, one line per row:
+     * `#` for a hidden zone, `F` for a flag, `?` for a question mark,
+     * `*` for a revealed or exposed mine, `.` for a revealed zone without
+     * adjacent mines and its count for any other revealed zone
+     * (`+` if the count isn't a single digit).
+     * Only the alternate form (`{:#?}`) gives away the hidden mines,
+     * drawing them as `*` too, so that logging a Field doesn't spoil the game.
+     */
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let spoil = f.alternate();
+        writeln!(
+            f,
+            "Field {}x{} with {} mines, {} flagged",
+            self.width(),
+            self.height(),
+            self.num_mines,
+            self.num_flagged
+        )?;
+        for y in 0..self.height() {
+            let row: String = (0..self.width())
+                .map(|x| match self.zone_at(x, y) {
+                    // masked out of the board
+                    None => ' ',
+                    Some(zone) if spoil && zone.has_mine() => '*',
+                    Some(zone) => match ZoneView::of(zone).state() {
+                        ZoneState::Hidden { exposed: true, .. } | ZoneState::RevealedMine => '*',
+                        ZoneState::Hidden { flagged: true, .. } => 'F',
+                        ZoneState::Hidden {
+                            questioned: true, ..
+                        } => '?',
+                        ZoneState::Hidden { .. } => '#',
+                        ZoneState::Revealed { adjacent: 0 } => '.',
+                        ZoneState::Revealed { adjacent } => {
+                            core::char::from_digit(adjacent as u32, 10)
+                                .filter(|_| adjacent > 0)
+                                .unwrap_or('+')
+                        }
+                    },
+                })
+                .collect();
+            writeln!(f, "{}", row)?;
+        }
+        Ok(())
+    }
+}
+
+impl Index<(u32, u32)> for Field {
+    type Output = ZoneView;
+
+    /**
+     * The view of a zone given by `Field::get`.
+     * Panics if there is no zone at the given coordinates.
+     */
+    fn index(&self, (x, y): (u32, u32)) -> &ZoneView {
+        self.get(x, y).unwrap_or_else(|| {
+            panic!(
+                "no zone at ({}, {}) on a {}x{} Field",
+                x,
+                y,
+                self.width(),
+                self.height()
+            )
+        })
+    }
+}
+
+impl TryFrom<&[Vec<bool>]> for Field {
+    type Error = CreationError;
+
+    /**
+     * See `Field::from_bool_grid`
+     */
+    fn try_from(rows: &[Vec<bool>]) -> Result<Self, Self::Error> {
+        Self::from_bool_grid(rows)
+    }
+}
+
+impl TryFrom<Vec<Vec<bool>>> for Field {
+    type Error = CreationError;
+
+    /**
+     * See `Field::from_bool_grid`
+     */
+    fn try_from(rows: Vec<Vec<bool>>) -> Result<Self, Self::Error> {
+        Self::from_bool_grid(&rows)
+    }
+}
@@ -0,0 +1,228 @@
+use crate::model::CreationError;
+use alloc::string::String;
+use alloc::vec;
+use alloc::vec::Vec;
+use core::fmt;
+use std::io::{self, BufRead};
+
+/**
+ * The most pixels an image may have, so that a corrupt header can't make
+ * the loader try to read billions of them
+ */
+pub const MAX_PIXELS: u64 = 1 << 24;
+
+#[derive(Debug)]
+/**
+ * Produced by `Field::from_pbm`
+ * `Io` indicates that reading failed, for the contained reason
+ * `UnknownFormat` indicates that the image did not start with P1 or P4
+ * `MalformedHeader` indicates that the width or height was missing or not a number
+ * `TooLarge` indicates that the image had more than `MAX_PIXELS` pixels
+ * `MissingPixels` indicates that the image ended after only `found` of its
+ *     `expected` pixels
+ * `InvalidPixel` indicates that an ASCII image contained something other
+ *     than 0, 1, whitespace or comments among its pixels
+ * `Creation` indicates that the image could not be turned into a Field,
+ *     for the contained reason
+ */
+pub enum PbmError {
+    Io(io::Error),
+    UnknownFormat,
+    MalformedHeader,
+    TooLarge { width: u32, height: u32 },
+    MissingPixels { expected: u64, found: u64 },
+    InvalidPixel(u8),
+    Creation(CreationError),
+}
+
+impl fmt::Display for PbmError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            PbmError::Io(e) => write!(f, "could not read the image: {}", e),
+            PbmError::UnknownFormat => write!(f, "the image is not a P1 or P4 PBM image"),
+            PbmError::MalformedHeader => {
+                write!(f, "the image header has no valid width and height")
+            }
+            PbmError::TooLarge { width, height } => write!(
+                f,
+                "a {}x{} image has more than {} pixels",
+                width, height, MAX_PIXELS
+            ),
+            PbmError::MissingPixels { expected, found } => write!(
+                f,
+                "the image has {} pixels, but only {} were found",
+                expected, found
+            ),
+            PbmError::InvalidPixel(byte) => {
+                write!(f, "{:?} is not a valid pixel", *byte as char)
+            }
+            PbmError::Creation(e) => write!(f, "could not create the board: {}", e),
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for PbmError {}
+
+impl From<io::Error> for PbmError {
+    fn from(e: io::Error) -> Self {
+        PbmError::Io(e)
+    }
+}
+
+impl From<CreationError> for PbmError {
+    fn from(e: CreationError) -> Self {
+        PbmError::Creation(e)
+    }
+}
+
+/**
+ * The contents of a 1-bit PBM image
+ */
+pub struct PbmImage {
+    pub width: u32,
+    pub height: u32,
+    /**
+     * the (x, y) of every black pixel, in row-major order
+     */
+    pub black_pixels: Vec<(u32, u32)>,
+}
+
+/**
+ * Reads a 1-bit PBM image in either the ASCII (P1) or the binary (P4)
+ * encoding. Anything after the last pixel is ignored.
+ */
+pub fn read_pbm(mut reader: impl BufRead) -> Result<PbmImage, PbmError> {
+    let mut magic = [0; 2];
+    reader.read_exact(&mut magic).map_err(|e| match e.kind() {
+        io::ErrorKind::UnexpectedEof => PbmError::UnknownFormat,
+        _ => PbmError::Io(e),
+    })?;
+    let binary = match &magic {
+        b"P1" => false,
+        b"P4" => true,
+        _ => return Err(PbmError::UnknownFormat),
+    };
+    let width = read_header_number(&mut reader)?;
+    let height = read_header_number(&mut reader)?;
+    let expected = width as u64 * height as u64;
+    if expected > MAX_PIXELS {
+        return Err(PbmError::TooLarge { width, height });
+    }
+    let pixels = if binary {
+        read_binary_pixels(&mut reader, width, height)?
+    } else {
+        read_ascii_pixels(&mut reader, expected)?
+    };
+    let black_pixels = pixels
+        .iter()
+        .enumerate()
+        .filter(|&(_, &black)| black)
+        .map(|(i, _)| {
+            (
+                (i as u64 % width as u64) as u32,
+                (i as u64 / width as u64) as u32,
+            )
+        })
+        .collect();
+    Ok(PbmImage {
+        width,
+        height,
+        black_pixels,
+    })
+}
+
+/**
+ * Reads a width or height from the header, skipping the whitespace and
+ * comments before it as well as the single whitespace byte after it
+ */
+fn read_header_number(reader: &mut impl BufRead) -> Result<u32, PbmError> {
+    skip_whitespace_and_comments(reader)?;
+    let mut digits = String::new();
+    while let Some(byte) = peek(reader)? {
+        if !byte.is_ascii_digit() {
+            break;
+        }
+        digits.push(byte as char);
+        reader.consume(1);
+    }
+    match peek(reader)? {
+        Some(byte) if byte.is_ascii_whitespace() => reader.consume(1),
+        // a comment can directly follow a number
+        Some(b'#') | None => (),
+        Some(_) => return Err(PbmError::MalformedHeader),
+    }
+    digits.parse().map_err(|_| PbmError::MalformedHeader)
+}
+
+/**
+ * P1 pixels are 0s and 1s, optionally separated by whitespace and comments
+ */
+fn read_ascii_pixels(reader: &mut impl BufRead, expected: u64) -> Result<Vec<bool>, PbmError> {
+    let mut pixels = Vec::with_capacity(expected as usize);
+    while (pixels.len() as u64) < expected {
+        skip_whitespace_and_comments(reader)?;
+        match peek(reader)? {
+            Some(b'0') => pixels.push(false),
+            Some(b'1') => pixels.push(true),
+            Some(byte) => return Err(PbmError::InvalidPixel(byte)),
+            None => {
+                return Err(PbmError::MissingPixels {
+                    expected,
+                    found: pixels.len() as u64,
+                })
+            }
+        }
+        reader.consume(1);
+    }
+    Ok(pixels)
+}
+
+/**
+ * P4 pixels are packed 8 to a byte, most significant bit first,
+ * with every row padded to a whole number of bytes
+ */
+fn read_binary_pixels(
+    reader: &mut impl BufRead,
+    width: u32,
+    height: u32,
+) -> Result<Vec<bool>, PbmError> {
+    let row_bytes = (width as usize).div_ceil(8);
+    let mut row = vec![0; row_bytes];
+    let mut pixels = Vec::with_capacity(width as usize * height as usize);
+    for y in 0..height as u64 {
+        if let Err(e) = reader.read_exact(&mut row) {
+            return Err(match e.kind() {
+                io::ErrorKind::UnexpectedEof => PbmError::MissingPixels {
+                    expected: width as u64 * height as u64,
+                    found: y * width as u64,
+                },
+                _ => PbmError::Io(e),
+            });
+        }
+        pixels.extend((0..width as usize).map(|x| row[x / 8] & (0x80 >> (x % 8)) != 0));
+    }
+    Ok(pixels)
+}
+
+fn skip_whitespace_and_comments(reader: &mut impl BufRead) -> Result<(), PbmError> {
+    let mut in_comment = false;
+    while let Some(byte) = peek(reader)? {
+        if in_comment {
+            in_comment = byte != b'\n';
+        } else if byte == b'#' {
+            in_comment = true;
+        } else if !byte.is_ascii_whitespace() {
+            break;
+        }
+        reader.consume(1);
+    }
+    Ok(())
+}
+
+/**
+ * The next byte, without consuming it. None at the end of the image.
+ */
+fn peek(reader: &mut impl BufRead) -> Result<Option<u8>, PbmError> {
+    Ok(reader.fill_buf()?.first().copied())
+}
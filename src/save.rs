@@ -0,0 +1,232 @@
+use crate::model::CreationError;
+use crate::model::InvariantViolation;
+#[cfg(feature = "std")]
+use alloc::vec;
+use alloc::vec::Vec;
+use core::fmt;
+#[cfg(feature = "std")]
+use std::io::{self, Read, Write};
+
+/**
+ * The bytes every save starts with
+ */
+pub const MAGIC: [u8; 4] = *b"MSWP";
+
+/**
+ * The version of the format written by `write_save`, the only one read
+ */
+pub const VERSION: u8 = 1;
+
+/**
+ * The most zones a save may have, so that a corrupt header can't make
+ * the loader try to read billions of them
+ */
+pub const MAX_ZONES: u64 = 1 << 24;
+
+// magic, version, width, height and mine count
+#[cfg(feature = "std")]
+const HEADER_LEN: usize = 4 + 1 + 4 + 4 + 4;
+
+#[derive(Debug)]
+/**
+ * Produced by `Field::read_save`
+ * `Io` indicates that reading or writing failed, for the contained reason
+ * `NotASave` indicates that the data did not start with `MAGIC`
+ * `UnsupportedVersion` indicates that the save is in the contained version
+ *     of the format, which this build can't read
+ * `Truncated` indicates that the data ended before the save did
+ * `TooLarge` indicates that the save had more than `MAX_ZONES` zones
+ * `ChecksumMismatch` indicates that the save was corrupted
+ * `MineCountMismatch` indicates that the header counted `expected` mines,
+ *     but the mine bitmap held `found`
+ * `Creation` indicates that the save could not be turned into a Field,
+ *     for the contained reason
+ * `Invalid` indicates that the save holds a board no moves could have left
+ *     as it is, for the contained reason
+ */
+pub enum SaveError {
+    #[cfg(feature = "std")]
+    Io(io::Error),
+    NotASave,
+    UnsupportedVersion(u8),
+    Truncated,
+    TooLarge {
+        width: u32,
+        height: u32,
+    },
+    ChecksumMismatch,
+    MineCountMismatch {
+        expected: u32,
+        found: u32,
+    },
+    Creation(CreationError),
+    Invalid(InvariantViolation),
+}
+
+impl fmt::Display for SaveError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            #[cfg(feature = "std")]
+            SaveError::Io(e) => write!(f, "could not read or write the save: {}", e),
+            SaveError::NotASave => write!(f, "the file is not a saved game"),
+            SaveError::UnsupportedVersion(version) => write!(
+                f,
+                "the save is in version {} of the format, but only version {} is supported",
+                version, VERSION
+            ),
+            SaveError::Truncated => write!(f, "the save is cut short"),
+            SaveError::TooLarge { width, height } => write!(
+                f,
+                "a {}x{} board has more than {} zones",
+                width, height, MAX_ZONES
+            ),
+            SaveError::ChecksumMismatch => write!(f, "the save is corrupted"),
+            SaveError::MineCountMismatch { expected, found } => write!(
+                f,
+                "the save should have {} mines, but has {}",
+                expected, found
+            ),
+            SaveError::Creation(e) => write!(f, "could not create the board: {}", e),
+            SaveError::Invalid(violation) => write!(f, "the save doesn't add up: {}", violation),
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for SaveError {}
+
+#[cfg(feature = "std")]
+impl From<io::Error> for SaveError {
+    fn from(e: io::Error) -> Self {
+        match e.kind() {
+            io::ErrorKind::UnexpectedEof => SaveError::Truncated,
+            _ => SaveError::Io(e),
+        }
+    }
+}
+
+impl From<CreationError> for SaveError {
+    fn from(e: CreationError) -> Self {
+        SaveError::Creation(e)
+    }
+}
+
+/**
+ * The contents of a save
+ * Each bitmap has a bit for every position, in row-major order, packed
+ * 8 to a byte, least significant bit first.
+ */
+pub struct SaveData {
+    pub width: u32,
+    pub height: u32,
+    pub num_mines: u32,
+    pub mines: Vec<u8>,
+    pub revealed: Vec<u8>,
+    pub flagged: Vec<u8>,
+}
+
+impl SaveData {
+    /**
+     * The number of bytes each bitmap of a board of the given size takes
+     */
+    pub fn bitmap_len(width: u32, height: u32) -> usize {
+        (width as usize * height as usize).div_ceil(8)
+    }
+
+    /**
+     * Whether the bit of the given index is set in the given bitmap
+     */
+    pub fn bit(bitmap: &[u8], index: usize) -> bool {
+        bitmap[index / 8] & 1 << (index % 8) != 0
+    }
+
+    /**
+     * Sets the bit of the given index in the given bitmap
+     */
+    pub fn set_bit(bitmap: &mut [u8], index: usize) {
+        bitmap[index / 8] |= 1 << (index % 8);
+    }
+}
+
+/**
+ * Writes a save, in this order:
+ * the 4 bytes of `MAGIC` and the single byte of `VERSION`,
+ * the width, the height and the mine count, each as 4 little-endian bytes,
+ * the mine, revealed and flagged bitmaps (see `SaveData`),
+ * and the 64-bit FNV-1a hash of everything before it, as 8 little-endian bytes.
+ * A 2x2 board with a mine at (1, 1) and (0, 0) revealed is saved as the bytes
+ *     4d 53 57 50 01 02 00 00 00 02 00 00 00 01 00 00 00
+ *     08 01 00 4d b2 b0 4d 47 53 72 a5
+ * Any change to that is a new version of the format.
+ */
+#[cfg(feature = "std")]
+pub fn write_save(mut writer: impl Write, save: &SaveData) -> io::Result<()> {
+    let mut bytes = Vec::with_capacity(HEADER_LEN + 3 * save.mines.len() + 8);
+    bytes.extend_from_slice(&MAGIC);
+    bytes.push(VERSION);
+    bytes.extend_from_slice(&save.width.to_le_bytes());
+    bytes.extend_from_slice(&save.height.to_le_bytes());
+    bytes.extend_from_slice(&save.num_mines.to_le_bytes());
+    bytes.extend_from_slice(&save.mines);
+    bytes.extend_from_slice(&save.revealed);
+    bytes.extend_from_slice(&save.flagged);
+    let checksum = checksum(&bytes);
+    bytes.extend_from_slice(&checksum.to_le_bytes());
+    writer.write_all(&bytes)
+}
+
+/**
+ * Reads a save written by `write_save`. Anything after the checksum is ignored.
+ */
+#[cfg(feature = "std")]
+pub fn read_save(mut reader: impl Read) -> Result<SaveData, SaveError> {
+    let mut header = [0; HEADER_LEN];
+    reader.read_exact(&mut header[..MAGIC.len()])?;
+    if header[..MAGIC.len()] != MAGIC {
+        return Err(SaveError::NotASave);
+    }
+    reader.read_exact(&mut header[MAGIC.len()..=MAGIC.len()])?;
+    let version = header[MAGIC.len()];
+    if version != VERSION {
+        return Err(SaveError::UnsupportedVersion(version));
+    }
+    reader.read_exact(&mut header[MAGIC.len() + 1..])?;
+    let number = |at: usize| {
+        u32::from_le_bytes([header[at], header[at + 1], header[at + 2], header[at + 3]])
+    };
+    let (width, height, num_mines) = (number(5), number(9), number(13));
+    if width as u64 * height as u64 > MAX_ZONES {
+        return Err(SaveError::TooLarge { width, height });
+    }
+    let bitmap_len = SaveData::bitmap_len(width, height);
+    let mut bitmaps = vec![0; 3 * bitmap_len];
+    reader.read_exact(&mut bitmaps)?;
+    let mut stored = [0; 8];
+    reader.read_exact(&mut stored)?;
+    let mut bytes = header.to_vec();
+    bytes.extend_from_slice(&bitmaps);
+    if checksum(&bytes) != u64::from_le_bytes(stored) {
+        return Err(SaveError::ChecksumMismatch);
+    }
+    let flagged = bitmaps.split_off(2 * bitmap_len);
+    let revealed = bitmaps.split_off(bitmap_len);
+    Ok(SaveData {
+        width,
+        height,
+        num_mines,
+        mines: bitmaps,
+        revealed,
+        flagged,
+    })
+}
+
+/**
+ * The 64-bit FNV-1a hash of the given bytes
+ */
+pub fn checksum(bytes: &[u8]) -> u64 {
+    const OFFSET_BASIS: u64 = 0xcbf2_9ce4_8422_2325;
+    const PRIME: u64 = 0x0100_0000_01b3;
+    bytes.iter().fold(OFFSET_BASIS, |hash, &byte| {
+        (hash ^ byte as u64).wrapping_mul(PRIME)
+    })
+}
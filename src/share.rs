@@ -0,0 +1,229 @@
+use crate::model::CreationError;
+use crate::save::checksum;
+use alloc::string::String;
+use alloc::vec::Vec;
+use core::convert::TryFrom;
+use core::fmt;
+
+/**
+ * The most zones a shared board may have, so that a hostile code can't make
+ * the decoder try to lay out billions of them
+ */
+pub const MAX_ZONES: u64 = 1 << 24;
+
+// the URL-safe base64 alphabet, so that codes survive being pasted into links
+const ALPHABET: &[u8; 64] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789-_";
+
+// the bytes of the checksum at the end of a code
+const CHECKSUM_LEN: usize = 2;
+
+#[derive(Debug, Clone, Eq, PartialEq)]
+/**
+ * Produced by `Field::from_share_code`
+ * `InvalidChar` indicates that `found`, the character at (zero-indexed)
+ *     `index`, is not URL-safe base64
+ * `ChecksumMismatch` indicates that the code was mistyped or cut short
+ * `Malformed` indicates that the code passed its checksum, but doesn't
+ *     describe a board
+ * `TooLarge` indicates that a board of the contained dimensions would have
+ *     more than `MAX_ZONES` zones
+ * `Creation` indicates that the code could not be turned into a Field,
+ *     for the contained reason
+ */
+pub enum ShareCodeError {
+    InvalidChar { index: usize, found: char },
+    ChecksumMismatch,
+    Malformed,
+    TooLarge { width: u32, height: u32 },
+    Creation(CreationError),
+}
+
+impl fmt::Display for ShareCodeError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ShareCodeError::InvalidChar { index, found } => {
+                write!(f, "{:?} at {} can't be part of a share code", found, index)
+            }
+            ShareCodeError::ChecksumMismatch => {
+                write!(f, "the share code has a typo, or is incomplete")
+            }
+            ShareCodeError::Malformed => write!(f, "the share code doesn't describe a board"),
+            ShareCodeError::TooLarge { width, height } => write!(
+                f,
+                "a {}x{} board has more than {} zones",
+                width, height, MAX_ZONES
+            ),
+            ShareCodeError::Creation(e) => write!(f, "could not create the board: {}", e),
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for ShareCodeError {}
+
+impl From<CreationError> for ShareCodeError {
+    fn from(e: CreationError) -> Self {
+        ShareCodeError::Creation(e)
+    }
+}
+
+/**
+ * The mine layout of a shared board
+ */
+pub struct SharedBoard {
+    pub width: u32,
+    pub height: u32,
+    /**
+     * the (x, y) of every mine, in row-major order
+     */
+    pub mines: Vec<(u32, u32)>,
+}
+
+/**
+ * Encodes a mine layout as a share code: the width and the height, then the
+ * lengths of the alternating runs of zones without and with mines, in
+ * row-major order and starting with a run without mines (which may be
+ * empty), each as an unsigned LEB128 number, followed by the low 2 bytes of
+ * the 64-bit FNV-1a hash of everything before them, little-endian; all of
+ * it in URL-safe base64 without padding.
+ * `mines` holds, in row-major order, whether each position has a mine.
+ */
+pub fn encode(width: u32, height: u32, mines: impl IntoIterator<Item = bool>) -> String {
+    let mut bytes = Vec::new();
+    write_number(&mut bytes, width as u64);
+    write_number(&mut bytes, height as u64);
+    let mut run_of_mines = false;
+    let mut run = 0;
+    for has_mine in mines {
+        if has_mine != run_of_mines {
+            write_number(&mut bytes, run);
+            run_of_mines = has_mine;
+            run = 0;
+        }
+        run += 1;
+    }
+    write_number(&mut bytes, run);
+    let checksum = checksum(&bytes) as u16;
+    bytes.extend_from_slice(&checksum.to_le_bytes());
+    to_base64(&bytes)
+}
+
+/**
+ * Decodes a share code produced by `encode`. Surrounding whitespace is ignored.
+ */
+pub fn decode(code: &str) -> Result<SharedBoard, ShareCodeError> {
+    let mut bytes = from_base64(code.trim())?;
+    if bytes.len() < CHECKSUM_LEN {
+        return Err(ShareCodeError::ChecksumMismatch);
+    }
+    let stored = bytes.split_off(bytes.len() - CHECKSUM_LEN);
+    if checksum(&bytes) as u16 != u16::from_le_bytes([stored[0], stored[1]]) {
+        return Err(ShareCodeError::ChecksumMismatch);
+    }
+    let mut bytes = bytes.into_iter();
+    let mut read_u32 = || {
+        read_number(&mut bytes)
+            .and_then(|number| u32::try_from(number).ok())
+            .ok_or(ShareCodeError::Malformed)
+    };
+    let (width, height) = (read_u32()?, read_u32()?);
+    let num_zones = width as u64 * height as u64;
+    if num_zones > MAX_ZONES {
+        return Err(ShareCodeError::TooLarge { width, height });
+    }
+    let mut mines = Vec::new();
+    let mut covered = 0;
+    let mut run_of_mines = false;
+    while covered < num_zones {
+        let run = read_number(&mut bytes).ok_or(ShareCodeError::Malformed)?;
+        if run > num_zones - covered {
+            return Err(ShareCodeError::Malformed);
+        }
+        if run_of_mines {
+            mines.extend(
+                (covered..covered + run)
+                    .map(|index| ((index % width as u64) as u32, (index / width as u64) as u32)),
+            );
+        }
+        covered += run;
+        run_of_mines = !run_of_mines;
+    }
+    if bytes.next().is_some() {
+        return Err(ShareCodeError::Malformed);
+    }
+    Ok(SharedBoard {
+        width,
+        height,
+        mines,
+    })
+}
+
+/**
+ * Appends the given number as unsigned LEB128: 7 bits to a byte, least
+ * significant first, with the high bit set on every byte but the last
+ */
+fn write_number(bytes: &mut Vec<u8>, mut number: u64) {
+    while number >= 0x80 {
+        bytes.push(number as u8 | 0x80);
+        number >>= 7;
+    }
+    bytes.push(number as u8);
+}
+
+/**
+ * Reads a number written by `write_number`,
+ * or None if the bytes end first or the number doesn't fit in 64 bits
+ */
+fn read_number(bytes: &mut impl Iterator<Item = u8>) -> Option<u64> {
+    let mut number = 0;
+    for shift in (0..64).step_by(7) {
+        let byte = bytes.next()?;
+        let bits = (byte & 0x7f) as u64;
+        if bits << shift >> shift != bits {
+            return None;
+        }
+        number |= bits << shift;
+        if byte & 0x80 == 0 {
+            return Some(number);
+        }
+    }
+    None
+}
+
+fn to_base64(bytes: &[u8]) -> String {
+    let mut code = String::with_capacity((bytes.len() * 4).div_ceil(3));
+    for chunk in bytes.chunks(3) {
+        let bits = chunk.iter().enumerate().fold(0u32, |bits, (i, &byte)| {
+            bits | (byte as u32) << (16 - 8 * i)
+        });
+        for i in 0..=chunk.len() {
+            code.push(ALPHABET[(bits >> (18 - 6 * i) & 0x3f) as usize] as char);
+        }
+    }
+    code
+}
+
+fn from_base64(code: &str) -> Result<Vec<u8>, ShareCodeError> {
+    let mut bytes = Vec::with_capacity(code.len() * 3 / 4);
+    let mut bits = 0u32;
+    let mut num_bits = 0;
+    for (index, found) in code.chars().enumerate() {
+        let value = ALPHABET
+            .iter()
+            .position(|&symbol| symbol as char == found)
+            .ok_or(ShareCodeError::InvalidChar { index, found })?;
+        bits = bits << 6 | value as u32;
+        num_bits += 6;
+        if num_bits >= 8 {
+            num_bits -= 8;
+            bytes.push((bits >> num_bits) as u8);
+            bits &= (1 << num_bits) - 1;
+        }
+    }
+    // the bits left over pad out the last character, so they are all 0
+    // unless it was mistyped, and there are never 6 of them
+    if bits != 0 || num_bits >= 6 {
+        return Err(ShareCodeError::ChecksumMismatch);
+    }
+    Ok(bytes)
+}
@@ -0,0 +1,220 @@
+use crate::controller::{Action, GameState, MinesweeperController};
+use crate::model::{CreationError, FieldBuilder, PlayerView, ZoneState};
+use alloc::vec::Vec;
+use rand::{Rng, SeedableRng};
+use rand_chacha::ChaCha8Rng;
+use std::time::{Duration, Instant};
+
+/**
+ * Decides the moves of a simulated player (see `simulate`), seeing nothing
+ * but what a player would
+ */
+pub trait Strategy {
+    /**
+     * The next move to make on the board seen in the given view,
+     * or None to give up the game
+     */
+    fn next_move(&mut self, view: &PlayerView) -> Option<(Action, u32, u32)>;
+
+    /**
+     * Called before each game, with the seed of its board, so that a
+     * strategy making random choices can make the same ones every run
+     */
+    fn new_game(&mut self, _seed: u64) {}
+}
+
+/**
+ * Reveals hidden, unflagged zones at random, until the game is over
+ */
+pub struct RandomStrategy {
+    rng: ChaCha8Rng,
+}
+
+impl RandomStrategy {
+    pub fn new() -> Self {
+        RandomStrategy {
+            rng: ChaCha8Rng::seed_from_u64(0),
+        }
+    }
+}
+
+impl Default for RandomStrategy {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Strategy for RandomStrategy {
+    fn next_move(&mut self, view: &PlayerView) -> Option<(Action, u32, u32)> {
+        let hidden: Vec<_> = view
+            .zones()
+            .filter(|(_, state)| matches!(state, ZoneState::Hidden { flagged: false, .. }))
+            .map(|(position, _)| position)
+            .collect();
+        if hidden.is_empty() {
+            return None;
+        }
+        let (x, y) = hidden[self.rng.gen_range(0, hidden.len())];
+        Some((Action::Reveal, x, y))
+    }
+
+    fn new_game(&mut self, seed: u64) {
+        self.rng = ChaCha8Rng::seed_from_u64(seed);
+    }
+}
+
+/**
+ * Reveals the zone in the middle of the board (or the first zone, if the
+ * middle is masked out of it), then gives up, for measuring how often
+ * a game is won by its first click alone
+ */
+pub struct FirstClickStrategy;
+
+impl Strategy for FirstClickStrategy {
+    fn next_move(&mut self, view: &PlayerView) -> Option<(Action, u32, u32)> {
+        let first_click = view.zones().all(|(_, state)| match state {
+            ZoneState::Hidden { .. } => true,
+            ZoneState::Revealed { .. } | ZoneState::RevealedMine => false,
+        });
+        if !first_click {
+            return None;
+        }
+        let (x, y) = (view.width() / 2, view.height() / 2);
+        match view.state_at(x, y) {
+            Some(_) => Some((Action::Reveal, x, y)),
+            None => view
+                .zones()
+                .next()
+                .map(|((x, y), _)| (Action::Reveal, x, y)),
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+/**
+ * How the games of a simulation are set up (see `simulate`)
+ * `board` builds the board of every game; its seed is replaced by
+ *     `first_seed` for the first game, `first_seed + 1` for the second
+ *     and so on, so that a simulation can be run again move for move
+ * `max_moves` is how many moves a game can take before it counts as
+ *     stalled, so that a strategy going around in circles still finishes
+ */
+pub struct SimulationConfig {
+    pub board: FieldBuilder,
+    pub first_seed: u64,
+    pub max_moves: u32,
+}
+
+impl Default for SimulationConfig {
+    /**
+     * The default board of `Field::builder`, from seed 0 and with up to 10000 moves per game
+     */
+    fn default() -> Self {
+        SimulationConfig {
+            board: FieldBuilder::default(),
+            first_seed: 0,
+            max_moves: 10_000,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default, PartialEq)]
+/**
+ * What happened over the games of a simulation (see `simulate`)
+ * `detonated`, `gave_up`, `illegal_moves` and `stalled` count the games
+ *     lost by revealing a mine, by the strategy giving up, by the strategy
+ *     making a move that isn't allowed, and by running out of moves
+ * `total_3bv` and `total_moves` add up the 3BV of every board
+ *     (see `MinesweeperModel::board_3bv`) and the moves of every game
+ * `total_time` adds up how long the games took to play. It is the only
+ *     part of a report which differs between runs of the same simulation.
+ */
+pub struct SimulationReport {
+    pub games: u32,
+    pub wins: u32,
+    pub detonated: u32,
+    pub gave_up: u32,
+    pub illegal_moves: u32,
+    pub stalled: u32,
+    pub total_3bv: u64,
+    pub total_moves: u64,
+    pub total_time: Duration,
+}
+
+impl SimulationReport {
+    /**
+     * The fraction of the games which were won, or 0 if there were none
+     */
+    pub fn win_rate(&self) -> f64 {
+        self.average(self.wins as f64)
+    }
+
+    pub fn average_3bv(&self) -> f64 {
+        self.average(self.total_3bv as f64)
+    }
+
+    pub fn average_moves(&self) -> f64 {
+        self.average(self.total_moves as f64)
+    }
+
+    pub fn average_time(&self) -> Duration {
+        self.total_time.checked_div(self.games).unwrap_or_default()
+    }
+
+    fn average(&self, total: f64) -> f64 {
+        if self.games == 0 {
+            0.0
+        } else {
+            total / self.games as f64
+        }
+    }
+}
+
+/**
+ * Plays `games` games with the given strategy, without anybody watching,
+ * on boards set up as the given config says, and sums up how they went.
+ * Fails if the config's board can't be built.
+ */
+pub fn simulate(
+    config: &SimulationConfig,
+    strategy: &mut dyn Strategy,
+    games: usize,
+) -> Result<SimulationReport, CreationError> {
+    let mut report = SimulationReport::default();
+    for game in 0..games {
+        let seed = config.first_seed.wrapping_add(game as u64);
+        let model = config.board.clone().seed(seed).build()?;
+        strategy.new_game(seed);
+        let started_at = Instant::now();
+        let mut controller = MinesweeperController::new(model);
+        let mut moves = 0;
+        while controller.can_keep_playing() {
+            if moves == config.max_moves {
+                report.stalled += 1;
+                break;
+            }
+            let (action, x, y) = match strategy.next_move(&controller.model().player_view()) {
+                Some(next_move) => next_move,
+                None => {
+                    report.gave_up += 1;
+                    break;
+                }
+            };
+            moves += 1;
+            if controller.apply(action, x, y).is_err() {
+                report.illegal_moves += 1;
+                break;
+            }
+        }
+        match controller.state() {
+            GameState::Won => report.wins += 1,
+            GameState::Lost { .. } => report.detonated += 1,
+            GameState::InProgress => {}
+        }
+        report.games += 1;
+        report.total_3bv += controller.model().board_3bv() as u64;
+        report.total_moves += moves as u64;
+        report.total_time += started_at.elapsed();
+    }
+    Ok(report)
+}
@@ -0,0 +1,301 @@
+use crate::model::{MinesweeperModel, PlayerView, PositionSet, ZoneState};
+use alloc::collections::BTreeMap;
+use alloc::vec::Vec;
+
+#[derive(Debug, Clone, Default, Eq, PartialEq)]
+/**
+ * Everything that can be logically deduced about the hidden zones of a
+ * MinesweeperModel, using only the information visible to the player.
+ * Flags placed by the player are not trusted, since they may be wrong.
+ */
+pub struct Deductions {
+    /**
+     * hidden positions which certainly do not contain a mine
+     */
+    pub safe: PositionSet,
+    /**
+     * hidden positions which certainly contain a mine
+     */
+    pub mines: PositionSet,
+}
+
+impl Deductions {
+    /**
+     * true if nothing could be deduced about any hidden position
+     */
+    pub fn is_empty(&self) -> bool {
+        self.safe.is_empty() && self.mines.is_empty()
+    }
+}
+
+/**
+ * A revealed, numbered zone's remaining requirement:
+ * exactly `mines` of the positions in `cells` contain a mine
+ */
+struct Requirement {
+    cells: PositionSet,
+    mines: usize,
+}
+
+/**
+ * Repeatedly applies the following rules until nothing new can be deduced:
+ *  - a number whose remaining mines are zero makes its undecided neighbors safe
+ *  - a number whose remaining mines equal its undecided neighbors makes them all mines
+ *  - if one number's undecided neighbors are a subset of another's, the
+ *    difference between them must hold the difference of their remaining mines
+ *  - the total number of mines left decides everything if it is zero,
+ *    or equal to the number of undecided positions
+ *
+ * Nothing is deduced on a model whose zones can hold several mines, on one
+ * with lying counts (see `MinesweeperModel::displayed_count_at`), or on one
+ * with anti-mines, rather than deducing something wrong.
+ * Mines shown to the player from the start (see `MinesweeperModel::expose_mines`)
+ * are among the deduced mines from the outset.
+ */
+pub fn deduce(model: &MinesweeperModel) -> Deductions {
+    deduce_from_view(&model.player_view())
+}
+
+/**
+ * `deduce`, from nothing but a PlayerView
+ */
+pub fn deduce_from_view(view: &PlayerView) -> Deductions {
+    let mut deductions = Deductions::default();
+    if view.max_mines_per_zone() > 1 || view.has_liars() || view.has_anti_mines() {
+        // none of the rules hold when a zone can hold several mines,
+        // or when the counts don't simply count mines
+        return deductions;
+    }
+    deductions.mines.extend(positions(view).filter(|&(x, y)| {
+        matches!(
+            view.state_at(x, y),
+            Some(ZoneState::Hidden { exposed: true, .. })
+        )
+    }));
+    let revealed_mines = revealed_mines(view);
+    loop {
+        let requirements = requirements(view, &deductions);
+        let mut progress = false;
+        for req in &requirements {
+            progress |= settle(&mut deductions, &req.cells, req.mines);
+        }
+        if !progress {
+            for a in &requirements {
+                for b in &requirements {
+                    if a.cells.len() < b.cells.len() && a.cells.is_subset(&b.cells) {
+                        let difference: PositionSet = b
+                            .cells
+                            .iter()
+                            .filter(|pos| !a.cells.contains(pos))
+                            .copied()
+                            .collect();
+                        progress |= settle(
+                            &mut deductions,
+                            &difference,
+                            b.mines.saturating_sub(a.mines),
+                        );
+                    }
+                }
+            }
+        }
+        if !progress {
+            let undecided: PositionSet = positions(view)
+                .filter(|&(x, y)| is_hidden(view, x, y))
+                .filter(|pos| !deductions.safe.contains(pos) && !deductions.mines.contains(pos))
+                .collect();
+            let mines_left =
+                (view.num_mines() as usize).saturating_sub(revealed_mines + deductions.mines.len());
+            progress |= settle(&mut deductions, &undecided, mines_left);
+        }
+        if !progress {
+            return deductions;
+        }
+    }
+}
+
+/**
+ * returns true if every mine-free position of the given model could be revealed
+ * by revealing `first_click` and then only ever revealing positions deduced to
+ * be safe by `deduce`, i.e. without any guessing.
+ * The given model itself is left untouched.
+ */
+pub fn is_solvable(model: &MinesweeperModel, first_click: (u32, u32)) -> bool {
+    let mut model = model.clone();
+    // flags can't be trusted, and would stand in the way of revealing
+    model.clear_flags();
+    if model.reveal_at(first_click.0, first_click.1) != Ok(false) {
+        return false;
+    }
+    loop {
+        let safe = deduce(&model).safe;
+        if safe.is_empty() {
+            break;
+        }
+        for (x, y) in safe {
+            model.reveal_at(x, y).unwrap();
+        }
+    }
+    let solved = model
+        .active_positions()
+        .all(|(x, y)| model.is_revealed_at(x, y).unwrap() || model.has_mine_at(x, y).unwrap());
+    solved
+}
+
+/**
+ * Estimates, for every hidden position, the probability that it contains a mine.
+ * Deduced positions are certain (0.0 or 1.0). For an undecided position next
+ * to revealed numbers, the estimate is the most pessimistic of the ratios of
+ * remaining mines to undecided neighbors among those numbers. Every other
+ * undecided position is estimated from the density of the mines left.
+ * Nothing is estimated on a model with lying counts or anti-mines.
+ */
+pub fn mine_probabilities(model: &MinesweeperModel) -> BTreeMap<(u32, u32), f64> {
+    mine_probabilities_from_view(&model.player_view())
+}
+
+/**
+ * `mine_probabilities`, from nothing but a PlayerView
+ */
+pub fn mine_probabilities_from_view(view: &PlayerView) -> BTreeMap<(u32, u32), f64> {
+    if view.has_liars() || view.has_anti_mines() {
+        return BTreeMap::new();
+    }
+    let deductions = deduce_from_view(view);
+    let requirements = requirements(view, &deductions);
+    let hidden: Vec<_> = view.hidden_positions().collect();
+    let revealed_mines = revealed_mines(view);
+    let undecided = hidden.len() - deductions.safe.len() - deductions.mines.len();
+    let mines_left =
+        (view.num_mines() as usize).saturating_sub(revealed_mines + deductions.mines.len());
+    let density = if undecided == 0 {
+        0.0
+    } else {
+        mines_left as f64 / undecided as f64
+    };
+    hidden
+        .into_iter()
+        .map(|pos| {
+            let probability = if deductions.safe.contains(&pos) {
+                0.0
+            } else if deductions.mines.contains(&pos) {
+                1.0
+            } else {
+                requirements
+                    .iter()
+                    .filter(|req| req.cells.contains(&pos))
+                    .map(|req| req.mines as f64 / req.cells.len() as f64)
+                    .fold(None, |max: Option<f64>, p| {
+                        Some(max.map_or(p, |m| m.max(p)))
+                    })
+                    .unwrap_or(density)
+            };
+            (pos, probability)
+        })
+        .collect()
+}
+
+/**
+ * The hidden, unflagged position least likely to contain a mine according to
+ * `mine_probabilities`, preferring lower x, then lower y, among equally likely
+ * positions. None if there are no hidden, unflagged positions,
+ * or if the model has lying counts or anti-mines.
+ */
+pub fn best_guess(model: &MinesweeperModel) -> Option<(u32, u32)> {
+    best_guess_from_view(&model.player_view())
+}
+
+/**
+ * `best_guess`, from nothing but a PlayerView
+ */
+pub fn best_guess_from_view(view: &PlayerView) -> Option<(u32, u32)> {
+    let probabilities = mine_probabilities_from_view(view);
+    positions(view)
+        .filter(|&(x, y)| {
+            !matches!(
+                view.state_at(x, y),
+                Some(ZoneState::Hidden { flagged: true, .. })
+            )
+        })
+        .filter_map(|pos| probabilities.get(&pos).map(|&p| (pos, p)))
+        .fold(
+            None,
+            |best: Option<((u32, u32), f64)>, (pos, p)| match best {
+                Some((_, best_p)) if best_p <= p => best,
+                _ => Some((pos, p)),
+            },
+        )
+        .map(|(pos, _)| pos)
+}
+
+/**
+ * If the given number of mines among the given undecided cells
+ * determines each of them, then records that.
+ * returns true if anything new was recorded.
+ */
+fn settle(deductions: &mut Deductions, cells: &PositionSet, mines: usize) -> bool {
+    if cells.is_empty() {
+        false
+    } else if mines == 0 {
+        cells
+            .iter()
+            .fold(false, |new, &pos| deductions.safe.insert(pos) | new)
+    } else if mines == cells.len() {
+        cells
+            .iter()
+            .fold(false, |new, &pos| deductions.mines.insert(pos) | new)
+    } else {
+        false
+    }
+}
+
+/**
+ * The requirements of every revealed, numbered zone
+ * that still has undecided neighbors
+ */
+fn requirements(view: &PlayerView, deductions: &Deductions) -> Vec<Requirement> {
+    let mut requirements = Vec::new();
+    for (x, y) in positions(view) {
+        let adjacent = match view.state_at(x, y) {
+            Some(ZoneState::Revealed { adjacent }) => adjacent,
+            _ => continue,
+        };
+        let mut cells = PositionSet::new();
+        let mut known_mines = 0;
+        for (adj_x, adj_y) in view.adjacent_positions(x, y) {
+            match view.state_at(adj_x, adj_y) {
+                Some(ZoneState::RevealedMine) => known_mines += 1,
+                Some(ZoneState::Revealed { .. }) => (),
+                _ if deductions.mines.contains(&(adj_x, adj_y)) => known_mines += 1,
+                _ if !deductions.safe.contains(&(adj_x, adj_y)) => {
+                    cells.insert((adj_x, adj_y));
+                }
+                _ => (),
+            }
+        }
+        if !cells.is_empty() {
+            // counts are never negative without anti-mines
+            let mines = (adjacent.max(0) as usize).saturating_sub(known_mines);
+            requirements.push(Requirement { cells, mines });
+        }
+    }
+    requirements
+}
+
+fn revealed_mines(view: &PlayerView) -> usize {
+    positions(view)
+        .filter(|&(x, y)| view.state_at(x, y) == Some(ZoneState::RevealedMine))
+        .count()
+}
+
+fn is_hidden(view: &PlayerView, x: u32, y: u32) -> bool {
+    matches!(view.state_at(x, y), Some(ZoneState::Hidden { .. }))
+}
+
+/**
+ * Every zone of the view, x-major like `Field::active_positions`
+ */
+fn positions(view: &PlayerView) -> impl Iterator<Item = (u32, u32)> + '_ {
+    (0..view.width())
+        .flat_map(move |x| (0..view.height()).map(move |y| (x, y)))
+        .filter(move |&(x, y)| view.state_at(x, y).is_some())
+}
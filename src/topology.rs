@@ -0,0 +1,449 @@
+use alloc::sync::Arc;
+use alloc::vec;
+use alloc::vec::Vec;
+use core::fmt;
+
+#[derive(Debug, Copy, Clone, Default, Eq, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+/**
+ * The shape of the cells making up a Field, which decides their neighbors
+ * `Square` cells have up to 8 neighbors, 4 of them diagonal
+ * `Hex` cells have up to 6 neighbors, using axial coordinates: (x, y) borders
+ *     (x ± 1, y), (x, y ± 1), (x + 1, y - 1) and (x - 1, y + 1), so that each
+ *     row sits half a cell further right than the row above it
+ */
+pub enum Tiling {
+    #[default]
+    Square,
+    Hex,
+}
+
+#[derive(Debug, Clone, Eq, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+/**
+ * A set of relative (x, y) offsets deciding which positions are adjacent to
+ * each other, for counting adjacent mines and for cascading alike
+ * (see `Field::new_with_kernel`)
+ */
+pub struct Kernel {
+    offsets: Vec<(i32, i32)>,
+}
+
+impl Kernel {
+    /**
+     * A Kernel made of the given offsets. Adjacency goes both ways, so the
+     * opposite of every offset is included too. (0, 0) and duplicates are
+     * left out.
+     */
+    pub fn new(offsets: impl IntoIterator<Item = (i32, i32)>) -> Self {
+        let mut all = Vec::new();
+        for (dx, dy) in offsets {
+            for &offset in &[(dx, dy), (-dx, -dy)] {
+                if offset != (0, 0) && !all.contains(&offset) {
+                    all.push(offset);
+                }
+            }
+        }
+        Kernel { offsets: all }
+    }
+
+    /**
+     * The 8 surrounding positions, as in classic minesweeper
+     */
+    pub fn classic() -> Self {
+        Self::within(1)
+    }
+
+    /**
+     * The 4 positions directly above, below, left and right
+     */
+    pub fn orthogonal() -> Self {
+        Self::new(vec![(0, 1), (1, 0)])
+    }
+
+    /**
+     * The 8 positions a knight's move away in chess
+     */
+    pub fn knight() -> Self {
+        Self::new(vec![(1, 2), (2, 1), (1, -2), (2, -1)])
+    }
+
+    /**
+     * The 24 positions at most two steps away in any direction (diagonals included)
+     */
+    pub fn radius_two() -> Self {
+        Self::within(2)
+    }
+
+    /**
+     * The offsets making up this Kernel
+     */
+    pub fn offsets(&self) -> &[(i32, i32)] {
+        &self.offsets
+    }
+
+    fn within(radius: i32) -> Self {
+        Self::new((-radius..=radius).flat_map(|dx| (-radius..=radius).map(move |dy| (dx, dy))))
+    }
+}
+
+/**
+ * Decides which positions of a Field are adjacent to each other, for
+ * counting adjacent mines as well as for cascading reveals
+ * (see `Field::new_with_topology`).
+ * Every method produces in-bounds positions only, each of them once, never
+ * including the given position itself. Adjacency must go both ways: if b is
+ * one of a's neighbors, then a must be one of b's.
+ */
+pub trait Topology: fmt::Debug + Send + Sync {
+    /**
+     * The positions adjacent to (x, y) on a board of the given dimensions
+     */
+    fn neighbors(&self, x: u32, y: u32, width: u32, height: u32) -> Vec<(u32, u32)>;
+
+    /**
+     * `neighbors`, leaving out diagonal adjacencies for topologies that have any
+     */
+    fn orthogonal_neighbors(&self, x: u32, y: u32, width: u32, height: u32) -> Vec<(u32, u32)> {
+        self.neighbors(x, y, width, height)
+    }
+
+    /**
+     * The positions a cascading reveal spreads to from (x, y),
+     * a revealed position without adjacent mines
+     */
+    fn cascade_neighbors(&self, x: u32, y: u32, width: u32, height: u32) -> Vec<(u32, u32)> {
+        self.neighbors(x, y, width, height)
+    }
+
+    /**
+     * For topologies whose neighbors are simply the positions at a fixed set
+     * of offsets, cut off at the edges of the board, those offsets, in the
+     * order `neighbors` produces them. Lets `Field::adjacent_iter` find
+     * neighbors without allocating. None otherwise.
+     */
+    fn neighbor_offsets(&self) -> Option<&[(i64, i64)]> {
+        None
+    }
+
+    /**
+     * The offsets behind `orthogonal_neighbors`, like `neighbor_offsets`
+     */
+    fn orthogonal_offsets(&self) -> Option<&[(i64, i64)]> {
+        self.neighbor_offsets()
+    }
+
+    /**
+     * The offsets behind `cascade_neighbors`, like `neighbor_offsets`
+     */
+    fn cascade_offsets(&self) -> Option<&[(i64, i64)]> {
+        self.neighbor_offsets()
+    }
+
+    /**
+     * The shape of the cells, for renderers
+     */
+    fn tiling(&self) -> Tiling {
+        Tiling::Square
+    }
+
+    /**
+     * true if opposite edges are adjacent, for renderers
+     */
+    fn wraps(&self) -> bool {
+        false
+    }
+
+    /**
+     * The number of layers the board is made of (see `Layered`)
+     */
+    fn depth(&self) -> u32 {
+        1
+    }
+
+    /**
+     * The built-in topology this is, if any.
+     * Only built-in topologies can be saved along with a Field.
+     */
+    fn builtin(&self) -> Option<BuiltinTopology> {
+        None
+    }
+}
+
+// the positions on the near side come first, so that cascades visit them
+// in the same order as they always have
+const ORTHOGONAL: [(i64, i64); 4] = [(0, -1), (-1, 0), (1, 0), (0, 1)];
+// the orthogonal positions, followed by the diagonal ones
+const SURROUNDING: [(i64, i64); 8] = [
+    (0, -1),
+    (-1, 0),
+    (1, 0),
+    (0, 1),
+    (-1, -1),
+    (-1, 1),
+    (1, -1),
+    (1, 1),
+];
+// the orthogonal positions, followed by the two diagonals of `Tiling::Hex`
+const HEXAGONAL: [(i64, i64); 6] = [(0, -1), (-1, 0), (1, 0), (0, 1), (1, -1), (-1, 1)];
+
+/**
+ * The positions at the given offsets from (x, y) which are within the given
+ * dimensions, with coordinates continuing from the opposite edge if `wrap`.
+ */
+fn offset_positions(
+    x: u32,
+    y: u32,
+    width: u32,
+    height: u32,
+    offsets: impl IntoIterator<Item = (i64, i64)>,
+    wrap: bool,
+) -> Vec<(u32, u32)> {
+    let (width, height) = (width as i64, height as i64);
+    let mut positions = Vec::with_capacity(8);
+    for (dx, dy) in offsets {
+        let (mut adj_x, mut adj_y) = (x as i64 + dx, y as i64 + dy);
+        if wrap {
+            adj_x = adj_x.rem_euclid(width);
+            adj_y = adj_y.rem_euclid(height);
+        }
+        if adj_x < 0 || adj_y < 0 || adj_x >= width || adj_y >= height {
+            continue;
+        }
+        let adj = (adj_x as u32, adj_y as u32);
+        // on tiny wrapping boards, several offsets can lead to the same position
+        if adj != (x, y) && !positions.contains(&adj) {
+            positions.push(adj);
+        }
+    }
+    positions
+}
+
+#[derive(Debug, Copy, Clone, Default, Eq, PartialEq)]
+/**
+ * Classic minesweeper: the 8 surrounding positions are adjacent.
+ * This is the topology of every Field unless stated otherwise.
+ */
+pub struct Standard8;
+
+impl Topology for Standard8 {
+    fn neighbors(&self, x: u32, y: u32, width: u32, height: u32) -> Vec<(u32, u32)> {
+        offset_positions(x, y, width, height, SURROUNDING.iter().copied(), false)
+    }
+
+    fn orthogonal_neighbors(&self, x: u32, y: u32, width: u32, height: u32) -> Vec<(u32, u32)> {
+        offset_positions(x, y, width, height, ORTHOGONAL.iter().copied(), false)
+    }
+
+    fn neighbor_offsets(&self) -> Option<&[(i64, i64)]> {
+        Some(&SURROUNDING)
+    }
+
+    fn orthogonal_offsets(&self) -> Option<&[(i64, i64)]> {
+        Some(&ORTHOGONAL)
+    }
+
+    fn builtin(&self) -> Option<BuiltinTopology> {
+        Some(BuiltinTopology::Standard8)
+    }
+}
+
+#[derive(Debug, Copy, Clone, Default, Eq, PartialEq)]
+/**
+ * Only the 4 positions directly above, below, left and right are adjacent
+ */
+pub struct Orthogonal4;
+
+impl Topology for Orthogonal4 {
+    fn neighbors(&self, x: u32, y: u32, width: u32, height: u32) -> Vec<(u32, u32)> {
+        offset_positions(x, y, width, height, ORTHOGONAL.iter().copied(), false)
+    }
+
+    fn neighbor_offsets(&self) -> Option<&[(i64, i64)]> {
+        Some(&ORTHOGONAL)
+    }
+
+    fn builtin(&self) -> Option<BuiltinTopology> {
+        Some(BuiltinTopology::Orthogonal4)
+    }
+}
+
+#[derive(Debug, Copy, Clone, Default, Eq, PartialEq)]
+/**
+ * Like `Standard8`, but on a torus: the left edge borders the right edge
+ * and the top edge borders the bottom, so no position is on an edge
+ */
+pub struct Torus8;
+
+impl Topology for Torus8 {
+    fn neighbors(&self, x: u32, y: u32, width: u32, height: u32) -> Vec<(u32, u32)> {
+        offset_positions(x, y, width, height, SURROUNDING.iter().copied(), true)
+    }
+
+    fn orthogonal_neighbors(&self, x: u32, y: u32, width: u32, height: u32) -> Vec<(u32, u32)> {
+        offset_positions(x, y, width, height, ORTHOGONAL.iter().copied(), true)
+    }
+
+    fn wraps(&self) -> bool {
+        true
+    }
+
+    fn builtin(&self) -> Option<BuiltinTopology> {
+        Some(BuiltinTopology::Torus8)
+    }
+}
+
+#[derive(Debug, Copy, Clone, Default, Eq, PartialEq)]
+/**
+ * Hexagonal cells (see `Tiling::Hex`), each with up to 6 neighbors
+ */
+pub struct Hex6;
+
+impl Topology for Hex6 {
+    fn neighbors(&self, x: u32, y: u32, width: u32, height: u32) -> Vec<(u32, u32)> {
+        offset_positions(x, y, width, height, HEXAGONAL.iter().copied(), false)
+    }
+
+    fn neighbor_offsets(&self) -> Option<&[(i64, i64)]> {
+        Some(&HEXAGONAL)
+    }
+
+    fn tiling(&self) -> Tiling {
+        Tiling::Hex
+    }
+
+    fn builtin(&self) -> Option<BuiltinTopology> {
+        Some(BuiltinTopology::Hex6)
+    }
+}
+
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+/**
+ * `depth` layers stacked on top of each other, laid out one after the other
+ * along the y axis (see `Field::new_layered`). Each position borders the
+ * positions around it in its own layer and in the layers directly above and
+ * below, for up to 26 in all.
+ */
+pub struct Layered {
+    pub depth: u32,
+}
+
+impl Layered {
+    fn positions(
+        &self,
+        x: u32,
+        y: u32,
+        width: u32,
+        height: u32,
+        diagonal: bool,
+    ) -> Vec<(u32, u32)> {
+        let depth = self.depth.max(1);
+        let layer_height = (height / depth) as i64;
+        let (x, layer_y, z) = (x as i64, y as i64 % layer_height, y as i64 / layer_height);
+        let mut positions = Vec::with_capacity(26);
+        for dz in -1..=1i64 {
+            for dy in -1..=1i64 {
+                for dx in -1..=1i64 {
+                    let steps = dx.abs() + dy.abs() + dz.abs();
+                    if steps == 0 || (!diagonal && steps > 1) {
+                        continue;
+                    }
+                    let (adj_x, adj_y, adj_z) = (x + dx, layer_y + dy, z + dz);
+                    if adj_x < 0
+                        || adj_y < 0
+                        || adj_z < 0
+                        || adj_x >= width as i64
+                        || adj_y >= layer_height
+                        || adj_z >= depth as i64
+                    {
+                        continue;
+                    }
+                    positions.push((adj_x as u32, (adj_z * layer_height + adj_y) as u32));
+                }
+            }
+        }
+        positions
+    }
+}
+
+impl Topology for Layered {
+    fn neighbors(&self, x: u32, y: u32, width: u32, height: u32) -> Vec<(u32, u32)> {
+        self.positions(x, y, width, height, true)
+    }
+
+    fn orthogonal_neighbors(&self, x: u32, y: u32, width: u32, height: u32) -> Vec<(u32, u32)> {
+        self.positions(x, y, width, height, false)
+    }
+
+    fn depth(&self) -> u32 {
+        self.depth.max(1)
+    }
+
+    fn builtin(&self) -> Option<BuiltinTopology> {
+        Some(BuiltinTopology::Layered { depth: self.depth })
+    }
+}
+
+impl Topology for Kernel {
+    fn neighbors(&self, x: u32, y: u32, width: u32, height: u32) -> Vec<(u32, u32)> {
+        let offsets = self.offsets.iter().map(|&(dx, dy)| (dx as i64, dy as i64));
+        offset_positions(x, y, width, height, offsets, false)
+    }
+
+    fn builtin(&self) -> Option<BuiltinTopology> {
+        Some(BuiltinTopology::Kernel(self.clone()))
+    }
+}
+
+#[derive(Debug, Clone, Eq, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+/**
+ * Names every built-in topology, so that it can be saved and restored
+ */
+pub enum BuiltinTopology {
+    Standard8,
+    Orthogonal4,
+    Torus8,
+    Hex6,
+    Layered { depth: u32 },
+    Kernel(Kernel),
+}
+
+impl BuiltinTopology {
+    pub fn into_topology(self) -> Arc<dyn Topology> {
+        match self {
+            BuiltinTopology::Standard8 => Arc::new(Standard8),
+            BuiltinTopology::Orthogonal4 => Arc::new(Orthogonal4),
+            BuiltinTopology::Torus8 => Arc::new(Torus8),
+            BuiltinTopology::Hex6 => Arc::new(Hex6),
+            BuiltinTopology::Layered { depth } => Arc::new(Layered { depth }),
+            BuiltinTopology::Kernel(kernel) => Arc::new(kernel),
+        }
+    }
+}
+
+/**
+ * Saves the topology of a Field as the BuiltinTopology it is
+ */
+#[cfg(feature = "serde")]
+pub mod serde_topology {
+    use super::{BuiltinTopology, Topology};
+    use alloc::sync::Arc;
+    use serde::{ser::Error, Deserialize, Deserializer, Serialize, Serializer};
+
+    pub fn serialize<S: Serializer>(
+        topology: &Arc<dyn Topology>,
+        serializer: S,
+    ) -> Result<S::Ok, S::Error> {
+        topology
+            .builtin()
+            .ok_or_else(|| S::Error::custom("only built-in topologies can be saved"))?
+            .serialize(serializer)
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(
+        deserializer: D,
+    ) -> Result<Arc<dyn Topology>, D::Error> {
+        Ok(BuiltinTopology::deserialize(deserializer)?.into_topology())
+    }
+}
@@ -0,0 +1,62 @@
+use minesweeper::ascii::AsciiError;
+use minesweeper::model::Field;
+
+#[test]
+fn reads_a_drawing() {
+    let field: Field = "....\n.*..\n".parse().unwrap();
+    assert_eq!((field.width(), field.height()), (4, 2));
+    assert_eq!(field.num_mines(), 1);
+    assert_eq!(field.has_mine_at(1, 1), Some(true));
+    assert_eq!(field.to_string(), "....\n.*..\n");
+    assert_eq!("....\n.*..".parse::<Field>().unwrap(), field);
+}
+
+#[test]
+fn round_trips() {
+    for seed in 0..50 {
+        let mut field = Field::new_with_seed(8, 6, 10, seed).unwrap();
+        let (x, y) = (seed as u32 % 8, seed as u32 / 8 % 6);
+        if !field.has_mine_at(x, y).unwrap() {
+            field.reveal_area(x, y).unwrap();
+        }
+        for (x, y) in field.active_positions().collect::<Vec<_>>() {
+            if (x * 3 + y) % 4 == 0 && !field.is_revealed_at(x, y).unwrap() {
+                field.change_flag_at(x, y, true).unwrap();
+            }
+        }
+        let text = field.to_string();
+        let parsed: Field = text.parse().unwrap();
+        assert_eq!(parsed.to_string(), text, "seed {}", seed);
+        assert_eq!(parsed.num_revealed(), field.num_revealed());
+        assert_eq!(parsed.num_flagged(), field.num_flagged());
+    }
+}
+
+#[test]
+fn refuses_malformed_drawings() {
+    assert_eq!("".parse::<Field>(), Err(AsciiError::Empty));
+    assert_eq!(
+        "!view\n##\n##\n".parse::<Field>(),
+        Err(AsciiError::PlayerView)
+    );
+    assert_eq!(
+        "...\n.x.\n".parse::<Field>(),
+        Err(AsciiError::UnexpectedChar {
+            line: 2,
+            column: 2,
+            found: 'x',
+        })
+    );
+    assert_eq!(
+        "...\n...\n..\n".parse::<Field>(),
+        Err(AsciiError::RaggedRow {
+            line: 3,
+            expected: 3,
+            found: 2,
+        })
+    );
+    assert!(matches!(
+        "\n".parse::<Field>(),
+        Err(AsciiError::Creation(_))
+    ));
+}
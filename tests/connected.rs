@@ -0,0 +1,27 @@
+use minesweeper::model::Field;
+
+#[test]
+fn counts_zones_holding_several_mines_once() {
+    let field = Field::with_mine_counts(2, 2, vec![((0, 0), 3), ((1, 1), 3)]).unwrap();
+    assert!(field.safe_cells_connected());
+
+    let field = Field::with_mine_counts(3, 1, vec![((0, 0), 2)]).unwrap();
+    assert!(field.safe_cells_connected());
+
+    let field = Field::with_mine_counts(3, 1, vec![((1, 0), 2)]).unwrap();
+    assert!(!field.safe_cells_connected());
+}
+
+#[test]
+fn pending_boards_are_connected() {
+    let field = Field::builder()
+        .width(5)
+        .height(5)
+        .mines(4)
+        .seed(1)
+        .first_click_safe(true)
+        .build()
+        .unwrap();
+    assert!(field.is_placement_pending());
+    assert!(field.safe_cells_connected());
+}
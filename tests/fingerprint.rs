@@ -0,0 +1,49 @@
+use minesweeper::model::Field;
+
+fn fingerprint(width: u32, height: u32, mines: Vec<(u32, u32)>) -> u64 {
+    Field::with_mine_placements(width, height, mines)
+        .unwrap()
+        .layout_fingerprint()
+}
+
+/**
+ * Fingerprints are compared across versions and machines, so these values
+ * must never change
+ */
+#[test]
+fn pinned() {
+    assert_eq!(fingerprint(2, 2, vec![(1, 1)]), 0xbc95_2908_1cd3_95f5);
+    assert_eq!(
+        fingerprint(9, 9, vec![(0, 0), (4, 4), (8, 8)]),
+        0x63be_63be_4a99_bcd5
+    );
+    assert_eq!(
+        fingerprint(9, 9, vec![(0, 0), (4, 4)]),
+        0x652e_5147_41b7_07b5
+    );
+    assert_eq!(fingerprint(3, 1, vec![]), 0x67bd_6b43_893f_4077);
+}
+
+#[test]
+fn follows_the_layout_only() {
+    let mines = vec![(0, 0), (4, 4), (8, 8)];
+    let mut field = Field::with_mine_placements(9, 9, mines.clone()).unwrap();
+    let before = field.layout_fingerprint();
+    assert_eq!(fingerprint(9, 9, mines.into_iter().rev().collect()), before);
+
+    field.reveal_area(8, 0).unwrap();
+    field.change_flag_at(4, 4, true).unwrap();
+    assert_eq!(field.layout_fingerprint(), before);
+}
+
+#[test]
+fn tells_layouts_apart() {
+    let base = fingerprint(9, 9, vec![(0, 0), (4, 4), (8, 8)]);
+    assert_ne!(fingerprint(9, 9, vec![(0, 0), (4, 4)]), base);
+    assert_ne!(
+        fingerprint(9, 9, vec![(0, 0), (4, 4), (8, 8), (1, 0)]),
+        base
+    );
+    assert_ne!(fingerprint(9, 9, vec![(0, 0), (4, 4), (8, 7)]), base);
+    assert_ne!(fingerprint(3, 1, vec![]), fingerprint(1, 3, vec![]));
+}
@@ -0,0 +1,56 @@
+#![cfg(feature = "std")]
+
+use minesweeper::controller::MinesweeperController;
+use minesweeper::model::Field;
+
+/**
+ * A 3x3 board with mines above and below the middle zone
+ */
+fn game() -> MinesweeperController {
+    MinesweeperController::new(Field::with_mine_placements(3, 3, vec![(1, 0), (1, 2)]).unwrap())
+}
+
+#[test]
+fn new_game() {
+    assert_eq!(
+        game().state_json(),
+        r#"{"width":3,"height":3,"mines":2,"flags_remaining":2,"status":"playing","cells":[["hidden","hidden","hidden"],["hidden","hidden","hidden"],["hidden","hidden","hidden"]]}"#
+    );
+}
+
+#[test]
+fn game_in_progress() {
+    let mut game = game();
+    game.reveal_zone_at(0, 1).unwrap();
+    game.toggle_flag_at(1, 0).unwrap();
+    game.toggle_flag_at(2, 2).unwrap();
+    assert_eq!(
+        game.state_json(),
+        r#"{"width":3,"height":3,"mines":2,"flags_remaining":0,"status":"playing","cells":[["hidden","flag","hidden"],[2,"hidden","hidden"],["hidden","hidden","flag"]]}"#
+    );
+}
+
+#[test]
+fn lost_game() {
+    let mut game = game();
+    game.reveal_zone_at(0, 1).unwrap();
+    game.toggle_flag_at(1, 0).unwrap();
+    game.toggle_flag_at(2, 2).unwrap();
+    game.reveal_zone_at(1, 2).unwrap();
+    assert_eq!(
+        game.state_json(),
+        r#"{"width":3,"height":3,"mines":2,"flags_remaining":0,"status":"lost","cells":[["hidden","mine","hidden"],[2,"hidden","hidden"],["hidden","mine","flag"]]}"#
+    );
+}
+
+#[test]
+fn won_game() {
+    let mut game =
+        MinesweeperController::new(Field::with_mine_placements(3, 2, vec![(2, 0)]).unwrap());
+    game.reveal_zone_at(0, 0).unwrap();
+    game.toggle_flag_at(2, 0).unwrap();
+    assert_eq!(
+        game.state_json(),
+        r#"{"width":3,"height":2,"mines":1,"flags_remaining":0,"status":"won","cells":[[0,1,"flag"],[0,1,1]]}"#
+    );
+}
@@ -0,0 +1,65 @@
+use minesweeper::model::{ErrorKind, Field};
+
+// whether every position borders each of its neighbors back
+fn adjacency_is_mutual(field: &Field) -> bool {
+    field.active_positions().all(|(x, y)| {
+        field.adjacent_iter(x, y, true).all(|(adj_x, adj_y)| {
+            field
+                .adjacent_iter(adj_x, adj_y, true)
+                .any(|pos| pos == (x, y))
+        })
+    })
+}
+
+#[test]
+fn rotates_each_layer() {
+    let rotated = Field::new_layered(1, 1, 3, 1, 5).unwrap().rotated_90();
+    assert_eq!(
+        (rotated.width(), rotated.height(), rotated.depth()),
+        (1, 3, 3)
+    );
+    assert_eq!(rotated.check_invariants(), Ok(()));
+
+    for seed in 0..10 {
+        let field = Field::new_layered(4, 3, 2, 8, seed).unwrap();
+        let rotated = field.rotated_90();
+        assert_eq!((rotated.width(), rotated.layer_height()), (3, 4));
+        assert_eq!(rotated.check_invariants(), Ok(()));
+        assert!(adjacency_is_mutual(&rotated));
+        for z in 0..2 {
+            for y in 0..3 {
+                for x in 0..4 {
+                    let (from_x, from_y) = field.flatten_position(x, y, z).unwrap();
+                    let (to_x, to_y) = rotated.flatten_position(2 - y, x, z).unwrap();
+                    assert_eq!(
+                        rotated.has_mine_at(to_x, to_y),
+                        field.has_mine_at(from_x, from_y)
+                    );
+                }
+            }
+        }
+        let transposed = field.transposed();
+        assert_eq!(transposed.check_invariants(), Ok(()));
+        assert!(adjacency_is_mutual(&transposed));
+    }
+}
+
+#[test]
+fn crops_whole_layers_or_within_one() {
+    let field = Field::new_layered(3, 2, 2, 1, 5).unwrap();
+    let cropped = field.subfield(0, 0, 3, 1).unwrap();
+    assert_eq!((cropped.height(), cropped.depth()), (1, 1));
+    assert_eq!(cropped.check_invariants(), Ok(()));
+
+    let field = Field::new_layered(3, 2, 3, 4, 5).unwrap();
+    for &(y0, height, depth) in &[(0, 4, 2), (2, 4, 2), (0, 6, 3), (1, 1, 1), (2, 2, 1)] {
+        let cropped = field.subfield(0, y0, 3, height).unwrap();
+        assert_eq!(cropped.depth(), depth);
+        assert_eq!(cropped.check_invariants(), Ok(()));
+        assert!(adjacency_is_mutual(&cropped));
+    }
+    assert_eq!(
+        field.subfield(0, 1, 3, 2).err(),
+        Some(ErrorKind::SplitsLayers { layer_height: 2 })
+    );
+}
@@ -0,0 +1,137 @@
+#![cfg(feature = "std")]
+
+use minesweeper::model::{Field, InvariantViolation};
+use minesweeper::save::{self, SaveData, SaveError, VERSION};
+
+/**
+ * A 2x2 board with a mine at (1, 1) and (0, 0) revealed, as version 1 of
+ * the format saves it (see `save::write_save`)
+ */
+const FIXTURE: &[u8] = include_bytes!("fixtures/2x2.mswp");
+
+/**
+ * A board with some zones revealed and some flagged, chosen by the seed
+ */
+fn played(seed: u64) -> Field {
+    let mut field = Field::new_with_seed(9, 7, 12, seed).unwrap();
+    let (x, y) = (seed as u32 % 9, seed as u32 / 9 % 7);
+    if !field.has_mine_at(x, y).unwrap() {
+        field.reveal_area(x, y).unwrap();
+    }
+    for (x, y) in field.active_positions().collect::<Vec<_>>() {
+        if (x + y) % 3 == 0 && !field.is_revealed_at(x, y).unwrap() {
+            field.change_flag_at(x, y, true).unwrap();
+        }
+    }
+    field
+}
+
+fn save(field: &Field) -> Vec<u8> {
+    let mut bytes = Vec::new();
+    field.write_save(&mut bytes).unwrap();
+    bytes
+}
+
+#[test]
+fn reads_the_fixture() {
+    let field = Field::read_save(FIXTURE).unwrap();
+    assert_eq!((field.width(), field.height()), (2, 2));
+    assert_eq!(field.num_mines(), 1);
+    assert_eq!(field.has_mine_at(1, 1), Some(true));
+    assert_eq!(field.is_revealed_at(0, 0), Some(true));
+    assert_eq!(field.num_revealed(), 1);
+    assert_eq!(field.num_flagged(), 0);
+}
+
+#[test]
+fn rewrites_the_fixture_byte_for_byte() {
+    assert_eq!(save(&Field::read_save(FIXTURE).unwrap()), FIXTURE);
+    let mut field = Field::with_mine_placements(2, 2, vec![(1, 1)]).unwrap();
+    field.reveal_at(0, 0).unwrap();
+    assert_eq!(save(&field), FIXTURE);
+}
+
+#[test]
+fn round_trips() {
+    for seed in 0..50 {
+        let field = played(seed);
+        let restored = Field::read_save(&save(&field)[..]).unwrap();
+        assert_eq!(
+            restored.to_ascii(true),
+            field.to_ascii(true),
+            "seed {}",
+            seed
+        );
+        assert_eq!(restored.num_revealed(), field.num_revealed());
+        assert_eq!(restored.num_flagged(), field.num_flagged());
+        assert_eq!(save(&restored), save(&field));
+    }
+}
+
+#[test]
+fn refuses_other_versions() {
+    let mut bytes = FIXTURE.to_vec();
+    bytes[4] = VERSION + 1;
+    assert!(matches!(
+        Field::read_save(&bytes[..]),
+        Err(SaveError::UnsupportedVersion(version)) if version == VERSION + 1
+    ));
+}
+
+#[test]
+fn refuses_anything_else() {
+    assert!(matches!(
+        Field::read_save(&b"PK\x03\x04 not a save"[..]),
+        Err(SaveError::NotASave)
+    ));
+}
+
+#[test]
+fn refuses_truncated_saves() {
+    let bytes = save(&played(7));
+    for len in 0..bytes.len() {
+        assert!(
+            matches!(
+                Field::read_save(&bytes[..len]),
+                Err(SaveError::Truncated) | Err(SaveError::NotASave)
+            ),
+            "cut to {} bytes",
+            len
+        );
+    }
+}
+
+#[test]
+fn catches_corruption() {
+    let bytes = save(&played(3));
+    for index in 5..bytes.len() {
+        let mut corrupted = bytes.clone();
+        corrupted[index] ^= 0x10;
+        assert!(Field::read_save(&corrupted[..]).is_err(), "byte {}", index);
+    }
+    let mut corrupted = bytes;
+    corrupted[17] ^= 0x01;
+    assert!(matches!(
+        Field::read_save(&corrupted[..]),
+        Err(SaveError::ChecksumMismatch)
+    ));
+}
+
+#[test]
+fn refuses_boards_no_moves_lead_to() {
+    // (0, 0) has no mine, yet is both revealed and flagged
+    let forged = SaveData {
+        width: 2,
+        height: 2,
+        num_mines: 1,
+        mines: vec![0b1000],
+        revealed: vec![0b0001],
+        flagged: vec![0b0001],
+    };
+    let mut bytes = Vec::new();
+    save::write_save(&mut bytes, &forged).unwrap();
+    assert!(matches!(
+        Field::read_save(&bytes[..]),
+        Err(SaveError::Invalid(InvariantViolation::MalformedZone { .. }))
+    ));
+}
@@ -0,0 +1,76 @@
+use minesweeper::model::Field;
+use minesweeper::share::{self, ShareCodeError};
+
+fn mines(field: &Field) -> Vec<(u32, u32)> {
+    field
+        .active_positions()
+        .filter(|&(x, y)| field.has_mine_at(x, y).unwrap())
+        .collect()
+}
+
+/**
+ * Share codes are posted and typed in by players, so these values must never
+ * change
+ */
+#[test]
+fn pinned() {
+    let field = Field::with_mine_placements(9, 9, vec![(0, 0), (4, 4), (8, 8)]).unwrap();
+    assert_eq!(field.to_share_code(), "CQkAAScBJwHswQ");
+    let field = Field::with_mine_placements(2, 2, vec![(1, 1)]).unwrap();
+    assert_eq!(field.to_share_code(), "AgIDAUlv");
+}
+
+#[test]
+fn round_trips() {
+    for seed in 0..50 {
+        let field = Field::new_with_seed(16, 16, 40, seed).unwrap();
+        let shared = Field::from_share_code(&field.to_share_code()).unwrap();
+        assert_eq!((shared.width(), shared.height()), (16, 16));
+        assert_eq!(mines(&shared), mines(&field), "seed {}", seed);
+        assert_eq!(shared.layout_fingerprint(), field.layout_fingerprint());
+    }
+}
+
+#[test]
+fn refuses_mangled_codes() {
+    let code = "CQkAAScBJwHswQ";
+    assert_eq!(
+        Field::from_share_code("CQkAA!cBJwHswQ").err(),
+        Some(ShareCodeError::InvalidChar {
+            index: 5,
+            found: '!',
+        })
+    );
+    for index in 0..code.len() {
+        let mut typo = code.to_string().into_bytes();
+        typo[index] = if typo[index] == b'A' { b'B' } else { b'A' };
+        let typo = String::from_utf8(typo).unwrap();
+        assert!(Field::from_share_code(&typo).is_err(), "typo at {}", index);
+    }
+    for len in 0..code.len() {
+        assert!(
+            Field::from_share_code(&code[..len]).is_err(),
+            "cut to {}",
+            len
+        );
+    }
+    assert_eq!(
+        Field::from_share_code("CQkAAScBJwHswA").err(),
+        Some(ShareCodeError::ChecksumMismatch)
+    );
+}
+
+#[test]
+fn refuses_codes_that_describe_no_board() {
+    assert_eq!(
+        share::decode(&share::encode(u32::MAX, u32::MAX, vec![])).err(),
+        Some(ShareCodeError::TooLarge {
+            width: u32::MAX,
+            height: u32::MAX,
+        })
+    );
+    assert_eq!(
+        share::decode(&share::encode(3, 3, vec![false; 4])).err(),
+        Some(ShareCodeError::Malformed)
+    );
+}
@@ -0,0 +1,25 @@
+#![cfg(feature = "std")]
+
+use minesweeper::controller::MinesweeperController;
+use minesweeper::model::Field;
+
+#[test]
+fn replays_flags_placed_before_the_mines() {
+    let field = Field::builder()
+        .width(5)
+        .height(2)
+        .mines(1)
+        .seed(14800844837621162358)
+        .first_click_safe(true)
+        .build()
+        .unwrap();
+    let mut game = MinesweeperController::new(field);
+    game.set_question_marks(true);
+    game.cycle_mark_at(3, 1).unwrap();
+    // the mine lands under the flag, which wins the game
+    game.reveal_zone_at(1, 0).unwrap();
+    assert!(game.won());
+    assert_eq!(game.model().has_mine_at(3, 1), Some(true));
+    assert_eq!(game.guess_count(), 1);
+    assert_eq!(game.safe_streak(), 0);
+}